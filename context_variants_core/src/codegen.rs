@@ -0,0 +1,2942 @@
+//! Generating the base struct, its variants and their impls
+
+use super::*;
+
+/// A field's attributes on a variant, with its docs copied, dropped or replaced by a link.
+fn variant_field_attrs(attrs: &[Attribute], ident: &Ident, struct_name: &Ident, docs: FieldDocs) -> Vec<Attribute> {
+    let is_doc = |attr: &&Attribute| attr.path().is_ident("doc");
+    if docs == FieldDocs::Copy {
+        return attrs.to_vec();
+    }
+    let mut kept: Vec<Attribute> = attrs.iter().filter(|attr| !is_doc(attr)).cloned().collect();
+    if docs == FieldDocs::Link && attrs.iter().any(|attr| is_doc(&attr)) {
+        let link = format!("See [`{}::{}`].", struct_name, ident.unraw());
+        kept.insert(0, syn::parse_quote! { #[doc = #link] });
+    }
+    kept
+}
+
+/// Performs the expansion of the macro.
+pub fn expand(mut cfg: VariantList, mut input: DeriveInput) -> Result<TokenStream2, syn::Error> {
+    // A base this macro generated is passed through, so re-expanding it can't define the variants twice
+    if input.attrs.iter().any(is_expanded_marker) {
+        input.attrs.retain(|attr| !is_macro_attr(attr, "variants"));
+        return Ok(quote! { #input });
+    }
+
+    // Validate item is a struct with named fields.
+    // Under base_name, the base and everything named after it use the new name
+    let base_ident = cfg.base_name.clone().unwrap_or_else(|| input.ident.clone());
+    let struct_name = &base_ident;
+    // Under optional_base, a renamed base keeps the struct as declared next to it, bridged both ways
+    let strict_ident = (cfg.optional_base && cfg.build_base && cfg.base_name.is_some()).then(|| input.ident.clone());
+    let generics = &input.generics;
+    let base_vis = &input.vis;
+    if let Some(max) = &cfg.max_visibility
+        && visibility_rank(base_vis) > visibility_rank(max) && !cfg.allow_public {
+        emit_error!(base_vis.span(), "`{}` is more public than max_visibility = {}; make the struct less public, or set allow_public = true to generate the variants as {} anyway",
+            quote!(#base_vis), quote!(#max), quote!(#max));
+    }
+    if let Some(wrapper) = &cfg.enum_wrapper
+        && !has_derive(&input.attrs, "Serialize") && !has_derive(&input.attrs, "Deserialize") {
+        emit_error!(wrapper.span(), "enum_wrapper is tagged with #[serde(tag = \"context\")]; derive Serialize or Deserialize on the struct");
+    }
+    check_variant_names(&cfg, struct_name, strict_ident.as_ref());
+    if cfg.serde_try_from && !has_derive(&input.attrs, "Deserialize") {
+        emit_error!(struct_name.span(), "serde_try_from changes how the base deserializes; derive Deserialize on the struct");
+    }
+    if cfg.parse_any && !has_derive(&input.attrs, "Deserialize") {
+        emit_error!(struct_name.span(), "parse_any deserializes each variant in turn; derive Deserialize on the struct");
+    }
+    // max_visibility caps everything generated; the base struct keeps its own visibility
+    let variant_vis = match &cfg.max_visibility {
+        Some(max) => clamp_visibility(base_vis, max),
+        None => base_vis.clone(),
+    };
+    let vis = &variant_vis;
+    if let Some(max) = &cfg.max_visibility {
+        for field in cfg.fluent_contexts.iter_mut().flat_map(|ctx| &mut ctx.verbatim_fields) {
+            field.vis = clamp_visibility(&field.vis, max);
+        }
+    }
+
+    // The errors emitted above are reported along with any analysis error, so none of them are lost
+    let processed_fields = match cfg.analyze(&input) {
+        Ok(fields) => fields,
+        Err(err) => {
+            emit_syn_error(err);
+            return Ok(TokenStream2::new());
+        }
+    };
+    let cfg = &cfg;
+    // Variants list ordered fields first, by position, then the rest in declaration order
+    let mut variant_fields: Vec<&FieldSpec> = processed_fields.iter().collect();
+    variant_fields.sort_by_key(|fs| match &fs.order {
+        Some(order) => (0, order.base10_parse::<u32>().expect("validated when parsed")),
+        None => (1, 0),
+    });
+
+    let (mut struct_attrs, on_convert) = split_struct_attrs(input.attrs, cfg);
+
+    // Option-wrapping is not FFI-safe, so a #[repr(C)] struct keeps every type as declared
+    let repr_c = repr_c_span(&struct_attrs);
+    if let Some(repr_span) = repr_c
+        && cfg.optional_base {
+        emit_error!(repr_span, "optional_base = true wraps fields in Option, which is not FFI-safe under #[repr(C)]");
+    }
+    if repr_c.is_some() {
+        for span in processed_fields.iter().filter_map(|fs| fs.base_optional_span) {
+            emit_error!(span, "#[ctx_base_optional] wraps the field in Option, which is not FFI-safe under #[repr(C)]");
+        }
+    }
+
+    // Secret fields are printed by a redacting Debug impl instead of the derived one
+    let redact_debug = processed_fields.iter().any(|fs| fs.is_secret)
+        && strip_derive(&mut struct_attrs, "Debug").unwrap_or_else(|err| {
+            emit_syn_error(err);
+            false
+        });
+
+    // The post_process hook goes first so it sees every other attribute
+    if let Some(post_process) = &cfg.post_process {
+        struct_attrs.insert(0, syn::parse_quote! { #[#post_process] });
+    }
+
+    // Impls and compile-time checks emitted after all structs
+    let mut impl_tokens = TokenStream2::new();
+    // Configuration that can never take effect is reported as a warning
+    if let Some(span) = cfg.preserve_attr_text {
+        impl_tokens.extend(compile_warning(span, "__PRESERVE_ATTR_TEXT",
+            "preserve_attr_text has no effect: every attribute is passed through as written; remove it"));
+    }
+    for fs in processed_fields.iter().filter(|_| cfg.warn_dead_attrs) {
+        for (span, note) in &fs.dead_attrs {
+            impl_tokens.extend(compile_warning(*span, "__UNREACHABLE_WHEN_ATTRIBUTE", note));
+        }
+    }
+
+    let orig_struct = base_struct(cfg, &input.ident, struct_name, base_vis, generics, &struct_attrs, &processed_fields);
+
+    // Generate variant structs.
+    let mut variant_tokens = TokenStream2::new();
+    // Derives that a `{Variant}Page` can share with its items
+    let page_derives = if cfg.flags.list_in.is_empty() { Vec::new() } else { forwarded_derives(&struct_attrs, &[]) };
+    // is_complete_for_{context}() methods on an optional_base struct
+    let mut completeness_checks = TokenStream2::new();
+    // Fields that get a Has{Field} trait: its signature can't name the struct's generics
+    let accessor_fields: Vec<&FieldSpec> = processed_fields.iter()
+        .filter(|fs| cfg.field_traits && !mentions_generics(&fs.ty, generics))
+        .collect();
+    // Emitted variants and their generated code with the name normalized, for dedup_variants
+    let mut variant_shapes: Vec<(Ident, String)> = Vec::new();
+    // Each generated (not aliased) variant's fields with their types, for cross_conversions
+    let mut variant_field_sets: Vec<(Ident, Vec<(Ident, TokenStream2)>)> = Vec::new();
+    // Fields of the emitted base and variant structs, for report_stats
+    let mut generated_fields = if cfg.build_base { processed_fields.len() } else { 0 };
+    for variant in &cfg.variants {
+        // Build struct name: prefix + variant + suffix, unless the context gives .name(...)
+        let variant_ident = cfg.variant_ident(variant);
+        let variant_name = variant_ident.to_string();
+        let fluent_ctx = cfg.fluent_contexts.iter().find(|ctx| ctx.name == *variant);
+        // Span attribute for the runtime methods generated on this variant
+        let instrument = cfg.instrument.as_ref().map(|path| {
+            let context = variant.to_string();
+            quote! { #[#path::instrument(skip_all, fields(context = #context))] }
+        });
+        let mut fields = VariantFields::collect(cfg, variant, &variant_ident, struct_name, vis, &variant_fields, repr_c);
+        impl_tokens.extend(std::mem::take(&mut fields.warnings));
+
+        // Copy generics and where clause
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+        let variant_attrs = variant_struct_attrs(cfg, variant, &variant_ident, &struct_attrs);
+        let var_fields = &fields.decls;
+        let struct_item = quote! {
+            #vis struct #variant_ident #impl_generics #where_clause {
+                #(#var_fields)*
+            }
+        };
+        // Search aliases such as `Create` and `UserCreate`, so rustdoc finds the variant by context
+        let doc_aliases: Vec<String> = [variant.to_string(), format!("{}{}", struct_name, variant)].into_iter()
+            .filter(|alias| *alias != variant_name)
+            .collect();
+        // Impls of this variant, dropped if it becomes an alias
+        let mut own_impls = TokenStream2::new();
+
+        if let Some(fluent_ctx) = fluent_ctx
+            && !fluent_ctx.invariants.is_empty() {
+            let exclusions: Vec<(&Ident, Exclusion)> = processed_fields.iter()
+                .filter_map(|fs| fs.exclusions.get(&variant.to_string()).map(|reason| (&fs.ident, *reason)))
+                .collect();
+            own_impls.extend(validate_impl(vis, &variant_ident, variant, generics, &fluent_ctx.invariants, &fields.present, &exclusions, cfg.error_messages.as_ref(), instrument.as_ref()));
+        }
+
+        if let Some(fluent_ctx) = fluent_ctx
+            && !fluent_ctx.tags.is_empty() {
+            own_impls.extend(tags_impl(vis, &variant_ident, generics, &fluent_ctx.tags));
+        }
+
+        if let Some(fluent_ctx) = fluent_ctx
+            && !fluent_ctx.key_fields.is_empty() {
+            own_impls.extend(key_impl(vis, struct_name, &variant_ident, generics, &fluent_ctx.key_fields, &processed_fields, &fields.types, cfg.build_base));
+        }
+
+        if cfg.flags.presence_in.contains(variant) {
+            let optional_fields: Vec<&Ident> = fields.present.iter()
+                .filter(|(_, optional)| *optional)
+                .map(|(ident, _)| *ident)
+                .collect();
+            own_impls.extend(presence_impl(vis, &variant_ident, variant, generics, &optional_fields));
+        }
+
+        if cfg.flags.columns_in.contains(variant) {
+            own_impls.extend(columns_impl(vis, &variant_ident, generics, &fields.types));
+        }
+
+        if cfg.flags.list_in.contains(variant) {
+            own_impls.extend(list_page_impl(vis, &variant_ident, generics, &page_derives, cfg.list_cursor.as_ref()));
+        }
+
+        if cfg.flags.canonical_json_in.contains(variant) {
+            own_impls.extend(canonical_json_impl(vis, &variant_ident, generics));
+        }
+
+        if cfg.flags.from_value_in.contains(variant) {
+            let error_ident = context_error_ident(struct_name);
+            own_impls.extend(from_value_impl(vis, &variant_ident, variant, generics, &error_ident, &fields.values));
+        }
+
+        if !fields.computed_helpers.is_empty() {
+            own_impls.extend(computed_helpers_impl(struct_name, generics, &fields.computed_helpers));
+        }
+
+        if cfg.flags.from_ref_in.contains(variant) {
+            own_impls.extend(from_ref_impl(struct_name, &variant_ident, generics, &fields.ref_inits));
+        }
+
+        if cfg.flags.conversions_in.contains(variant) {
+            let strict = cfg.flags.conversions_named.contains(variant) || cfg.serde_try_from;
+            let hook = on_convert.iter().find(|(ctx, _)| ctx == variant).map(|(_, hook)| hook);
+            own_impls.extend(into_base_impl(struct_name, &variant_ident, variant, generics, &processed_fields, &fields.types, strict, hook));
+        }
+
+        if cfg.flags.as_conversions_in.contains(variant) {
+            let strict = cfg.flags.as_conversions_named.contains(variant) || cfg.serde_try_from;
+            let computed: Vec<&Ident> = fluent_ctx.iter().flat_map(|ctx| &ctx.computed_fields).map(|(ident, _, _)| ident).collect();
+            own_impls.extend(as_conversion_impls(struct_name, &variant_ident, variant, generics, &processed_fields, &fields.types, &computed, strict));
+        }
+
+        if cfg.flags.from_parts_in.contains(variant) {
+            let strict = cfg.flags.from_parts_named.contains(variant);
+            own_impls.extend(from_parts_impl(vis, struct_name, &variant_ident, variant, generics, &processed_fields, &fields.types, strict));
+        }
+
+        if cfg.diff.as_ref() == Some(variant) {
+            own_impls.extend(diff_impl(vis, struct_name, &variant_ident, generics, &fields.diff_inits, instrument.as_ref()));
+        }
+
+        if cfg.flags.constructors_in.contains(variant) {
+            own_impls.extend(constructor_impl(vis, &variant_ident, generics, &fields.entries, &cfg.ctor_order.arrange(&fields.types, &fields.entries)));
+        }
+
+        if cfg.flags.builder_in.contains(variant) {
+            own_impls.extend(builder_impl(vis, struct_name, &variant_ident, generics, &fields.entries, &cfg.ctor_order.arrange(&fields.types, &fields.entries)));
+        }
+
+        if cfg.flags.merge_in.contains(variant) {
+            let strict = cfg.flags.merge_named.contains(variant);
+            own_impls.extend(merge_impl(vis, &variant_ident, variant, generics, &fields.types, strict));
+        }
+
+        if cfg.flags.apply_to_in.contains(variant) {
+            own_impls.extend(apply_to_impl(vis, struct_name, &variant_ident, variant, generics, &fields.entries, &fields.flattened, &fields.types, instrument.as_ref()));
+        }
+
+        if cfg.flags.try_from_in.contains(variant) {
+            own_impls.extend(try_from_impl(struct_name, &variant_ident, generics, &fields.try_inits, &fields.try_checked, instrument.as_ref()));
+        }
+
+        if cfg.flags.field_iter_in.contains(variant) {
+            let entry_ident = field_entry_ident(struct_name);
+            own_impls.extend(field_iter_impl(&variant_ident, generics, &entry_ident, &fields.entries));
+        }
+
+        if cfg.flags.field_consts_in.contains(variant) {
+            own_impls.extend(field_consts_impl(vis, &variant_ident, generics, &fields.entries, &processed_fields));
+        }
+
+        if cfg.flags.serialize_required_in.contains(variant) {
+            own_impls.extend(serialize_required_impl(vis, &variant_ident, generics, &fields.entries));
+        }
+
+        if cfg.inherit_defaults {
+            own_impls.extend(inherit_default_impl(struct_name, &variant_ident, generics, &fields.default_inits));
+        }
+
+        if let Some(any_variant) = &cfg.any_variant {
+            let required: Vec<&FieldSpec> = variant_fields.iter().copied()
+                .filter(|fs| fs.required_in.contains(variant) && !fs.never_in.contains(variant))
+                .collect();
+            own_impls.extend(any_variant_impl(any_variant, &variant_ident, variant, generics, &required));
+        }
+
+        // Accessor traits apply where the field is required and keeps its declared type
+        for (fs, required_here) in &fields.entries {
+            let declared = &fs.ty;
+            let declared = quote!(#declared).to_string();
+            let keeps_type = fields.types.iter().any(|(ident, _, ty)| **ident == fs.ident && ty.to_string() == declared);
+            if *required_here && keeps_type && accessor_fields.iter().any(|accessor| accessor.ident == fs.ident) {
+                own_impls.extend(field_accessor_impl(&variant_ident, generics, fs));
+            }
+        }
+
+        if cfg.optional_base && cfg.build_base {
+            completeness_checks.extend(completeness_check(vis, variant, &fields.entries));
+        }
+
+        if let Some(trait_path) = &cfg.context_variant {
+            own_impls.extend(context_variant_impl(trait_path, struct_name, &variant_ident, variant, generics));
+        }
+
+        if let Some(trait_path) = &cfg.same_context {
+            let same_fields: Vec<&Ident> = fields.present.iter().map(|(ident, _)| *ident)
+                .chain(fluent_ctx.iter().flat_map(|ctx| &ctx.verbatim_fields).filter_map(|field| field.ident.as_ref()))
+                .collect();
+            for template in &cfg.same_context_families {
+                // Listing every family in each invocation is fine; a variant never converts to itself
+                let other_name = template.value().replace("{}", &variant.to_string());
+                if other_name != variant_name {
+                    let other = Ident::new(&other_name, template.span());
+                    own_impls.extend(same_context_impl(trait_path, &variant_ident, &other, generics, &same_fields));
+                }
+            }
+        }
+
+        if redact_debug {
+            let included = variant_fields.iter().copied().filter(|fs| !fs.never_in.contains(variant));
+            own_impls.extend(redacted_debug_impl(&variant_ident, generics, included));
+        }
+
+        if cfg.flags.assert_auto_traits_in.contains(variant) {
+            own_impls.extend(auto_trait_assertion(quote! { #variant_ident #ty_generics }, variant.span(), generics));
+        }
+
+        // A variant whose code matches an earlier one up to its name becomes an alias.
+        // Companion types are named after the variant, so those variants are always emitted.
+        let has_companions = cfg.flags.presence_in.contains(variant) || cfg.flags.columns_in.contains(variant) || cfg.flags.list_in.contains(variant)
+            || fluent_ctx.is_some_and(|ctx| !ctx.key_fields.is_empty());
+        if cfg.dedup_variants && !has_companions {
+            let shape = variant_shape(quote! { #(#variant_attrs)* #struct_item #own_impls }, &variant_ident);
+            if let Some((original, _)) = variant_shapes.iter().find(|(_, known)| *known == shape) {
+                variant_tokens.extend(quote! {
+                    #(#[doc(alias = #doc_aliases)])*
+                    #[allow(type_alias_bounds)]
+                    #vis type #variant_ident #impl_generics = #original #ty_generics;
+                });
+                continue;
+            }
+            variant_shapes.push((variant_ident.clone(), shape));
+        }
+        if cfg.cross_conversions {
+            let field_set = fields.types.iter().map(|(ident, _, ty)| ((*ident).clone(), ty.clone())).collect();
+            variant_field_sets.push((variant_ident.clone(), field_set));
+        }
+        generated_fields += fields.decls.len();
+        variant_tokens.extend(quote! {
+            #(#variant_attrs)*
+            #(#[doc(alias = #doc_aliases)])*
+            #struct_item
+        });
+        impl_tokens.extend(own_impls);
+    }
+
+    // registry = path: one `path!(Base, Context, Variant);` per context, e.g. an inventory::submit! wrapper
+    if let Some(registry) = &cfg.registry {
+        for variant in &cfg.variants {
+            let variant_ident = cfg.variant_ident(variant);
+            impl_tokens.extend(quote! { #registry!(#struct_name, #variant, #variant_ident); });
+        }
+    }
+
+    if let Some(wrapper) = &cfg.enum_wrapper {
+        impl_tokens.extend(enum_wrapper_impl(vis, struct_name, wrapper, cfg, generics, &forwarded_derives(&struct_attrs, WRAPPER_EXCLUDED_DERIVES)));
+        if cfg.serde_try_from {
+            impl_tokens.extend(serde_try_from_impl(struct_name, wrapper, cfg, generics));
+        }
+    }
+
+    if !completeness_checks.is_empty() {
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+        impl_tokens.extend(quote! {
+            #[allow(dead_code)]
+            impl #impl_generics #struct_name #ty_generics #where_clause {
+                #completeness_checks
+            }
+        });
+    }
+
+    for fs in &accessor_fields {
+        impl_tokens.extend(field_accessor_trait(vis, struct_name, fs));
+        if cfg.build_base && !fs.base_wrapped {
+            impl_tokens.extend(field_accessor_impl(struct_name, generics, fs));
+        }
+    }
+
+    if cfg.parse_any {
+        impl_tokens.extend(parse_any_impl(vis, struct_name, cfg, generics, &forwarded_derives(&struct_attrs, WRAPPER_EXCLUDED_DERIVES)));
+    }
+
+    if let Some(style) = cfg.for_each_macro {
+        impl_tokens.extend(for_each_context_macro(struct_name, cfg, style));
+    }
+
+    for (source, source_fields) in &variant_field_sets {
+        for (target, target_fields) in variant_field_sets.iter().filter(|(target, _)| target != source) {
+            impl_tokens.extend(cross_conversion_impl(source, source_fields, target, target_fields, generics));
+        }
+    }
+
+    for fluent_ctx in &cfg.fluent_contexts {
+        if let Some((envelope, wrapped)) = &fluent_ctx.envelope {
+            impl_tokens.extend(envelope_alias(vis, cfg, &fluent_ctx.name, envelope, wrapped.as_ref(), generics));
+        }
+    }
+
+    // One error type per invocation, shared by every variant's from_value()
+    if !cfg.flags.from_value_in.is_empty() {
+        impl_tokens.extend(context_error_type(vis, struct_name));
+    }
+    if !cfg.flags.field_iter_in.is_empty() {
+        impl_tokens.extend(field_entry_type(vis, struct_name));
+    }
+    if !cfg.flags.as_conversions_in.is_empty() {
+        impl_tokens.extend(conversion_error_type(vis, struct_name));
+    }
+    if !cfg.flags.try_from_in.is_empty() || !cfg.flags.builder_in.is_empty() || strict_ident.is_some() {
+        impl_tokens.extend(missing_fields_type(vis, struct_name));
+    }
+    if let Some(strict) = &strict_ident {
+        impl_tokens.extend(strict_base_impl(strict, struct_name, &struct_attrs, base_vis, generics, &processed_fields));
+        if redact_debug {
+            impl_tokens.extend(redacted_debug_impl(strict, generics, processed_fields.iter()));
+        }
+    }
+
+    if cfg.build_base && redact_debug {
+        impl_tokens.extend(redacted_debug_impl(struct_name, generics, processed_fields.iter()));
+    }
+
+    // The base struct is checked too when assertions are enabled for every context
+    if cfg.build_base && cfg.flags.assert_auto_traits_base {
+        let (_, ty_generics, _) = generics.split_for_impl();
+        impl_tokens.extend(auto_trait_assertion(quote! { #struct_name #ty_generics }, struct_name.span(), generics));
+    }
+
+    // Compose final tokens
+    let expanded = if cfg.build_base {
+        quote! {
+            #orig_struct
+            #variant_tokens
+            #impl_tokens
+        }
+    } else {
+        quote! {
+            #variant_tokens
+            #impl_tokens
+        }
+    };
+    if cfg.report_stats {
+        let report = expansion_stats(struct_name, &expanded, generated_fields);
+        return Ok(quote! { #expanded #report });
+    }
+    Ok(expanded)
+}
+
+/// Every generated struct needs a name of its own
+fn check_variant_names(cfg: &VariantList, struct_name: &Ident, strict_ident: Option<&Ident>) {
+    let mut variant_names: Vec<(Ident, &Ident)> = Vec::new();
+    for variant in &cfg.variants {
+        let name = cfg.variant_ident(variant);
+        if (cfg.build_base && name == *struct_name) || strict_ident == Some(&name) {
+            emit_error!(variant.span(), "the {} variant would be named '{}', like the base struct; give it a different .name(...)", variant, name);
+        } else if let Some((_, other)) = variant_names.iter().find(|(existing, _)| *existing == name) {
+            emit_error!(variant.span(), "{} and {} would both be named '{}'; give one of them a different .name(...)", other, variant, name);
+        }
+        variant_names.push((name, variant));
+    }
+}
+
+/// The struct's attributes without the macro's own, and the `#[ctx_on_convert]` hook of each context
+fn split_struct_attrs(attrs: Vec<Attribute>, cfg: &VariantList) -> (Vec<Attribute>, Vec<(Ident, syn::Path)>) {
+    let mut struct_attrs = Vec::new();
+    let mut on_convert: Vec<(Ident, syn::Path)> = Vec::new();
+    for attr in attrs {
+        if is_macro_attr(&attr, "variants") {
+            // Skip the main macro attribute
+            continue;
+        } else if is_macro_attr(&attr, "ctx_on_convert") {
+            // #[ctx_on_convert(Create = "crate::hooks::fill_user")] runs after From<CreateForm> for User maps the fields
+            let hooks = parse_ctx_on_convert_attribute(&attr, cfg).unwrap_or_else(|err| {
+                emit_syn_error(err);
+                Vec::new()
+            });
+            for (ctx, hook) in hooks {
+                if !cfg.flags.conversions_in.contains(&ctx) {
+                    emit_error!(ctx.span(), "ctx_on_convert runs in the From<{}> impl of conversions; enable conversions for {}", cfg.variant_ident(&ctx), ctx);
+                } else if on_convert.iter().any(|(existing, _)| *existing == ctx) {
+                    emit_error!(ctx.span(), "{} already has a ctx_on_convert hook", ctx);
+                } else {
+                    on_convert.push((ctx, hook));
+                }
+            }
+        } else {
+            struct_attrs.push(attr);
+        }
+    }
+    (struct_attrs, on_convert)
+}
+
+/// The base struct: its fields without the field-level macro attributes, and docs linking the variants
+fn base_struct(
+    cfg: &VariantList,
+    declared_name: &Ident,
+    struct_name: &Ident,
+    vis: &Visibility,
+    generics: &syn::Generics,
+    struct_attrs: &[Attribute],
+    fields: &[FieldSpec],
+) -> TokenStream2 {
+    let where_clause = &generics.where_clause;
+    let field_tokens = fields.iter().map(|fs| {
+        let FieldSpec { ident, ty, vis, attrs, base_attrs, base_wrapped, .. } = fs;
+
+        // Under optional_base (or #[ctx_base_optional]), wrap non-Option types in Option<T>
+        let field_type = if *base_wrapped {
+            quote! { Option<#ty> }
+        } else {
+            quote! { #ty }
+        };
+
+        quote! {
+            #(#attrs)*
+            #(#base_attrs)*
+            #vis #ident : #field_type,
+        }
+    });
+
+    // The base struct's docs list its variants, as an anchor for rustdoc
+    let variant_links: Vec<String> = cfg.variants.iter()
+        .map(|variant| format!("[`{}`]", cfg.variant_ident(variant)))
+        .collect();
+    let variants_doc = format!("Generated context variants: {}.", variant_links.join(", "));
+    let has_docs = struct_attrs.iter().any(|attr| attr.path().is_ident("doc"));
+    let doc_separator = has_docs.then(|| quote! { #[doc = ""] });
+    let base_struct_attrs = &cfg.base_struct_attrs;
+    let plan_doc = if cfg.explain { field_plan_doc(cfg, fields) } else { Vec::new() };
+    // The annotated name stays searchable after base_name renames the struct
+    let working_name = cfg.base_name.is_some().then(|| {
+        let name = declared_name.to_string();
+        quote! { #[doc(alias = #name)] }
+    });
+    // serde_try_from routes deserializing the base through the wrapper enum
+    let serde_glue = cfg.enum_wrapper.as_ref().filter(|_| cfg.serde_try_from).map(|wrapper| {
+        let (_, ty_generics, _) = generics.split_for_impl();
+        let source = quote!(#wrapper #ty_generics).to_string();
+        quote! { #[serde(try_from = #source)] }
+    });
+    quote! {
+        #(#struct_attrs)*
+        #(#base_struct_attrs)*
+        #serde_glue
+        #doc_separator
+        #[doc = #variants_doc]
+        #(#[doc = #plan_doc])*
+        #working_name
+        #[doc(alias = #EXPANDED_MARKER)]
+        #vis struct #struct_name #generics #where_clause {
+            #(#field_tokens)*
+        }
+    }
+}
+
+/// A variant's fields, and the per-field pieces of the impls generated for it
+#[derive(Default)]
+struct VariantFields<'a> {
+    /// Field declarations of the variant struct
+    decls: Vec<TokenStream2>,
+    /// Fields present in this variant and whether their type is Option<...>
+    present: Vec<(&'a Ident, bool)>,
+    /// Visibility and final type of each field, for companion types
+    types: Vec<(&'a Ident, &'a Visibility, TokenStream2)>,
+    /// Fields and whether each is required here, for field_iter
+    entries: Vec<(&'a FieldSpec, bool)>,
+    /// Field, its type and whether it may be absent, for from_value()
+    values: Vec<(&'a FieldSpec, TokenStream2, bool)>,
+    /// Field initializers cloned from a borrowed base, for from_ref
+    ref_inits: Vec<TokenStream2>,
+    /// Field initializers moved out of an owned base, and the fields whose `None` fails, for try_from
+    try_inits: Vec<TokenStream2>,
+    try_checked: Vec<&'a Ident>,
+    /// Field initializers from the newer base, `None` where an optional field is unchanged, for diff
+    diff_inits: Vec<TokenStream2>,
+    /// Field initializers for the inherited Default impl
+    default_inits: Vec<TokenStream2>,
+    /// Fields replaced by their nested struct's fields here, from #[ctx_flatten_into]
+    flattened: Vec<&'a FieldSpec>,
+    /// Base methods computing the variant's computed fields
+    computed_helpers: Vec<TokenStream2>,
+    /// Warnings for fields that stay Option under default(required)
+    warnings: TokenStream2,
+}
+
+impl<'a> VariantFields<'a> {
+    /// The base fields `variant` keeps, in variant order, then its verbatim and computed fields
+    fn collect(
+        cfg: &'a VariantList,
+        variant: &Ident,
+        variant_ident: &Ident,
+        struct_name: &Ident,
+        vis: &'a Visibility,
+        fields: &[&'a FieldSpec],
+        repr_c: Option<Span>,
+    ) -> Self {
+        let mut collected = Self::default();
+        for fs in fields.iter().copied().filter(|fs| !fs.never_in.contains(variant)) {
+            match fs.flatten_into.as_ref().filter(|flatten| flatten.contexts.contains(variant)) {
+                Some(flatten) => collected.push_flattened(cfg, variant, fs, flatten),
+                None => collected.push_field(cfg, variant, struct_name, fs, repr_c),
+            }
+        }
+        for (sub, _) in collected.flattened.iter().flat_map(|fs| fs.flatten_into.iter().flat_map(|flatten| &flatten.fields)) {
+            if collected.types.iter().filter(|(existing, _, _)| *existing == sub).count() > 1 {
+                emit_error!(sub.span(), "flattened field '{}' is already a field of {}", sub, variant_ident;
+                    help = "add `excludes({})` to {} to drop the base field", sub, variant);
+            }
+        }
+        if let Some(fluent_ctx) = cfg.fluent_contexts.iter().find(|ctx| ctx.name == *variant) {
+            collected.push_verbatim(variant, variant_ident, &fluent_ctx.verbatim_fields);
+            collected.push_computed(cfg, variant, variant_ident, vis, &fluent_ctx.computed_fields);
+        }
+        collected
+    }
+
+    /// The nested struct's fields stand in for the field, without its attributes such as serde(flatten)
+    fn push_flattened(&mut self, cfg: &VariantList, variant: &Ident, fs: &'a FieldSpec, flatten: &'a FlattenInto) {
+        let ident = &fs.ident;
+        let vis = &fs.variant_vis;
+        for (sub, sub_ty) in &flatten.fields {
+            self.decls.push(quote! { #vis #sub: #sub_ty, });
+            self.types.push((sub, vis, quote! { #sub_ty }));
+            if cfg.flags.from_ref_in.contains(variant) {
+                self.ref_inits.push(quote! { #sub: ::core::clone::Clone::clone(&__base.#ident.#sub) });
+            }
+            if cfg.diff.as_ref() == Some(variant) {
+                self.diff_inits.push(quote! { #sub: ::core::clone::Clone::clone(&__base.#ident.#sub) });
+            }
+            if cfg.flags.try_from_in.contains(variant) {
+                self.try_inits.push(quote! { #sub: __base.#ident.#sub });
+            }
+            if cfg.inherit_defaults {
+                self.default_inits.push(quote! { #sub: __base.#ident.#sub });
+            }
+        }
+        self.flattened.push(fs);
+    }
+
+    /// A base field with the type and attributes it has in `variant`
+    fn push_field(&mut self, cfg: &VariantList, variant: &Ident, struct_name: &Ident, fs: &'a FieldSpec, repr_c: Option<Span>) {
+        let ident = &fs.ident;
+        let vis = &fs.variant_vis;
+        // Fields are optional unless required here
+        let required_here = fs.required_in.contains(variant) && !fs.optional_in.contains(variant);
+
+        // Check if there's a variant-specific type for this field in this variant
+        let variant_type = fs.variant_types.get(&variant.to_string());
+        if required_here && fs.is_option && variant_type.is_none() && !cfg.allow_option_required
+            && fs.required_by_default.contains(variant) {
+            let note = format!(
+                "field '{}' is required in {} by default(required) but stays Option because the base field is Option; \
+                 write `requires({} as T)` to unwrap it, list it in optional(...), or set allow_option_required = true",
+                ident, variant, ident
+            );
+            self.warnings.extend(compile_warning(ident.span(), "__OPTION_FIELD_STAYS_OPTIONAL", &note));
+        }
+        let encrypted = fs.encrypted_in.contains(variant);
+        let field_type = variant_field_type(fs, variant);
+
+        let ty_tokens: TokenStream2 = if required_here {
+            // An explicit `as` type, a secret and ciphertext are taken verbatim; otherwise apply the context's required wrapper
+            match cfg.required_wrappers.get(&variant.to_string()) {
+                Some(wrapper) if variant_type.is_none() && !fs.is_secret && !encrypted => quote! { #wrapper<#field_type> },
+                _ => quote! { #field_type },
+            }
+        } else if is_option_type(&field_type) || fs.is_option {
+            // If the variant type or original type is Option<...>, preserve it; otherwise wrap in Option
+            quote! { #field_type }
+        } else {
+            if let Some(repr_span) = repr_c {
+                emit_error!(
+                    ident.span(),
+                    "field '{}' is optional in {} and would be wrapped in Option, which is not FFI-safe under #[repr(C)]", ident, variant;
+                    help = "exclude it or make it required in {}, e.g. `requires({} as *const T)` for an FFI-safe nullable type", variant, ident;
+                    note = repr_span => "#[repr(C)] is declared here"
+                );
+            }
+            quote! { ::core::option::Option<#field_type> }
+        };
+
+        let conditional_attrs = context_field_attrs(cfg, fs, variant, required_here);
+        let docs = if fs.skip_variant_docs { FieldDocs::Skip } else { cfg.copy_field_docs };
+        let field_attrs = variant_field_attrs(&fs.attrs, ident, struct_name, docs);
+        let may_be_absent = !required_here || is_option_type(&field_type);
+        self.present.push((ident, may_be_absent));
+        self.types.push((ident, vis, ty_tokens.clone()));
+        self.values.push((fs, ty_tokens.clone(), may_be_absent));
+        self.entries.push((fs, required_here));
+        self.push_base_inits(cfg, variant, fs, &ty_tokens, required_here);
+        self.decls.push(quote! {
+            #(#field_attrs)*
+            #(#conditional_attrs)*
+            #vis #ident : #ty_tokens,
+        });
+    }
+
+    /// How each conversion from the base the variant gets fills a base field of type `ty_tokens` in the variant
+    fn push_base_inits(&mut self, cfg: &VariantList, variant: &Ident, fs: &'a FieldSpec, ty_tokens: &TokenStream2, required_here: bool) {
+        let ident = &fs.ident;
+        let base_ty = base_field_type(fs);
+        let variant_ty: Type = syn::parse2(ty_tokens.clone()).expect("generated field types parse");
+        let encrypt_module = fs.encrypt_with.as_ref()
+            .filter(|_| fs.encrypted_in.contains(variant) && !fs.variant_types.contains_key(&variant.to_string()));
+        if cfg.flags.from_ref_in.contains(variant) {
+            match borrowed_base_init(ident, encrypt_module, &variant_ty, &base_ty) {
+                Some(init) => self.ref_inits.push(quote! { #ident: #init }),
+                None => emit_error!(
+                    ident.span(), "from_ref cannot fill '{}' in {}: the base field is Option but the variant's is not", ident, variant;
+                    help = "make '{}' optional in {}, or leave {} out of from_ref", ident, variant, variant
+                ),
+            }
+        }
+        if cfg.diff.as_ref() == Some(variant) {
+            match borrowed_base_init(ident, encrypt_module, &variant_ty, &base_ty) {
+                Some(init) if required_here => self.diff_inits.push(quote! { #ident: #init }),
+                Some(init) => self.diff_inits.push(quote! {
+                    #ident: if self.#ident != __base.#ident { #init } else { ::core::option::Option::None }
+                }),
+                None => emit_error!(
+                    ident.span(), "diff cannot fill '{}' in {}: the base field is Option but the variant's is not", ident, variant;
+                    help = "make '{}' optional in {}", ident, variant
+                ),
+            }
+        }
+        if cfg.flags.try_from_in.contains(variant) {
+            let init = match (encrypt_module, option_inner_type(&base_ty)) {
+                (Some(module), Some(_)) if !is_option_type(&variant_ty) => {
+                    self.try_checked.push(ident);
+                    Some(quote! { match &__base.#ident { ::core::option::Option::Some(__value) => #module::encrypt(__value), ::core::option::Option::None => unreachable!() } })
+                }
+                (Some(module), _) => encrypt_from_ref(module, ident, &base_ty, &variant_ty),
+                (None, Some(inner)) if !is_option_type(&variant_ty) => {
+                    // A required field left `None` in the base is what the conversion reports
+                    self.try_checked.push(ident);
+                    convert_from_ref(quote! { __value }, inner, &variant_ty).map(|value| quote! {
+                        match __base.#ident { ::core::option::Option::Some(__value) => #value, ::core::option::Option::None => unreachable!() }
+                    })
+                }
+                (None, _) => convert_from_ref(quote! { __base.#ident }, &base_ty, &variant_ty),
+            };
+            match init {
+                Some(init) => self.try_inits.push(quote! { #ident: #init }),
+                None => emit_error!(ident.span(), "try_from cannot fill '{}' in {} from the base field's type", ident, variant;
+                    help = "leave {} out of try_from", variant),
+            }
+        }
+        if cfg.inherit_defaults {
+            // The base value carries over wherever the field keeps the base type
+            let base_ty = quote!(#base_ty);
+            self.default_inits.push(if ty_tokens.to_string() == base_ty.to_string() {
+                quote! { #ident: __base.#ident }
+            } else if required_here {
+                quote! { #ident: ::core::default::Default::default() }
+            } else {
+                quote! { #ident: ::core::option::Option::None }
+            });
+        }
+    }
+
+    /// Fields from .verbatim_field(...) go last, as written; constructors fill them with Default
+    fn push_verbatim(&mut self, variant: &Ident, variant_ident: &Ident, verbatim_fields: &'a [syn::Field]) {
+        for (index, field) in verbatim_fields.iter().enumerate() {
+            let ident = field.ident.as_ref().expect("verbatim fields are named");
+            if verbatim_fields[..index].iter().any(|earlier| earlier.ident.as_ref() == Some(ident)) {
+                emit_error!(ident.span(), "verbatim field '{}' is already a field of {}", ident, variant_ident;
+                    help = "rename one of the verbatim fields");
+                continue;
+            }
+            if self.types.iter().any(|(existing, _, _)| *existing == ident) {
+                emit_error!(ident.span(), "verbatim field '{}' is already a field of {}", ident, variant_ident;
+                    help = "rename the verbatim field, or add `excludes({})` to {} to drop the base field", ident, variant);
+                continue;
+            }
+            let ty = &field.ty;
+            self.decls.push(quote! { #field, });
+            self.types.push((ident, &field.vis, quote! { #ty }));
+            self.ref_inits.push(quote! { #ident: ::core::default::Default::default() });
+            self.diff_inits.push(quote! { #ident: ::core::default::Default::default() });
+            self.try_inits.push(quote! { #ident: ::core::default::Default::default() });
+            self.default_inits.push(quote! { #ident: ::core::default::Default::default() });
+        }
+    }
+
+    /// Computed fields are filled by a helper on the base, which conversions call
+    fn push_computed(&mut self, cfg: &VariantList, variant: &Ident, variant_ident: &Ident, vis: &'a Visibility, computed_fields: &'a [(Ident, Type, syn::Expr)]) {
+        for (ident, ty, value) in computed_fields {
+            if !cfg.build_base {
+                emit_error!(ident.span(), "computed fields are filled from the base struct; remove build_base = false");
+                break;
+            }
+            if self.types.iter().any(|(existing, _, _)| *existing == ident) {
+                emit_error!(ident.span(), "computed field '{}' is already a field of {}", ident, variant_ident;
+                    help = "rename the computed field, or add `excludes({})` to {} to drop the base field", ident, variant);
+                continue;
+            }
+            let helper = computed_helper_ident(variant, ident);
+            self.decls.push(quote! { #vis #ident: #ty, });
+            self.types.push((ident, vis, quote! { #ty }));
+            self.ref_inits.push(quote! { #ident: __base.#helper() });
+            self.diff_inits.push(quote! { #ident: __base.#helper() });
+            // Evaluated before the other fields move out of the base
+            self.default_inits.insert(0, quote! { #ident: __base.#helper() });
+            self.try_inits.insert(0, quote! { #ident: __base.#helper() });
+            self.computed_helpers.push(quote! {
+                #[doc(hidden)]
+                #[allow(dead_code)]
+                fn #helper(&self) -> #ty {
+                    #value
+                }
+            });
+        }
+    }
+}
+
+/// A base field's type in `variant` before any `Option` or required wrapper: its `as` type, its
+/// ciphertext, or the declared (or type_map) type, behind `Arc` where it is shared
+fn variant_field_type(fs: &FieldSpec, variant: &Ident) -> Type {
+    let ty = fs.mapped_types.get(&variant.to_string()).unwrap_or(&fs.ty);
+    if let Some(variant_type) = fs.variant_types.get(&variant.to_string()) {
+        variant_type.clone()
+    } else if let Some(module) = fs.encrypt_with.as_ref().filter(|_| fs.encrypted_in.contains(variant)) {
+        // Ciphertext of the declared type, keeping an existing Option on the outside
+        let declared = &fs.ty;
+        match option_inner_type(declared) {
+            Some(inner) => syn::parse_quote! { ::core::option::Option<#module::Encrypted<#inner>> },
+            None => syn::parse_quote! { #module::Encrypted<#declared> },
+        }
+    } else if fs.shared_in.contains(variant) {
+        // Share the value behind Arc, keeping an existing Option on the outside
+        match option_inner_type(ty) {
+            Some(inner) => syn::parse_quote! { ::core::option::Option<::std::sync::Arc<#inner>> },
+            None => syn::parse_quote! { ::std::sync::Arc<#ty> },
+        }
+    } else {
+        ty.clone()
+    }
+}
+
+/// The `when_required`/`when_optional` attributes a field gets in `variant`, then the configured
+/// defaults, the context's scoped attributes and the role marker
+fn context_field_attrs(cfg: &VariantList, fs: &FieldSpec, variant: &Ident, required_here: bool) -> Vec<Attribute> {
+    let mut attrs = if required_here {
+        fs.required_attrs.clone()
+    } else {
+        fs.optional_attrs.clone()
+    };
+    let (default_attrs, scoped_attrs) = if required_here {
+        (&cfg.default_required_attrs, &cfg.scoped_required_attrs)
+    } else {
+        (&cfg.default_optional_attrs, &cfg.scoped_optional_attrs)
+    };
+    attrs.extend(default_attrs.iter().cloned());
+    attrs.extend(scoped_attrs.iter().filter(|(ctx, _)| ctx == variant).flat_map(|(_, attrs)| attrs.iter().cloned()));
+    if let Some(style) = cfg.emit_markers {
+        attrs.push(style.marker(required_here, variant));
+    }
+    attrs
+}
+
+/// The struct's attributes on a variant, with the serde derive of its role, its `.doc(...)` docs and `.derive(...)` derives
+fn variant_struct_attrs(cfg: &VariantList, variant: &Ident, variant_ident: &Ident, struct_attrs: &[Attribute]) -> Vec<Attribute> {
+    let fluent_ctx = cfg.fluent_contexts.iter().find(|ctx| ctx.name == *variant);
+    // Copy all struct attributes to variants
+    let mut variant_attrs: Vec<_> = struct_attrs.to_vec();
+    // A role adds its serde derive unless the struct derives it already; it goes ahead of any
+    // `#[serde(...)]` helper attributes, after the post_process hook
+    if let Some(role) = fluent_ctx.and_then(|ctx| ctx.role) {
+        let (name, derive) = role.serde_derive();
+        if !has_derive(&variant_attrs, name) {
+            variant_attrs.insert(usize::from(cfg.post_process.is_some()), syn::parse_quote! { #[derive(#derive)] });
+        }
+    }
+    // Docs from .doc(...) replace the struct's `///` docs; doc(hidden) and the like stay
+    let context_docs = fluent_ctx.map(|ctx| ctx.docs.as_slice()).unwrap_or_default();
+    if !context_docs.is_empty() {
+        variant_attrs.retain(|attr| !(attr.path().is_ident("doc") && matches!(attr.meta, Meta::NameValue(_))));
+        let at = usize::from(cfg.post_process.is_some());
+        variant_attrs.splice(at..at, context_docs.iter().map(|line| -> Attribute { syn::parse_quote! { #[doc = #line] } }));
+    }
+    // Derives from .derive(...) go in the same place, so serde helper attributes come after them
+    let extra_derives = fluent_ctx.map(|ctx| ctx.derives.as_slice()).unwrap_or_default();
+    for derive in extra_derives {
+        let name = derive.segments.last().map(|last| last.ident.to_string()).unwrap_or_default();
+        if has_derive(&variant_attrs, &name) {
+            emit_error!(derive.span(), "{} already derives {}; remove it from derive()", variant_ident, name);
+        }
+    }
+    if !extra_derives.is_empty() {
+        variant_attrs.insert(usize::from(cfg.post_process.is_some()), syn::parse_quote! { #[derive(#(#extra_derives),*)] });
+    }
+    variant_attrs
+}
+
+/// Base methods computing a variant's computed fields, for its conversions to call
+fn computed_helpers_impl(struct_name: &Ident, generics: &syn::Generics, helpers: &[TokenStream2]) -> TokenStream2 {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    quote! {
+        impl #impl_generics #struct_name #ty_generics #where_clause {
+            #(#helpers)*
+        }
+    }
+}
+
+/// `From<&Base>` for the variant, cloning each field out of the base
+fn from_ref_impl(struct_name: &Ident, variant_ident: &Ident, generics: &syn::Generics, inits: &[TokenStream2]) -> TokenStream2 {
+    let (_, ty_generics, _) = generics.split_for_impl();
+    let mut ref_generics = generics.clone();
+    for param in ref_generics.type_params_mut() {
+        param.bounds.push(syn::parse_quote!(::core::clone::Clone));
+    }
+    let (impl_generics, _, where_clause) = ref_generics.split_for_impl();
+    quote! {
+        impl #impl_generics ::core::convert::From<&#struct_name #ty_generics> for #variant_ident #ty_generics #where_clause {
+            fn from(__base: &#struct_name #ty_generics) -> Self {
+                Self {
+                    #(#inits,)*
+                }
+            }
+        }
+    }
+}
+
+/// `Base::diff(&self, other)`, returning the variant that turns `self` into `other`
+fn diff_impl(vis: &Visibility, struct_name: &Ident, variant_ident: &Ident, generics: &syn::Generics, inits: &[TokenStream2], instrument: Option<&TokenStream2>) -> TokenStream2 {
+    let (_, ty_generics, _) = generics.split_for_impl();
+    let mut diff_generics = generics.clone();
+    for param in diff_generics.type_params_mut() {
+        param.bounds.push(syn::parse_quote!(::core::clone::Clone));
+        param.bounds.push(syn::parse_quote!(::core::cmp::PartialEq));
+    }
+    let (impl_generics, _, where_clause) = diff_generics.split_for_impl();
+    let doc = format!("The {} turning `self` into `other`: optional fields are `Some` only where they differ", variant_ident);
+    quote! {
+        #[allow(dead_code)]
+        impl #impl_generics #struct_name #ty_generics #where_clause {
+            #[doc = #doc]
+            #instrument
+            #vis fn diff(&self, other: &Self) -> #variant_ident #ty_generics {
+                let __base = other;
+                #variant_ident {
+                    #(#inits,)*
+                }
+            }
+        }
+    }
+}
+
+/// `TryFrom<Base>` for the variant, failing with the required fields that are `None` in the base
+fn try_from_impl(struct_name: &Ident, variant_ident: &Ident, generics: &syn::Generics, inits: &[TokenStream2], checked: &[&Ident], instrument: Option<&TokenStream2>) -> TokenStream2 {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let error_ident = missing_fields_ident(struct_name);
+    let variant_name = variant_ident.to_string();
+    let checked_names: Vec<String> = checked.iter().map(|ident| ident.unraw().to_string()).collect();
+    quote! {
+        impl #impl_generics ::core::convert::TryFrom<#struct_name #ty_generics> for #variant_ident #ty_generics #where_clause {
+            type Error = #error_ident;
+
+            #instrument
+            fn try_from(__base: #struct_name #ty_generics) -> ::core::result::Result<Self, Self::Error> {
+                let mut __missing = ::std::vec::Vec::new();
+                #(
+                    if __base.#checked.is_none() {
+                        __missing.push(#checked_names);
+                    }
+                )*
+                if !__missing.is_empty() {
+                    return ::core::result::Result::Err(#error_ident { variant: #variant_name, fields: __missing });
+                }
+                ::core::result::Result::Ok(Self {
+                    #(#inits,)*
+                })
+            }
+        }
+    }
+}
+
+/// `REQUIRED_FIELDS`, `OPTIONAL_FIELDS` and `EXCLUDED_FIELDS`: the base fields by their role in the variant
+fn field_consts_impl(vis: &Visibility, variant_ident: &Ident, generics: &syn::Generics, entries: &[(&FieldSpec, bool)], fields: &[FieldSpec]) -> TokenStream2 {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let names = |required: bool| -> Vec<String> {
+        entries.iter()
+            .filter(|(_, required_here)| *required_here == required)
+            .map(|(fs, _)| fs.ident.unraw().to_string())
+            .collect()
+    };
+    let (required, optional) = (names(true), names(false));
+    let excluded: Vec<String> = fields.iter()
+        .filter(|fs| !entries.iter().any(|(entry, _)| entry.ident == fs.ident))
+        .map(|fs| fs.ident.unraw().to_string())
+        .collect();
+    quote! {
+        #[allow(dead_code)]
+        impl #impl_generics #variant_ident #ty_generics #where_clause {
+            /// Fields of the base struct required in this context, in the variant's field order
+            #vis const REQUIRED_FIELDS: &'static [&'static str] = &[#(#required),*];
+            /// Fields of the base struct that are optional in this context
+            #vis const OPTIONAL_FIELDS: &'static [&'static str] = &[#(#optional),*];
+            /// Fields of the base struct left out of this context, in declaration order
+            #vis const EXCLUDED_FIELDS: &'static [&'static str] = &[#(#excluded),*];
+        }
+    }
+}
+
+/// `Default` for the variant, built from the base struct's `Default`
+fn inherit_default_impl(struct_name: &Ident, variant_ident: &Ident, generics: &syn::Generics, inits: &[TokenStream2]) -> TokenStream2 {
+    let (_, ty_generics, _) = generics.split_for_impl();
+    let mut default_generics = generics.clone();
+    for param in default_generics.type_params_mut() {
+        param.bounds.push(syn::parse_quote!(::core::default::Default));
+    }
+    let (impl_generics, _, where_clause) = default_generics.split_for_impl();
+    quote! {
+        impl #impl_generics ::core::default::Default for #variant_ident #ty_generics #where_clause {
+            fn default() -> Self {
+                let __base = <#struct_name #ty_generics as ::core::default::Default>::default();
+                Self {
+                    #(#inits,)*
+                }
+            }
+        }
+    }
+}
+
+/// `is_complete_for_{context}()` on an optional_base struct
+fn completeness_check(vis: &Visibility, context: &Ident, entries: &[(&FieldSpec, bool)]) -> TokenStream2 {
+    let method = Ident::new(&format!("is_complete_for_{}", snake_case(context)), context.span());
+    let checked: Vec<&Ident> = entries.iter().filter(|(fs, required_here)| *required_here && fs.base_wrapped).map(|(fs, _)| &fs.ident).collect();
+    let body = if checked.is_empty() { quote! { true } } else { quote! { #(self.#checked.is_some())&&* } };
+    let doc = format!("Whether every field the {} context requires is `Some`", context);
+    quote! {
+        #[doc = #doc]
+        #vis fn #method(&self) -> bool {
+            #body
+        }
+    }
+}
+
+/// The user's `context_variant` trait for the variant, naming its base and context
+fn context_variant_impl(trait_path: &syn::Path, struct_name: &Ident, variant_ident: &Ident, context: &Ident, generics: &syn::Generics) -> TokenStream2 {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let context = context.to_string();
+    quote! {
+        impl #impl_generics #trait_path for #variant_ident #ty_generics #where_clause {
+            type Base = #struct_name #ty_generics;
+            const CONTEXT: &'static str = #context;
+        }
+    }
+}
+
+/// Report the size of an expansion as a warning at the struct name.
+fn expansion_stats(struct_name: &Ident, expanded: &TokenStream2, fields: usize) -> TokenStream2 {
+    let mut structs = 0;
+    let mut aliases = 0;
+    let mut impls = 0;
+    // Items are top-level tokens; nested `impl Trait` and companion internals sit inside groups
+    for tree in expanded.clone() {
+        if let proc_macro2::TokenTree::Ident(ident) = tree {
+            match ident.to_string().as_str() {
+                "struct" => structs += 1,
+                "type" => aliases += 1,
+                "impl" => impls += 1,
+                _ => {}
+            }
+        }
+    }
+    let count = |count: usize, one: &str, many: &str| format!("{} {}", count, if count == 1 { one } else { many });
+    let note = format!(
+        "context_variants: {} expands to {} ({}), {} and {}",
+        struct_name,
+        count(structs, "struct", "structs"),
+        count(fields, "field", "fields"),
+        count(aliases, "type alias", "type aliases"),
+        count(impls, "impl block", "impl blocks"),
+    );
+    compile_warning(struct_name.span(), "__CONTEXT_VARIANTS_STATS", &note)
+}
+
+/// A warning at `span` on stable: referencing a `#[deprecated]` constant named `name` reports `note`.
+fn compile_warning(span: Span, name: &str, note: &str) -> TokenStream2 {
+    let warning_ident = Ident::new(name, span);
+    quote::quote_spanned! {span=>
+        const _: () = {
+            #[deprecated(note = #note)]
+            const #warning_ident: () = ();
+            #warning_ident
+        };
+    }
+}
+
+/// Emit a compile-time check that `target` is `Send + Sync`, reported at `span`.
+/// Generic parameters are assumed `Send + Sync`, so the check is about the fields themselves.
+fn auto_trait_assertion(target: TokenStream2, span: Span, generics: &syn::Generics) -> TokenStream2 {
+    let mut check_generics = generics.clone();
+    for param in check_generics.type_params_mut() {
+        param.bounds.push(syn::parse_quote!(::core::marker::Send));
+        param.bounds.push(syn::parse_quote!(::core::marker::Sync));
+    }
+    let (impl_generics, _, where_clause) = check_generics.split_for_impl();
+    let assert_call = quote::quote_spanned! {span=> __assert_send_sync::<#target>(); };
+    quote! {
+        const _: () = {
+            fn __assert_send_sync<T: ?::core::marker::Sized + ::core::marker::Send + ::core::marker::Sync>() {}
+            #[allow(dead_code)]
+            fn __assert_auto_traits #impl_generics () #where_clause {
+                #assert_call
+            }
+        };
+    }
+}
+
+/// Doc lines with a table of each field's role in each context, for `explain = true`
+fn field_plan_doc(cfg: &VariantList, fields: &[FieldSpec]) -> Vec<String> {
+    let mut lines = vec![
+        String::new(),
+        "Resolved context plan:".to_string(),
+        String::new(),
+        format!("| Field | {} |", cfg.variants.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(" | ")),
+        format!("|---|{}", "---|".repeat(cfg.variants.len())),
+    ];
+    for fs in fields {
+        let cells: Vec<String> = cfg.variants.iter().map(|variant| {
+            if fs.never_in.contains(variant) {
+                return "excluded".to_string();
+            }
+            let role = if !fs.optional_in.contains(variant) && fs.required_in.contains(variant) { "required" } else { "optional" };
+            match fs.variant_types.get(&variant.to_string()) {
+                Some(variant_type) => format!("{} as `{}`", role, quote!(#variant_type).to_string().replace(' ', "")),
+                None => role.to_string(),
+            }
+        }).collect();
+        lines.push(format!("| `{}` | {} |", fs.ident, cells.join(" | ")));
+    }
+    lines
+}
+
+/// `Debug` impl for a generated struct that prints secret fields as `[REDACTED]`.
+fn redacted_debug_impl<'a>(name: &Ident, generics: &syn::Generics, fields: impl Iterator<Item = &'a FieldSpec>) -> TokenStream2 {
+    let mut generics = generics.clone();
+    let type_params: Vec<Ident> = generics.type_params().map(|param| param.ident.clone()).collect();
+    let where_clause = generics.make_where_clause();
+    for param in type_params {
+        where_clause.predicates.push(syn::parse_quote! { #param: ::core::fmt::Debug });
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let name_str = name.to_string();
+    let field_entries = fields.map(|fs| {
+        let ident = &fs.ident;
+        let label = ident.unraw().to_string();
+        if fs.is_secret {
+            quote! { .field(#label, &::core::format_args!("[REDACTED]")) }
+        } else {
+            quote! { .field(#label, &self.#ident) }
+        }
+    });
+
+    quote! {
+        impl #impl_generics ::core::fmt::Debug for #name #ty_generics #where_clause {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                f.debug_struct(#name_str)
+                    #(#field_entries)*
+                    .finish()
+            }
+        }
+    }
+}
+
+/// Generated code of a variant with its own name replaced, so identical variants compare equal.
+fn variant_shape(tokens: TokenStream2, variant_ident: &Ident) -> String {
+    fn normalize(tokens: TokenStream2, variant_ident: &Ident) -> TokenStream2 {
+        tokens.into_iter().map(|tree| match tree {
+            proc_macro2::TokenTree::Ident(ident) if ident == *variant_ident => {
+                proc_macro2::TokenTree::Ident(Ident::new("__Variant", ident.span()))
+            }
+            proc_macro2::TokenTree::Group(group) => {
+                proc_macro2::TokenTree::Group(proc_macro2::Group::new(group.delimiter(), normalize(group.stream(), variant_ident)))
+            }
+            other => other,
+        }).collect()
+    }
+    normalize(tokens, variant_ident).to_string()
+}
+
+/// `UserProfile` becomes `user_profile`
+fn snake_case(name: &Ident) -> String {
+    let mut snake = String::new();
+    for (index, ch) in name.unraw().to_string().chars().enumerate() {
+        if ch.is_uppercase() && index > 0 {
+            snake.push('_');
+        }
+        snake.extend(ch.to_lowercase());
+    }
+    snake
+}
+
+/// `for_each_{struct}_context!(mac)`, which expands to `mac!(Context, Variant);` for every context.
+fn for_each_context_macro(struct_name: &Ident, cfg: &VariantList, style: ForEachMacro) -> TokenStream2 {
+    let macro_ident = Ident::new(&format!("for_each_{}_context", snake_case(struct_name)), struct_name.span());
+    let calls = cfg.variants.iter().map(|variant| {
+        let variant_ident = cfg.variant_ident(variant);
+        quote! { $($mac)::+!(#variant, #variant_ident); }
+    });
+    let export = (style == ForEachMacro::Export).then(|| quote! { #[macro_export] });
+    let doc = format!("Invokes `mac!(Context, Variant);` once for each context of [`{}`].", struct_name);
+    quote! {
+        #[doc = #doc]
+        #export
+        macro_rules! #macro_ident {
+            ($($mac:ident)::+) => {
+                #(#calls)*
+            };
+        }
+    }
+}
+
+/// `type {Context}Response = Envelope<{Variant}>;` for a context's `.envelope(...)`.
+fn envelope_alias(vis: &Visibility, cfg: &VariantList, context: &Ident, envelope: &syn::Path, wrapped: Option<&Ident>, generics: &syn::Generics) -> TokenStream2 {
+    let prefix = cfg.prefix.as_deref().unwrap_or_default();
+    let wrapped = wrapped.unwrap_or(context);
+    if !cfg.variants.contains(wrapped) {
+        emit_error!(wrapped.span(), "unknown context '{}' in envelope()", wrapped);
+        return TokenStream2::new();
+    }
+    let alias_name = format!("{}{}Response", prefix, context);
+    if cfg.variants.iter().any(|variant| cfg.variant_ident(variant) == alias_name) {
+        emit_error!(context.span(), "envelope alias '{}' has the same name as a generated variant", alias_name;
+            help = "change the suffix, or the name of one of the contexts");
+        return TokenStream2::new();
+    }
+    let alias_ident = Ident::new(&alias_name, context.span());
+    let variant_ident = cfg.variant_ident(wrapped);
+    let (impl_generics, ty_generics, _) = generics.split_for_impl();
+    let doc = format!("Response of the {} context: [`{}`] in `{}`.", context, variant_ident, quote!(#envelope).to_string().replace(' ', ""));
+    quote! {
+        #[doc = #doc]
+        #[allow(type_alias_bounds, dead_code)]
+        #vis type #alias_ident #impl_generics = #envelope<#variant_ident #ty_generics>;
+    }
+}
+
+/// The struct's derives that a generated wrapper (list page, enum wrapper) can implement from what it holds,
+/// minus the `excluded` ones it can't.
+fn forwarded_derives(attrs: &[Attribute], excluded: &[&str]) -> Vec<syn::Path> {
+    const FORWARDED_DERIVES: &[&str] = &["Debug", "Clone", "PartialEq", "Eq", "Hash", "Default", "Serialize", "Deserialize"];
+    attrs.iter()
+        .filter(|attr| attr.path().is_ident("derive"))
+        .filter_map(|attr| attr.parse_args_with(syn::punctuated::Punctuated::<syn::Path, syn::Token![,]>::parse_terminated).ok())
+        .flatten()
+        .filter(|path| path.segments.last().is_some_and(|last| {
+            let name = last.ident.to_string();
+            FORWARDED_DERIVES.contains(&name.as_str()) && !excluded.contains(&name.as_str())
+        }))
+        .collect()
+}
+
+/// Derives the `enum_wrapper` enum does not forward to its cases
+const WRAPPER_EXCLUDED_DERIVES: &[&str] = &["Hash", "Default"];
+
+/// Enum with one case per context, holding that context's variant and tagged by the context name
+fn enum_wrapper_impl(vis: &Visibility, struct_name: &Ident, wrapper: &Ident, cfg: &VariantList, generics: &syn::Generics, derives: &[syn::Path]) -> TokenStream2 {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let cases = cfg.variants.iter().map(|variant| {
+        let variant_ident = cfg.variant_ident(variant);
+        let doc = format!("The {} context", variant);
+        quote! {
+            #[doc = #doc]
+            #variant(#variant_ident #ty_generics),
+        }
+    });
+    let doc = format!("Any context of [`{}`], tagged by the context name under `\"context\"`.", struct_name);
+    quote! {
+        #[doc = #doc]
+        #[derive(#(#derives),*)]
+        #[serde(tag = "context")]
+        #vis enum #wrapper #impl_generics #where_clause {
+            #(#cases)*
+        }
+    }
+}
+
+/// `TryFrom<Wrapper> for Base` behind `#[serde(try_from)]`: each case is validated, when its context
+/// has invariants, and then converted with its `From` or `TryFrom` impl into the base
+fn serde_try_from_impl(struct_name: &Ident, wrapper: &Ident, cfg: &VariantList, generics: &syn::Generics) -> TokenStream2 {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let arms = cfg.variants.iter().map(|variant| {
+        let validated = cfg.fluent_contexts.iter().any(|ctx| ctx.name == *variant && !ctx.invariants.is_empty());
+        let validate = validated.then(|| quote! { __variant.validate()?; });
+        quote! {
+            #wrapper::#variant(__variant) => {
+                #validate
+                ::core::convert::TryFrom::try_from(__variant).map_err(|err| ::std::string::ToString::to_string(&err))
+            }
+        }
+    });
+    quote! {
+        impl #impl_generics ::core::convert::TryFrom<#wrapper #ty_generics> for #struct_name #ty_generics #where_clause {
+            type Error = ::std::string::String;
+
+            fn try_from(__payload: #wrapper #ty_generics) -> ::core::result::Result<Self, Self::Error> {
+                match __payload {
+                    #(#arms)*
+                }
+            }
+        }
+    }
+}
+
+/// Name of the accessor trait for a field: `id` becomes `HasId`, `created_at` becomes `HasCreatedAt`
+fn field_accessor_ident(field: &Ident) -> Ident {
+    let name: String = field.unraw().to_string().split('_').filter(|part| !part.is_empty()).map(|part| {
+        let mut chars = part.chars();
+        chars.next().map(|first| first.to_uppercase().chain(chars).collect::<String>()).unwrap_or_default()
+    }).collect();
+    Ident::new(&format!("Has{}", name), field.span())
+}
+
+/// `Has{Field}` trait with a by-reference getter for the field's declared type
+fn field_accessor_trait(vis: &Visibility, struct_name: &Ident, fs: &FieldSpec) -> TokenStream2 {
+    let trait_ident = field_accessor_ident(&fs.ident);
+    let ident = &fs.ident;
+    let ty = &fs.ty;
+    let doc = format!("Types with a `{}` field of [`{}`], for code generic over its variants.", ident.unraw(), struct_name);
+    quote! {
+        #[doc = #doc]
+        #vis trait #trait_ident {
+            /// Borrow the field
+            fn #ident(&self) -> &#ty;
+        }
+    }
+}
+
+/// `impl Has{Field} for Target` returning the field by reference
+fn field_accessor_impl(target: &Ident, generics: &syn::Generics, fs: &FieldSpec) -> TokenStream2 {
+    let trait_ident = field_accessor_ident(&fs.ident);
+    let ident = &fs.ident;
+    let ty = &fs.ty;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    quote! {
+        impl #impl_generics #trait_ident for #target #ty_generics #where_clause {
+            fn #ident(&self) -> &#ty {
+                &self.#ident
+            }
+        }
+    }
+}
+
+/// Whether `ty` names one of the struct's type, lifetime or const parameters
+fn mentions_generics(ty: &Type, generics: &syn::Generics) -> bool {
+    fn walk(tokens: TokenStream2, params: &[String]) -> bool {
+        tokens.into_iter().any(|tree| match tree {
+            proc_macro2::TokenTree::Ident(ident) => params.contains(&ident.to_string()),
+            proc_macro2::TokenTree::Group(group) => walk(group.stream(), params),
+            _ => false,
+        })
+    }
+    let params: Vec<String> = generics.params.iter().map(|param| match param {
+        syn::GenericParam::Type(param) => param.ident.to_string(),
+        syn::GenericParam::Lifetime(param) => param.lifetime.ident.to_string(),
+        syn::GenericParam::Const(param) => param.ident.to_string(),
+    }).collect();
+    walk(quote!(#ty), &params)
+}
+
+/// Untagged `{Struct}Any` enum over every context, and `{Struct}::parse_any` trying each in declaration order
+fn parse_any_impl(vis: &Visibility, struct_name: &Ident, cfg: &VariantList, generics: &syn::Generics, derives: &[syn::Path]) -> TokenStream2 {
+    let any_ident = Ident::new(&format!("{}Any", struct_name), struct_name.span());
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let cases = cfg.variants.iter().map(|variant| {
+        let variant_ident = cfg.variant_ident(variant);
+        let doc = format!("Parsed as the {} context", variant);
+        quote! {
+            #[doc = #doc]
+            #variant(#variant_ident #ty_generics),
+        }
+    });
+    let mut parse_generics = generics.clone();
+    parse_generics.make_where_clause().predicates.push(syn::parse_quote!(#any_ident #ty_generics: ::serde::de::DeserializeOwned));
+    let (_, _, parse_where) = parse_generics.split_for_impl();
+    let enum_doc = format!("Any context of [`{}`], recognized by which variant accepts the payload.", struct_name);
+    let fn_doc = format!("Parse `json` as the first context of [`{}`], in declaration order, whose variant deserializes it.", any_ident);
+    quote! {
+        #[doc = #enum_doc]
+        #[derive(#(#derives),*)]
+        #[serde(untagged)]
+        #vis enum #any_ident #impl_generics #where_clause {
+            #(#cases)*
+        }
+
+        #[allow(dead_code)]
+        impl #impl_generics #struct_name #ty_generics #parse_where {
+            #[doc = #fn_doc]
+            #vis fn parse_any(json: &str) -> ::serde_json::Result<#any_ident #ty_generics> {
+                ::serde_json::from_str(json)
+            }
+        }
+    }
+}
+
+/// Listing wrapper `{Variant}Page` with the items and the cursor of the next page.
+fn list_page_impl(vis: &Visibility, variant_ident: &Ident, generics: &syn::Generics, derives: &[syn::Path], cursor: Option<&Ident>) -> TokenStream2 {
+    let page_ident = Ident::new(&format!("{}Page", variant_ident), variant_ident.span());
+    let cursor = cursor.cloned().unwrap_or_else(|| Ident::new("next_cursor", Span::call_site()));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let struct_doc = format!("One page of [`{}`] values.", variant_ident);
+    let derive = (!derives.is_empty()).then(|| quote! { #[derive(#(#derives),*)] });
+    quote! {
+        #[doc = #struct_doc]
+        #derive
+        #vis struct #page_ident #impl_generics #where_clause {
+            /// Items on this page
+            #vis items: ::std::vec::Vec<#variant_ident #ty_generics>,
+            /// Cursor of the next page, `None` on the last page
+            #vis #cursor: ::core::option::Option<::std::string::String>,
+        }
+
+        #[allow(dead_code)]
+        impl #impl_generics #page_ident #ty_generics #where_clause {
+            /// A page of `items` followed by the page at `cursor`
+            #vis fn new(items: ::std::vec::Vec<#variant_ident #ty_generics>, #cursor: ::core::option::Option<::std::string::String>) -> Self {
+                Self { items, #cursor }
+            }
+
+            /// Whether this is the last page
+            #vis fn is_last(&self) -> bool {
+                self.#cursor.is_none()
+            }
+        }
+    }
+}
+
+/// Struct-of-arrays companion `{Variant}Columns` with one `Vec` per field.
+fn columns_impl(vis: &Visibility, variant_ident: &Ident, generics: &syn::Generics, fields: &[(&Ident, &Visibility, TokenStream2)]) -> TokenStream2 {
+    let columns_ident = Ident::new(&format!("{}Columns", variant_ident), variant_ident.span());
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let idents: Vec<&Ident> = fields.iter().map(|(ident, _, _)| *ident).collect();
+    let first = idents.first();
+    let column_fields = fields.iter().map(|(ident, field_vis, ty)| quote! { #field_vis #ident: ::std::vec::Vec<#ty>, });
+    let len = match first {
+        Some(first) => quote! { self.#first.len() },
+        None => quote! { 0 },
+    };
+    let struct_doc = format!("Columnar storage for [`{}`] values, one `Vec` per field.", variant_ident);
+    quote! {
+        #[doc = #struct_doc]
+        #vis struct #columns_ident #impl_generics #where_clause {
+            #(#column_fields)*
+        }
+
+        impl #impl_generics ::core::default::Default for #columns_ident #ty_generics #where_clause {
+            fn default() -> Self {
+                Self { #(#idents: ::std::vec::Vec::new(),)* }
+            }
+        }
+
+        #[allow(dead_code)]
+        impl #impl_generics #columns_ident #ty_generics #where_clause {
+            /// Create empty columns
+            #vis fn new() -> Self {
+                ::core::default::Default::default()
+            }
+
+            /// Number of rows
+            #vis fn len(&self) -> usize {
+                #len
+            }
+
+            /// Whether there are no rows
+            #vis fn is_empty(&self) -> bool {
+                self.len() == 0
+            }
+
+            /// Append one row
+            #vis fn push(&mut self, row: #variant_ident #ty_generics) {
+                #(self.#idents.push(row.#idents);)*
+            }
+
+            /// Turn the columns back into rows
+            #vis fn into_rows(self) -> impl ::core::iter::Iterator<Item = #variant_ident #ty_generics> {
+                let len = self.len();
+                #(let mut #idents = self.#idents.into_iter();)*
+                (0..len).map(move |_| #variant_ident {
+                    #(#idents: #idents.next().expect("columns have equal lengths"),)*
+                })
+            }
+        }
+
+        impl #impl_generics ::core::iter::Extend<#variant_ident #ty_generics> for #columns_ident #ty_generics #where_clause {
+            fn extend<I: ::core::iter::IntoIterator<Item = #variant_ident #ty_generics>>(&mut self, rows: I) {
+                for row in rows {
+                    #(self.#idents.push(row.#idents);)*
+                }
+            }
+        }
+
+        impl #impl_generics ::core::iter::FromIterator<#variant_ident #ty_generics> for #columns_ident #ty_generics #where_clause {
+            fn from_iter<I: ::core::iter::IntoIterator<Item = #variant_ident #ty_generics>>(rows: I) -> Self {
+                let mut columns = Self::default();
+                columns.extend(rows);
+                columns
+            }
+        }
+    }
+}
+
+/// `{Variant}Presence` bit mask over a variant's optional fields, with `presence()` on the variant.
+fn presence_impl(vis: &Visibility, variant_ident: &Ident, context: &Ident, generics: &syn::Generics, optional_fields: &[&Ident]) -> TokenStream2 {
+    let bits: TokenStream2 = match optional_fields.len() {
+        0..=8 => quote! { u8 },
+        9..=16 => quote! { u16 },
+        17..=32 => quote! { u32 },
+        33..=64 => quote! { u64 },
+        65..=128 => quote! { u128 },
+        count => {
+            emit_error!(context.span(), "presence supports at most 128 optional fields, {} has {}", variant_ident, count);
+            return TokenStream2::new();
+        }
+    };
+    let presence_ident = Ident::new(&format!("{}Presence", variant_ident), variant_ident.span());
+    let flags = optional_fields.iter().enumerate().map(|(index, field)| {
+        let name = field.unraw().to_string();
+        let const_ident = Ident::new(&name.to_uppercase(), field.span());
+        let doc = format!("`{}` is set", name);
+        let shift = index as u32;
+        quote! {
+            #[doc = #doc]
+            #vis const #const_ident: Self = Self(1 << #shift);
+        }
+    });
+    let checks = optional_fields.iter().enumerate().map(|(index, field)| {
+        let shift = index as u32;
+        quote! { (#bits::from(self.#field.is_some()) << #shift) }
+    });
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let struct_doc = format!("Which optional fields of [`{}`] are set.", variant_ident);
+    quote! {
+        #[doc = #struct_doc]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+        #vis struct #presence_ident(#bits);
+
+        #[allow(dead_code)]
+        impl #presence_ident {
+            #(#flags)*
+
+            /// No fields set
+            #vis const fn empty() -> Self {
+                Self(0)
+            }
+
+            /// The raw bit mask
+            #vis const fn bits(self) -> #bits {
+                self.0
+            }
+
+            /// Whether no field is set
+            #vis const fn is_empty(self) -> bool {
+                self.0 == 0
+            }
+
+            /// Whether every field in `other` is set
+            #vis const fn contains(self, other: Self) -> bool {
+                self.0 & other.0 == other.0
+            }
+        }
+
+        impl ::core::ops::BitOr for #presence_ident {
+            type Output = Self;
+            fn bitor(self, other: Self) -> Self {
+                Self(self.0 | other.0)
+            }
+        }
+
+        impl ::core::ops::BitAnd for #presence_ident {
+            type Output = Self;
+            fn bitand(self, other: Self) -> Self {
+                Self(self.0 & other.0)
+            }
+        }
+
+        #[allow(dead_code)]
+        impl #impl_generics #variant_ident #ty_generics #where_clause {
+            /// Which optional fields are populated
+            #vis fn presence(&self) -> #presence_ident {
+                #presence_ident(0 #(| #checks)*)
+            }
+        }
+    }
+}
+
+/// `{Variant}Key` holding a context's `.key(...)` fields, ordered and hashable,
+/// with conversions from the variant and, when it is generated, the base struct.
+#[allow(clippy::too_many_arguments)]
+fn key_impl(
+    vis: &Visibility,
+    struct_name: &Ident,
+    variant_ident: &Ident,
+    generics: &syn::Generics,
+    key_fields: &[Ident],
+    fields: &[FieldSpec],
+    field_types: &[(&Ident, &Visibility, TokenStream2)],
+    build_base: bool,
+) -> TokenStream2 {
+    if !generics.params.is_empty() {
+        emit_error!(key_fields[0].span(), "key() is not supported on generic structs");
+        return TokenStream2::new();
+    }
+    let mut members = Vec::new();
+    let mut base_inits = Vec::new();
+    for key in key_fields {
+        let Some(fs) = fields.iter().find(|fs| fs.ident == *key) else {
+            emit_error!(key.span(), "unknown field '{}' in key()", key);
+            continue;
+        };
+        let Some((_, field_vis, ty)) = field_types.iter().find(|(ident, _, _)| *ident == key) else {
+            emit_error!(key.span(), "key field '{}' is excluded from {}", key, variant_ident;
+                help = "key() can only list fields present in the variant");
+            continue;
+        };
+        if fs.is_secret {
+            emit_error!(key.span(), "secret field '{}' cannot be part of a key", key);
+            continue;
+        }
+        if members.iter().any(|(ident, _, _): &(&Ident, _, _)| *ident == key) {
+            emit_error!(key.span(), "duplicate field '{}' in key()", key);
+            continue;
+        }
+        members.push((key, *field_vis, ty.clone()));
+        let base_ty = base_field_type(fs);
+        let key_ty: Type = syn::parse2(ty.clone()).expect("generated field types parse");
+        base_inits.push(convert_from_ref(quote! { __base.#key.clone() }, &base_ty, &key_ty).map(|init| quote! { #key: #init }));
+    }
+    let key_ident = Ident::new(&format!("{}Key", variant_ident), variant_ident.span());
+    let struct_doc = format!("Key fields of [`{}`], for maps and caches.", variant_ident);
+    let definitions = members.iter().map(|(ident, field_vis, ty)| quote! { #field_vis #ident: #ty });
+    let idents: Vec<&Ident> = members.iter().map(|(ident, _, _)| *ident).collect();
+    // The base conversion is left out when a base field cannot fill the key's type
+    let from_base = (build_base && base_inits.iter().all(Option::is_some)).then(|| {
+        let base_inits = base_inits.iter().flatten();
+        quote! {
+            impl ::core::convert::From<&#struct_name> for #key_ident {
+                fn from(__base: &#struct_name) -> Self {
+                    Self { #(#base_inits,)* }
+                }
+            }
+        }
+    });
+    quote! {
+        #[doc = #struct_doc]
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        #vis struct #key_ident {
+            #(#definitions,)*
+        }
+
+        impl ::core::convert::From<&#variant_ident> for #key_ident {
+            fn from(__variant: &#variant_ident) -> Self {
+                Self { #(#idents: __variant.#idents.clone(),)* }
+            }
+        }
+
+        impl #variant_ident {
+            /// This value's key fields
+            #[allow(dead_code)]
+            #vis fn key(&self) -> #key_ident {
+                #key_ident::from(self)
+            }
+        }
+
+        #from_base
+    }
+}
+
+/// `TAG_KEY` associated consts for a context's `.tag(...)` metadata.
+fn tags_impl(vis: &Visibility, variant_ident: &Ident, generics: &syn::Generics, tags: &[(Ident, syn::Lit)]) -> TokenStream2 {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let consts = tags.iter().map(|(key, lit)| {
+        let const_ident = Ident::new(&format!("TAG_{}", key.unraw().to_string().to_uppercase()), key.span());
+        let ty = match lit {
+            syn::Lit::Str(_) => quote! { &'static str },
+            syn::Lit::Int(int) if !int.suffix().is_empty() => {
+                let suffix = Ident::new(int.suffix(), int.span());
+                quote! { #suffix }
+            }
+            syn::Lit::Int(_) => quote! { i64 },
+            syn::Lit::Float(float) if !float.suffix().is_empty() => {
+                let suffix = Ident::new(float.suffix(), float.span());
+                quote! { #suffix }
+            }
+            syn::Lit::Float(_) => quote! { f64 },
+            _ => quote! { bool },
+        };
+        let doc = format!("Value of the `{}` tag", key);
+        quote! {
+            #[doc = #doc]
+            #vis const #const_ident: #ty = #lit;
+        }
+    });
+    quote! {
+        #[allow(dead_code)]
+        impl #impl_generics #variant_ident #ty_generics #where_clause {
+            #(#consts)*
+        }
+    }
+}
+
+/// Name of the error type returned by `from_value()`, e.g. `UserContextError`
+fn context_error_ident(struct_name: &Ident) -> Ident {
+    Ident::new(&format!("{}ContextError", struct_name), struct_name.span())
+}
+
+/// The `{Struct}ContextError` type naming the context and field a JSON value failed on.
+fn context_error_type(vis: &Visibility, struct_name: &Ident) -> TokenStream2 {
+    let error_ident = context_error_ident(struct_name);
+    let doc = format!("Error from `from_value()` on a [`{}`] variant", struct_name);
+    quote! {
+        #[doc = #doc]
+        #[derive(Debug, Clone, PartialEq)]
+        #vis struct #error_ident {
+            /// Context the value was decoded for
+            #vis context: &'static str,
+            /// Serialized name of the offending field, if known
+            #vis field: ::core::option::Option<::std::string::String>,
+            /// What went wrong
+            #vis message: ::std::string::String,
+        }
+
+        impl ::core::fmt::Display for #error_ident {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                match &self.field {
+                    ::core::option::Option::Some(field) => write!(f, "{}: field `{}`: {}", self.context, field, self.message),
+                    ::core::option::Option::None => write!(f, "{}: {}", self.context, self.message),
+                }
+            }
+        }
+
+        impl ::std::error::Error for #error_ident {}
+    }
+}
+
+/// Name of the error from a fallible `as_conversions` impl, e.g. `UserConversionError`
+fn conversion_error_ident(struct_name: &Ident) -> Ident {
+    Ident::new(&format!("{}ConversionError", struct_name), struct_name.span())
+}
+
+/// The `{Struct}ConversionError` naming the field whose changed type failed to convert.
+fn conversion_error_type(vis: &Visibility, struct_name: &Ident) -> TokenStream2 {
+    let error_ident = conversion_error_ident(struct_name);
+    let doc = format!("Error from a `TryFrom` impl of `as_conversions` between [`{}`] and a variant", struct_name);
+    quote! {
+        #[doc = #doc]
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        #vis struct #error_ident {
+            /// Context of the variant being converted to or from
+            #vis context: &'static str,
+            /// Field whose value did not convert
+            #vis field: &'static str,
+            /// The conversion's own error message
+            #vis message: ::std::string::String,
+        }
+
+        impl ::core::fmt::Display for #error_ident {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                write!(f, "{}: field `{}`: {}", self.context, self.field, self.message)
+            }
+        }
+
+        impl ::std::error::Error for #error_ident {}
+    }
+}
+
+/// Name of the error returned by `TryFrom<Base>` for a variant, e.g. `UserMissingFields`
+fn missing_fields_ident(struct_name: &Ident) -> Ident {
+    Ident::new(&format!("{}MissingFields", struct_name), struct_name.span())
+}
+
+/// The `{Struct}MissingFields` error listing the required fields a base value left `None`.
+fn missing_fields_type(vis: &Visibility, struct_name: &Ident) -> TokenStream2 {
+    let error_ident = missing_fields_ident(struct_name);
+    let doc = format!("Error from `TryFrom<{}>` or a builder's `build()` when required fields of a variant are missing", struct_name);
+    quote! {
+        #[doc = #doc]
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        #vis struct #error_ident {
+            /// Name of the variant being built
+            #vis variant: &'static str,
+            /// Required fields that were `None`, in declaration order
+            #vis fields: ::std::vec::Vec<&'static str>,
+        }
+
+        impl ::core::fmt::Display for #error_ident {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                write!(f, "{}: missing required field", self.variant)?;
+                if self.fields.len() > 1 {
+                    f.write_str("s")?;
+                }
+                for (index, field) in self.fields.iter().enumerate() {
+                    write!(f, "{}`{}`", if index == 0 { " " } else { ", " }, field)?;
+                }
+                ::core::result::Result::Ok(())
+            }
+        }
+
+        impl ::std::error::Error for #error_ident {}
+    }
+}
+
+/// Convert an owned base value of type `from` into the variant's type `to`: unchanged when the types
+/// match, with `Some` or `Option::map` around `Into::into` otherwise. `None` if `to` can't hold an absent value.
+fn convert_from_ref(value: TokenStream2, from: &Type, to: &Type) -> Option<TokenStream2> {
+    if type_matches(from, to) {
+        return Some(value);
+    }
+    match (option_inner_type(from), option_inner_type(to)) {
+        (Some(from_inner), Some(to_inner)) => {
+            let inner = convert_from_ref(quote! { __value }, from_inner, to_inner)?;
+            Some(quote! { #value.map(|__value| #inner) })
+        }
+        (None, Some(to_inner)) => {
+            let inner = convert_from_ref(value, from, to_inner)?;
+            Some(quote! { ::core::option::Option::Some(#inner) })
+        }
+        (Some(_), None) => None,
+        (None, None) => Some(quote! { ::core::convert::Into::into(#value) }),
+    }
+}
+
+/// `From<Variant> for Base`, moving each field back. A base field the variant lacks becomes `None`.
+/// A variant that can't fill the base is skipped, or reported when `strict` (named in `conversions = { ... }`);
+/// only then are changed field types converted with `Into`.
+/// A `hook` (from `#[ctx_on_convert]`) implies `strict`, defaults the other excluded fields and then gets
+/// `&mut` the base and the original variant.
+#[allow(clippy::too_many_arguments)]
+fn into_base_impl(
+    struct_name: &Ident,
+    variant_ident: &Ident,
+    context: &Ident,
+    generics: &syn::Generics,
+    fields: &[FieldSpec],
+    field_types: &[(&Ident, &Visibility, TokenStream2)],
+    strict: bool,
+    hook: Option<&syn::Path>,
+) -> TokenStream2 {
+    let strict = strict || hook.is_some();
+    let mut inits = Vec::new();
+    for fs in fields {
+        let ident = &fs.ident;
+        let init = match base_field_from_variant(fs, context, field_types, strict, hook.is_some()) {
+            Some(init) => init,
+            None if is_option_type(&base_field_type(fs)) => Ok(quote! { ::core::option::Option::None }),
+            None if hook.is_some() => Ok(quote! { ::core::default::Default::default() }),
+            None => Err("it is excluded from the variant and not Option in the base"),
+        };
+        match init {
+            Ok(init) => inits.push(quote! { #ident: #init }),
+            Err(reason) => {
+                if strict {
+                    emit_error!(context.span(), "conversions cannot build {} from {}: field '{}' can't be filled because {}", struct_name, variant_ident, ident, reason;
+                        help = "leave {} out of conversions", context);
+                }
+                return TokenStream2::new();
+            }
+        }
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let body = match hook {
+        // The hook sees the variant as it came in, so the mapped fields are cloned out of it
+        Some(hook) => quote! {
+            let __variant = &__original;
+            let mut __base = Self {
+                #(#inits,)*
+            };
+            #hook(&mut __base, &__original);
+            __base
+        },
+        None => quote! {
+            let __variant = __original;
+            Self {
+                #(#inits,)*
+            }
+        },
+    };
+    quote! {
+        impl #impl_generics ::core::convert::From<#variant_ident #ty_generics> for #struct_name #ty_generics #where_clause {
+            fn from(__original: #variant_ident #ty_generics) -> Self {
+                #body
+            }
+        }
+    }
+}
+
+/// The field's type in the base struct, `Option`-wrapped under optional_base
+fn base_field_type(fs: &FieldSpec) -> Type {
+    let declared = &fs.ty;
+    if fs.base_wrapped { syn::parse_quote!(Option<#declared>) } else { declared.clone() }
+}
+
+/// Initializer of a base field from `__variant`, `None` if the variant lacks the field, or why it can't be filled.
+/// A `borrowed` variant is a reference, so the field is cloned out of it.
+fn base_field_from_variant(
+    fs: &FieldSpec,
+    context: &Ident,
+    field_types: &[(&Ident, &Visibility, TokenStream2)],
+    strict: bool,
+    borrowed: bool,
+) -> Option<Result<TokenStream2, &'static str>> {
+    let ident = &fs.ident;
+    if let Some(flatten) = fs.flatten_into.as_ref().filter(|flatten| flatten.contexts.contains(context)) {
+        // Rebuild the nested struct from its hoisted fields
+        let path = &flatten.path;
+        let subs = flatten.fields.iter().map(|(sub, _)| if borrowed {
+            quote! { #sub: ::core::clone::Clone::clone(&__variant.#sub) }
+        } else {
+            quote! { #sub: __variant.#sub }
+        });
+        return Some(Ok(quote! { #path { #(#subs),* } }));
+    }
+    let base_ty = base_field_type(fs);
+    let (_, _, ty) = field_types.iter().find(|(existing, _, _)| *existing == ident)?;
+    if fs.encrypt_with.is_some() && fs.encrypted_in.contains(context) {
+        return Some(Err("it is encrypted in the variant"));
+    }
+    let variant_ty: Type = syn::parse2(ty.clone()).expect("generated field types parse");
+    // A changed type (`as`, a wrapper, type_map) converts with Into only when asked for by name
+    let changed = !type_matches(option_inner_type(&variant_ty).unwrap_or(&variant_ty), option_inner_type(&base_ty).unwrap_or(&base_ty));
+    Some(if changed && !strict {
+        Err("its type differs")
+    } else {
+        let value = if borrowed { quote! { ::core::clone::Clone::clone(&__variant.#ident) } } else { quote! { __variant.#ident } };
+        convert_from_ref(value, &variant_ty, &base_ty)
+            .ok_or("it is optional in the variant but not in the base")
+    })
+}
+
+/// Expression moving `value` from type `from` into `to` for `as_conversions`, and whether it can fail.
+/// `String` targets use `ToString`, `String` sources `str::parse`, other changed types `TryFrom`.
+/// Fallible steps end in `?` with `error` mapping the failure, so the expression always has type `to`.
+fn as_conversion(value: TokenStream2, from: &Type, to: &Type, error: &TokenStream2) -> Option<(TokenStream2, bool)> {
+    if type_matches(from, to) {
+        return Some((value, false));
+    }
+    let string: Type = syn::parse_quote!(String);
+    match (option_inner_type(from), option_inner_type(to)) {
+        (Some(from_inner), Some(to_inner)) => {
+            let (inner, fallible) = as_conversion(quote! { __value }, from_inner, to_inner, error)?;
+            Some((quote! {
+                match #value {
+                    ::core::option::Option::Some(__value) => ::core::option::Option::Some(#inner),
+                    ::core::option::Option::None => ::core::option::Option::None,
+                }
+            }, fallible))
+        }
+        (None, Some(to_inner)) => {
+            let (inner, fallible) = as_conversion(value, from, to_inner, error)?;
+            Some((quote! { ::core::option::Option::Some(#inner) }, fallible))
+        }
+        (Some(_), None) => None,
+        (None, None) if type_matches(&string, to) => Some((quote! { ::std::string::ToString::to_string(&#value) }, false)),
+        (None, None) if type_matches(&string, from) => Some((quote! { ::core::primitive::str::parse::<#to>(&#value).map_err(#error)? }, true)),
+        (None, None) => Some((quote! { <#to as ::core::convert::TryFrom<#from>>::try_from(#value).map_err(#error)? }, true)),
+    }
+}
+
+/// Name of the hidden base method evaluating a computed field, e.g. `__computed_create_slug`
+fn computed_helper_ident(context: &Ident, field: &Ident) -> Ident {
+    Ident::new(&format!("__computed_{}_{}", context.unraw().to_string().to_lowercase(), field.unraw()), field.span())
+}
+
+/// `From` or `TryFrom` impls in both directions between the base and a variant, converting changed field
+/// types with [`as_conversion`]. A direction is `TryFrom` with `{Struct}ConversionError` when any step can fail.
+/// A variant that can't convert is skipped, or reported when `strict` (named in `as_conversions = { ... }`).
+#[allow(clippy::too_many_arguments)]
+fn as_conversion_impls(
+    struct_name: &Ident,
+    variant_ident: &Ident,
+    context: &Ident,
+    generics: &syn::Generics,
+    fields: &[FieldSpec],
+    field_types: &[(&Ident, &Visibility, TokenStream2)],
+    computed: &[&Ident],
+    strict: bool,
+) -> TokenStream2 {
+    let error_ident = conversion_error_ident(struct_name);
+    let context_name = context.to_string();
+    let error_for = |ident: &Ident| {
+        let field = ident.unraw().to_string();
+        quote! {
+            |__err| #error_ident { context: #context_name, field: #field, message: ::std::string::ToString::to_string(&__err) }
+        }
+    };
+    let fail = |direction: &str, ident: &Ident, reason: &str| {
+        if strict {
+            emit_error!(context.span(), "as_conversions cannot convert {}: field '{}' can't be filled because {}", direction, ident, reason;
+                help = "leave {} out of as_conversions", context);
+        }
+        TokenStream2::new()
+    };
+
+    // Base -> variant, with computed fields first so they see the whole base
+    let mut into_variant = Vec::new();
+    let mut into_variant_fallible = false;
+    let into_variant_direction = format!("{} into {}", struct_name, variant_ident);
+    if let Some(fs) = fields.iter().find(|fs| fs.flatten_into.as_ref().is_some_and(|flatten| flatten.contexts.contains(context))) {
+        return fail(&into_variant_direction, &fs.ident, "it is flattened in the variant");
+    }
+    for (ident, _, ty) in field_types {
+        let init = match fields.iter().find(|fs| fs.ident == **ident) {
+            Some(fs) if fs.encrypt_with.is_some() && fs.encrypted_in.contains(context) => {
+                return fail(&into_variant_direction, ident, "it is encrypted in the variant");
+            }
+            Some(fs) => {
+                let variant_ty: Type = syn::parse2(ty.clone()).expect("generated field types parse");
+                match as_conversion(quote! { __base.#ident }, &base_field_type(fs), &variant_ty, &error_for(ident)) {
+                    Some((init, fallible)) => {
+                        into_variant_fallible |= fallible;
+                        init
+                    }
+                    None => return fail(&into_variant_direction, ident, "it is Option in the base but not in the variant"),
+                }
+            }
+            None if computed.contains(ident) => {
+                let helper = computed_helper_ident(context, ident);
+                into_variant.insert(0, quote! { #ident: __base.#helper() });
+                continue;
+            }
+            None => quote! { ::core::default::Default::default() },
+        };
+        into_variant.push(quote! { #ident: #init });
+    }
+
+    // Variant -> base; verbatim and computed fields are dropped
+    let mut into_base = Vec::new();
+    let mut into_base_fallible = false;
+    let into_base_direction = format!("{} into {}", variant_ident, struct_name);
+    for fs in fields {
+        let ident = &fs.ident;
+        let base_ty = base_field_type(fs);
+        let init = match field_types.iter().find(|(existing, _, _)| *existing == ident) {
+            Some(_) if fs.encrypt_with.is_some() && fs.encrypted_in.contains(context) => {
+                return fail(&into_base_direction, ident, "it is encrypted in the variant");
+            }
+            Some((_, _, ty)) => {
+                let variant_ty: Type = syn::parse2(ty.clone()).expect("generated field types parse");
+                match as_conversion(quote! { __variant.#ident }, &variant_ty, &base_ty, &error_for(ident)) {
+                    Some((init, fallible)) => {
+                        into_base_fallible |= fallible;
+                        init
+                    }
+                    None => return fail(&into_base_direction, ident, "it is optional in the variant but not in the base"),
+                }
+            }
+            None if is_option_type(&base_ty) => quote! { ::core::option::Option::None },
+            None => return fail(&into_base_direction, ident, "it is excluded from the variant and not Option in the base"),
+        };
+        into_base.push(quote! { #ident: #init });
+    }
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let variant_ty = quote! { #variant_ident #ty_generics };
+    let base_ty = quote! { #struct_name #ty_generics };
+    let conversion = |from: &TokenStream2, to: &TokenStream2, input: TokenStream2, inits: &[TokenStream2], fallible: bool| if fallible {
+        quote! {
+            impl #impl_generics ::core::convert::TryFrom<#from> for #to #where_clause {
+                type Error = #error_ident;
+                fn try_from(#input: #from) -> ::core::result::Result<Self, Self::Error> {
+                    ::core::result::Result::Ok(Self {
+                        #(#inits,)*
+                    })
+                }
+            }
+        }
+    } else {
+        quote! {
+            impl #impl_generics ::core::convert::From<#from> for #to #where_clause {
+                fn from(#input: #from) -> Self {
+                    Self {
+                        #(#inits,)*
+                    }
+                }
+            }
+        }
+    };
+    let mut tokens = conversion(&base_ty, &variant_ty, quote! { __base }, &into_variant, into_variant_fallible);
+    tokens.extend(conversion(&variant_ty, &base_ty, quote! { __variant }, &into_base, into_base_fallible));
+    tokens
+}
+
+/// `Base::from_{context}(variant, ...)` taking the fields the variant excludes as arguments, in declaration order
+#[allow(clippy::too_many_arguments)]
+fn from_parts_impl(
+    vis: &Visibility,
+    struct_name: &Ident,
+    variant_ident: &Ident,
+    context: &Ident,
+    generics: &syn::Generics,
+    fields: &[FieldSpec],
+    field_types: &[(&Ident, &Visibility, TokenStream2)],
+    strict: bool,
+) -> TokenStream2 {
+    let mut params = Vec::new();
+    let mut inits = Vec::new();
+    for fs in fields {
+        let ident = &fs.ident;
+        match base_field_from_variant(fs, context, field_types, strict, false) {
+            Some(Ok(init)) => inits.push(quote! { #ident: #init }),
+            Some(Err(reason)) => {
+                if strict {
+                    emit_error!(context.span(), "from_parts cannot build {} from {}: field '{}' can't be filled because {}", struct_name, variant_ident, ident, reason;
+                        help = "leave {} out of from_parts", context);
+                }
+                return TokenStream2::new();
+            }
+            None => {
+                let ty = base_field_type(fs);
+                params.push(quote! { #ident: #ty });
+                inits.push(quote! { #ident });
+            }
+        }
+    }
+    let method = Ident::new(&format!("from_{}", snake_case(context)), context.span());
+    let doc = format!("Build {} from a [`{}`] and the fields it leaves out", struct_name, variant_ident);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    quote! {
+        #[allow(dead_code)]
+        impl #impl_generics #struct_name #ty_generics #where_clause {
+            #[doc = #doc]
+            #vis fn #method(__variant: #variant_ident #ty_generics, #(#params),*) -> Self {
+                Self {
+                    #(#inits,)*
+                }
+            }
+        }
+    }
+}
+
+/// `Variant::new(...)` taking every required field in declaration order; optional fields start as `None`
+/// and get a chainable `with_{field}` setter. Verbatim and computed fields have no base field to be
+/// optional for, so they are taken too.
+fn constructor_impl(
+    vis: &Visibility,
+    variant_ident: &Ident,
+    generics: &syn::Generics,
+    entry_fields: &[(&FieldSpec, bool)],
+    field_types: &[&(&Ident, &Visibility, TokenStream2)],
+) -> TokenStream2 {
+    let mut params = Vec::new();
+    let mut inits = Vec::new();
+    let mut setters = Vec::new();
+    for (ident, _, ty) in field_types {
+        let parsed: Type = syn::parse2(ty.clone()).expect("generated field types parse");
+        // An optional field given a non-Option type with `as` is taken like a required one
+        let optional = entry_fields.iter().any(|(fs, required)| fs.ident == **ident && !required);
+        if let Some(inner) = option_inner_type(&parsed).filter(|_| optional) {
+            inits.push(quote! { #ident: ::core::option::Option::None });
+            let setter = Ident::new(&format!("with_{}", ident.unraw()), ident.span());
+            let doc = format!("Set `{}`", ident.unraw());
+            setters.push(quote! {
+                #[doc = #doc]
+                #vis fn #setter(mut self, #ident: #inner) -> Self {
+                    self.#ident = ::core::option::Option::Some(#ident);
+                    self
+                }
+            });
+        } else {
+            params.push(quote! { #ident: #ty });
+            inits.push(quote! { #ident });
+        }
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let doc = format!("Create a {} from its required fields, leaving the optional ones `None`", variant_ident);
+    quote! {
+        #[allow(dead_code)]
+        impl #impl_generics #variant_ident #ty_generics #where_clause {
+            #[doc = #doc]
+            #[allow(clippy::too_many_arguments)]
+            #vis fn new(#(#params),*) -> Self {
+                Self {
+                    #(#inits,)*
+                }
+            }
+
+            #(#setters)*
+        }
+    }
+}
+
+/// `{Variant}Builder` with a setter per field and a `build()` that reports every required field
+/// left unset, plus `{Variant}::builder()`.
+fn builder_impl(
+    vis: &Visibility,
+    struct_name: &Ident,
+    variant_ident: &Ident,
+    generics: &syn::Generics,
+    entry_fields: &[(&FieldSpec, bool)],
+    field_types: &[&(&Ident, &Visibility, TokenStream2)],
+) -> TokenStream2 {
+    let builder_ident = Ident::new(&format!("{}Builder", variant_ident), variant_ident.span());
+    let error_ident = missing_fields_ident(struct_name);
+    let variant_name = variant_ident.to_string();
+    let mut fields = Vec::new();
+    let mut setters = Vec::new();
+    let mut checks = Vec::new();
+    let mut inits = Vec::new();
+    for (ident, _, ty) in field_types {
+        let parsed: Type = syn::parse2(ty.clone()).expect("generated field types parse");
+        // As in constructor_impl, an optional field given a non-Option type with `as` must be set
+        let optional = entry_fields.iter().any(|(fs, required)| fs.ident == **ident && !required);
+        let doc = format!("Set `{}`", ident.unraw());
+        if let Some(inner) = option_inner_type(&parsed).filter(|_| optional) {
+            fields.push(quote! { #ident: #ty });
+            setters.push(quote! {
+                #[doc = #doc]
+                #vis fn #ident(mut self, #ident: #inner) -> Self {
+                    self.#ident = ::core::option::Option::Some(#ident);
+                    self
+                }
+            });
+            inits.push(quote! { #ident: self.#ident });
+        } else {
+            let name = ident.unraw().to_string();
+            fields.push(quote! { #ident: ::core::option::Option<#ty> });
+            setters.push(quote! {
+                #[doc = #doc]
+                #vis fn #ident(mut self, #ident: #ty) -> Self {
+                    self.#ident = ::core::option::Option::Some(#ident);
+                    self
+                }
+            });
+            checks.push(quote! {
+                if self.#ident.is_none() {
+                    __missing.push(#name);
+                }
+            });
+            inits.push(quote! {
+                #ident: match self.#ident {
+                    ::core::option::Option::Some(__value) => __value,
+                    ::core::option::Option::None => unreachable!(),
+                }
+            });
+        }
+    }
+    let unset: Vec<TokenStream2> = field_types.iter()
+        .map(|(ident, _, _)| quote! { #ident: ::core::option::Option::None })
+        .collect();
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let builder_doc = format!("Builder for [`{}`]; `build()` fails if a required field was not set", variant_ident);
+    let entry_doc = format!("Start a [`{}`] with every field unset", builder_ident);
+    quote! {
+        #[doc = #builder_doc]
+        #vis struct #builder_ident #impl_generics #where_clause {
+            #(#fields,)*
+        }
+
+        impl #impl_generics ::core::default::Default for #builder_ident #ty_generics #where_clause {
+            fn default() -> Self {
+                Self {
+                    #(#unset,)*
+                }
+            }
+        }
+
+        #[allow(dead_code)]
+        impl #impl_generics #builder_ident #ty_generics #where_clause {
+            #(#setters)*
+
+            /// Build the variant, listing every required field that was not set
+            #vis fn build(self) -> ::core::result::Result<#variant_ident #ty_generics, #error_ident> {
+                let mut __missing: ::std::vec::Vec<&'static str> = ::std::vec::Vec::new();
+                #(#checks)*
+                if !__missing.is_empty() {
+                    return ::core::result::Result::Err(#error_ident { variant: #variant_name, fields: __missing });
+                }
+                ::core::result::Result::Ok(#variant_ident {
+                    #(#inits,)*
+                })
+            }
+        }
+
+        #[allow(dead_code)]
+        impl #impl_generics #variant_ident #ty_generics #where_clause {
+            #[doc = #entry_doc]
+            #vis fn builder() -> #builder_ident #ty_generics {
+                ::core::default::Default::default()
+            }
+        }
+    }
+}
+
+/// `From<Source> for Target` when every field of the target is in the source, with the same type
+/// or wrapped in `Option`; nothing otherwise.
+fn cross_conversion_impl(
+    source: &Ident,
+    source_fields: &[(Ident, TokenStream2)],
+    target: &Ident,
+    target_fields: &[(Ident, TokenStream2)],
+    generics: &syn::Generics,
+) -> TokenStream2 {
+    let mut inits = Vec::new();
+    for (ident, ty) in target_fields {
+        let Some((_, source_ty)) = source_fields.iter().find(|(existing, _)| existing == ident) else {
+            return TokenStream2::new();
+        };
+        let source_ty = source_ty.to_string();
+        if source_ty == ty.to_string() {
+            inits.push(quote! { #ident: __source.#ident });
+            continue;
+        }
+        let target_ty: Type = syn::parse2(ty.clone()).expect("generated field types parse");
+        match option_inner_type(&target_ty) {
+            Some(inner) if quote!(#inner).to_string() == source_ty => {
+                inits.push(quote! { #ident: ::core::option::Option::Some(__source.#ident) });
+            }
+            _ => return TokenStream2::new(),
+        }
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    quote! {
+        impl #impl_generics ::core::convert::From<#source #ty_generics> for #target #ty_generics #where_clause {
+            fn from(__source: #source #ty_generics) -> Self {
+                Self {
+                    #(#inits,)*
+                }
+            }
+        }
+    }
+}
+
+/// `merge` and `merge_from` on a variant whose fields are all `Option`, with the right-hand `Some` winning.
+/// Other variants are skipped, or reported when `strict` (named in `merge = { ... }`).
+fn merge_impl(
+    vis: &Visibility,
+    variant_ident: &Ident,
+    context: &Ident,
+    generics: &syn::Generics,
+    field_types: &[(&Ident, &Visibility, TokenStream2)],
+    strict: bool,
+) -> TokenStream2 {
+    let required: Vec<&Ident> = field_types.iter()
+        .filter(|(_, _, ty)| syn::parse2::<Type>(ty.clone()).map_or(true, |ty| !is_option_type(&ty)))
+        .map(|(ident, _, _)| *ident)
+        .collect();
+    if !required.is_empty() {
+        if strict {
+            let names: Vec<String> = required.iter().map(|ident| ident.to_string()).collect();
+            emit_error!(context.span(), "merge needs every field of {} to be Option; not Option: {}", variant_ident, names.join(", ");
+                help = "make them optional in {}, or leave {} out of merge", context, context);
+        }
+        return TokenStream2::new();
+    }
+    let idents = field_types.iter().map(|(ident, _, _)| *ident);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    quote! {
+        #[allow(dead_code)]
+        impl #impl_generics #variant_ident #ty_generics #where_clause {
+            /// Combine with `other`, keeping each field of `other` that is `Some`
+            #vis fn merge(mut self, other: Self) -> Self {
+                self.merge_from(other);
+                self
+            }
+
+            /// Overwrite each field with the one from `other` where that is `Some`
+            #vis fn merge_from(&mut self, other: Self) {
+                #(
+                    if other.#idents.is_some() {
+                        self.#idents = other.#idents;
+                    }
+                )*
+            }
+        }
+    }
+}
+
+/// `Variant::apply_to(&self, &mut Base)`: required fields overwrite the target, optional ones only when `Some`.
+#[allow(clippy::too_many_arguments)]
+fn apply_to_impl(
+    vis: &Visibility,
+    struct_name: &Ident,
+    variant_ident: &Ident,
+    context: &Ident,
+    generics: &syn::Generics,
+    entry_fields: &[(&FieldSpec, bool)],
+    flattened: &[&FieldSpec],
+    field_types: &[(&Ident, &Visibility, TokenStream2)],
+    instrument: Option<&TokenStream2>,
+) -> TokenStream2 {
+    let mut writes = Vec::new();
+    // Hoisted fields go back into the nested struct
+    for fs in flattened {
+        let ident = &fs.ident;
+        for (sub, _) in fs.flatten_into.iter().flat_map(|flatten| &flatten.fields) {
+            writes.push(quote! { target.#ident.#sub = ::core::clone::Clone::clone(&self.#sub); });
+        }
+    }
+    for (fs, required) in entry_fields {
+        let ident = &fs.ident;
+        let base_ty = base_field_type(fs);
+        let Some((_, _, ty)) = field_types.iter().find(|(existing, _, _)| *existing == ident) else { continue };
+        if fs.encrypt_with.is_some() && fs.encrypted_in.contains(context) {
+            emit_error!(ident.span(), "apply_to cannot write '{}' from {}: it is encrypted in the variant", ident, variant_ident;
+                help = "leave {} out of apply_to", context);
+            continue;
+        }
+        let variant_ty: Type = syn::parse2(ty.clone()).expect("generated field types parse");
+        let write = match option_inner_type(&variant_ty) {
+            Some(inner) if !required => convert_from_ref(quote! { ::core::clone::Clone::clone(__value) }, inner, &base_ty)
+                .map(|value| quote! {
+                    if let ::core::option::Option::Some(__value) = &self.#ident {
+                        target.#ident = #value;
+                    }
+                }),
+            _ => convert_from_ref(quote! { ::core::clone::Clone::clone(&self.#ident) }, &variant_ty, &base_ty)
+                .map(|value| quote! { target.#ident = #value; }),
+        };
+        match write {
+            Some(write) => writes.push(write),
+            None => emit_error!(ident.span(), "apply_to cannot write '{}' from {}: it is Option in the variant but not in the base", ident, variant_ident;
+                help = "make '{}' optional in {}, or leave {} out of apply_to", ident, context, context),
+        }
+    }
+    let mut clone_generics = generics.clone();
+    for param in clone_generics.type_params_mut() {
+        param.bounds.push(syn::parse_quote!(::core::clone::Clone));
+    }
+    let (impl_generics, _, where_clause) = clone_generics.split_for_impl();
+    let (_, ty_generics, _) = generics.split_for_impl();
+    let doc = format!("Copy every field set in this {} onto `target`, leaving the other fields of the [`{}`] unchanged", variant_ident, struct_name);
+    quote! {
+        #[allow(dead_code)]
+        impl #impl_generics #variant_ident #ty_generics #where_clause {
+            #[doc = #doc]
+            #instrument
+            #vis fn apply_to(&self, target: &mut #struct_name #ty_generics) {
+                #(#writes)*
+            }
+        }
+    }
+}
+
+/// Initializer of `ident` from the borrowed base `__base`, for from_ref and diff: cloned and converted to
+/// `variant_ty`, or encrypted with `encrypt_module`. `None` if the base field is `Option` but the variant's is not.
+fn borrowed_base_init(ident: &Ident, encrypt_module: Option<&syn::Path>, variant_ty: &Type, base_ty: &Type) -> Option<TokenStream2> {
+    match encrypt_module {
+        Some(module) => encrypt_from_ref(module, ident, base_ty, variant_ty),
+        None => convert_from_ref(quote! { __base.#ident.clone() }, base_ty, variant_ty),
+    }
+}
+
+/// Encrypt a borrowed base field with `module::encrypt(&T)`, keeping or adding the `Option` the variant has
+fn encrypt_from_ref(module: &syn::Path, ident: &Ident, from: &Type, to: &Type) -> Option<TokenStream2> {
+    match (is_option_type(from), is_option_type(to)) {
+        (true, true) => Some(quote! { __base.#ident.as_ref().map(|__value| #module::encrypt(__value)) }),
+        (false, true) => Some(quote! { ::core::option::Option::Some(#module::encrypt(&__base.#ident)) }),
+        (false, false) => Some(quote! { #module::encrypt(&__base.#ident) }),
+        (true, false) => None,
+    }
+}
+
+/// The struct as declared, kept next to an optional_base renamed by base_name, with
+/// `From<Strict> for Partial` and a `TryFrom<Partial> for Strict` that lists the fields still `None`.
+fn strict_base_impl(
+    strict: &Ident,
+    partial: &Ident,
+    struct_attrs: &[Attribute],
+    vis: &Visibility,
+    generics: &syn::Generics,
+    fields: &[FieldSpec],
+) -> TokenStream2 {
+    let error_ident = missing_fields_ident(partial);
+    let strict_name = strict.to_string();
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let declared = fields.iter().map(|fs| {
+        let FieldSpec { ident, ty, vis, attrs, base_attrs, .. } = fs;
+        quote! {
+            #(#attrs)*
+            #(#base_attrs)*
+            #vis #ident: #ty,
+        }
+    });
+    let wrapped: Vec<&Ident> = fields.iter().filter(|fs| fs.base_wrapped).map(|fs| &fs.ident).collect();
+    let wrapped_names: Vec<String> = wrapped.iter().map(|ident| ident.unraw().to_string()).collect();
+    let into_partial = fields.iter().map(|fs| {
+        let ident = &fs.ident;
+        if fs.base_wrapped {
+            quote! { #ident: ::core::option::Option::Some(__strict.#ident) }
+        } else {
+            quote! { #ident: __strict.#ident }
+        }
+    });
+    let into_strict = fields.iter().map(|fs| {
+        let ident = &fs.ident;
+        if fs.base_wrapped {
+            quote! {
+                #ident: match __partial.#ident {
+                    ::core::option::Option::Some(__value) => __value,
+                    ::core::option::Option::None => unreachable!(),
+                }
+            }
+        } else {
+            quote! { #ident: __partial.#ident }
+        }
+    });
+    let doc = format!("[`{}`] with every field set", partial);
+    let doc_separator = struct_attrs.iter().any(|attr| attr.path().is_ident("doc")).then(|| quote! { #[doc = ""] });
+    quote! {
+        #(#struct_attrs)*
+        #doc_separator
+        #[doc = #doc]
+        #vis struct #strict #generics #where_clause {
+            #(#declared)*
+        }
+
+        impl #impl_generics ::core::convert::From<#strict #ty_generics> for #partial #ty_generics #where_clause {
+            fn from(__strict: #strict #ty_generics) -> Self {
+                Self {
+                    #(#into_partial,)*
+                }
+            }
+        }
+
+        impl #impl_generics ::core::convert::TryFrom<#partial #ty_generics> for #strict #ty_generics #where_clause {
+            type Error = #error_ident;
+
+            fn try_from(__partial: #partial #ty_generics) -> ::core::result::Result<Self, Self::Error> {
+                let mut __missing = ::std::vec::Vec::new();
+                #(
+                    if __partial.#wrapped.is_none() {
+                        __missing.push(#wrapped_names);
+                    }
+                )*
+                if !__missing.is_empty() {
+                    return ::core::result::Result::Err(#error_ident { variant: #strict_name, fields: __missing });
+                }
+                ::core::result::Result::Ok(Self {
+                    #(#into_strict,)*
+                })
+            }
+        }
+    }
+}
+
+/// Name of the item type yielded by iterating over a variant, e.g. `UserFieldEntry`
+fn field_entry_ident(struct_name: &Ident) -> Ident {
+    Ident::new(&format!("{}FieldEntry", struct_name), struct_name.span())
+}
+
+/// The `{Struct}FieldEntry` type describing one field of a variant and its serialized value.
+fn field_entry_type(vis: &Visibility, struct_name: &Ident) -> TokenStream2 {
+    let entry_ident = field_entry_ident(struct_name);
+    let doc = format!("A field of a [`{}`] variant, yielded by iterating over `&variant`", struct_name);
+    quote! {
+        #[doc = #doc]
+        #[derive(Debug, Clone, PartialEq)]
+        #vis struct #entry_ident {
+            /// Rust field name
+            #vis name: &'static str,
+            /// Serialized name, following `#[serde(rename = "...")]`
+            #vis serde_name: &'static str,
+            /// Whether the field is required in this variant
+            #vis required: bool,
+            /// The field's doc comment, or an empty string
+            #vis doc: &'static str,
+            /// The serialized value; `None` when the field is absent or serializes to `null`
+            #vis value: ::core::option::Option<::serde_json::Value>,
+        }
+    }
+}
+
+/// `serialize_required(&self, serializer)`: the variant as a struct holding only its required,
+/// non-secret fields, under their serde names.
+fn serialize_required_impl(vis: &Visibility, variant_ident: &Ident, generics: &syn::Generics, fields: &[(&FieldSpec, bool)]) -> TokenStream2 {
+    let mut serialize_generics = generics.clone();
+    for param in serialize_generics.type_params_mut() {
+        param.bounds.push(syn::parse_quote!(::serde::Serialize));
+    }
+    let (impl_generics, _, where_clause) = serialize_generics.split_for_impl();
+    let (_, ty_generics, _) = generics.split_for_impl();
+
+    let serialized: Vec<TokenStream2> = fields.iter()
+        .filter(|(fs, required)| *required && !fs.is_secret)
+        .map(|(fs, _)| {
+            let ident = &fs.ident;
+            let key = SerdeFieldInfo::from_field(fs).key;
+            quote! { ::serde::ser::SerializeStruct::serialize_field(&mut __state, #key, &self.#ident)?; }
+        })
+        .collect();
+    let len = serialized.len();
+    let name = variant_ident.to_string();
+    let doc = format!("Serialize only the fields {} requires, e.g. for an idempotency key or digest", variant_ident);
+    quote! {
+        #[allow(dead_code)]
+        impl #impl_generics #variant_ident #ty_generics #where_clause {
+            #[doc = #doc]
+            #vis fn serialize_required<__S: ::serde::Serializer>(&self, serializer: __S) -> ::core::result::Result<__S::Ok, __S::Error> {
+                let mut __state = ::serde::Serializer::serialize_struct(serializer, #name, #len)?;
+                #(#serialized)*
+                ::serde::ser::SerializeStruct::end(__state)
+            }
+        }
+    }
+}
+
+/// `IntoIterator for &Variant`: the variant's non-secret fields with metadata and JSON values.
+fn field_iter_impl(variant_ident: &Ident, generics: &syn::Generics, entry_ident: &Ident, fields: &[(&FieldSpec, bool)]) -> TokenStream2 {
+    let mut iter_generics = generics.clone();
+    iter_generics.params.insert(0, syn::parse_quote!('__field_iter));
+    for param in iter_generics.type_params_mut() {
+        param.bounds.push(syn::parse_quote!(::serde::Serialize));
+    }
+    let (impl_generics, _, where_clause) = iter_generics.split_for_impl();
+    let (_, ty_generics, _) = generics.split_for_impl();
+
+    // Secrets stay out of generic renderers
+    let entries = fields.iter().filter(|(fs, _)| !fs.is_secret).map(|(fs, required)| {
+        let ident = &fs.ident;
+        let name = ident.unraw().to_string();
+        let serde_name = SerdeFieldInfo::from_field(fs).key;
+        let doc = fs.attrs.iter()
+            .filter_map(|attr| match &attr.meta {
+                Meta::NameValue(nv) if nv.path.is_ident("doc") => parse_string_value(&nv.value).ok(),
+                _ => None,
+            })
+            .map(|line| line.trim().to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+        quote! {
+            #entry_ident {
+                name: #name,
+                serde_name: #serde_name,
+                required: #required,
+                doc: #doc,
+                value: ::serde_json::to_value(&self.#ident).ok().filter(|value| !value.is_null()),
+            }
+        }
+    });
+    quote! {
+        impl #impl_generics ::core::iter::IntoIterator for &'__field_iter #variant_ident #ty_generics #where_clause {
+            type Item = #entry_ident;
+            type IntoIter = ::std::vec::IntoIter<#entry_ident>;
+
+            fn into_iter(self) -> Self::IntoIter {
+                ::std::vec![#(#entries),*].into_iter()
+            }
+        }
+    }
+}
+
+/// `from_value()`: decode a variant from JSON, naming the missing or mistyped field on failure.
+fn from_value_impl(vis: &Visibility, variant_ident: &Ident, context: &Ident, generics: &syn::Generics, error_ident: &Ident, fields: &[(&FieldSpec, TokenStream2, bool)]) -> TokenStream2 {
+    let mut value_generics = generics.clone();
+    for param in value_generics.type_params_mut() {
+        param.bounds.push(syn::parse_quote!(::serde::de::DeserializeOwned));
+    }
+    let (impl_generics, _, where_clause) = value_generics.split_for_impl();
+    let (_, ty_generics, _) = generics.split_for_impl();
+    let context_name = context.to_string();
+
+    let mut missing_checks = Vec::new();
+    let mut type_checks = Vec::new();
+    for (fs, ty, may_be_absent) in fields {
+        let info = SerdeFieldInfo::from_field(fs);
+        let key = &info.key;
+        if !may_be_absent && !info.may_be_absent {
+            missing_checks.push(quote! {
+                if !map.contains_key(#key) {
+                    return ::core::result::Result::Err(error(#key, ::std::string::String::from("missing required field")));
+                }
+            });
+        }
+        if !info.custom {
+            type_checks.push(quote! {
+                if let ::core::option::Option::Some(field_value) = map.get(#key) {
+                    if let ::core::result::Result::Err(err) = <#ty as ::serde::Deserialize>::deserialize(field_value) {
+                        return ::core::result::Result::Err(error(#key, err.to_string()));
+                    }
+                }
+            });
+        }
+    }
+
+    quote! {
+        #[allow(dead_code)]
+        impl #impl_generics #variant_ident #ty_generics #where_clause {
+            /// Decode from JSON, reporting which required field is missing or which field has the wrong type
+            #vis fn from_value(value: ::serde_json::Value) -> ::core::result::Result<Self, #error_ident> {
+                let error = |field: &str, message: ::std::string::String| #error_ident {
+                    context: #context_name,
+                    field: ::core::option::Option::Some(::std::string::String::from(field)),
+                    message,
+                };
+                let ::serde_json::Value::Object(map) = &value else {
+                    return ::core::result::Result::Err(#error_ident {
+                        context: #context_name,
+                        field: ::core::option::Option::None,
+                        message: ::std::string::String::from("expected a JSON object"),
+                    });
+                };
+                #(#missing_checks)*
+                match <Self as ::serde::Deserialize>::deserialize(&value) {
+                    ::core::result::Result::Ok(decoded) => ::core::result::Result::Ok(decoded),
+                    ::core::result::Result::Err(err) => {
+                        // Attribute the failure to the first field that does not decode on its own
+                        #(#type_checks)*
+                        ::core::result::Result::Err(#error_ident {
+                            context: #context_name,
+                            field: ::core::option::Option::None,
+                            message: err.to_string(),
+                        })
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// `canonical_bytes()`: compact JSON with object keys sorted at every level.
+fn canonical_json_impl(vis: &Visibility, variant_ident: &Ident, generics: &syn::Generics) -> TokenStream2 {
+    let mut json_generics = generics.clone();
+    for param in json_generics.type_params_mut() {
+        param.bounds.push(syn::parse_quote!(::serde::Serialize));
+    }
+    let (impl_generics, _, where_clause) = json_generics.split_for_impl();
+    let (_, ty_generics, _) = generics.split_for_impl();
+    quote! {
+        #[allow(dead_code)]
+        impl #impl_generics #variant_ident #ty_generics #where_clause {
+            /// Canonical JSON encoding: sorted keys and no whitespace, independent of field order.
+            /// Panics if the value cannot be represented as JSON, e.g. a map with non-string keys.
+            #vis fn canonical_bytes(&self) -> ::std::vec::Vec<u8> {
+                fn write(value: &::serde_json::Value, out: &mut ::std::vec::Vec<u8>) {
+                    match value {
+                        ::serde_json::Value::Object(map) => {
+                            let mut entries: ::std::vec::Vec<_> = map.iter().collect();
+                            entries.sort_by(|a, b| a.0.cmp(b.0));
+                            out.push(b'{');
+                            for (index, (key, value)) in entries.into_iter().enumerate() {
+                                if index > 0 {
+                                    out.push(b',');
+                                }
+                                out.extend(::serde_json::to_vec(key).expect("string keys serialize"));
+                                out.push(b':');
+                                write(value, out);
+                            }
+                            out.push(b'}');
+                        }
+                        ::serde_json::Value::Array(items) => {
+                            out.push(b'[');
+                            for (index, item) in items.iter().enumerate() {
+                                if index > 0 {
+                                    out.push(b',');
+                                }
+                                write(item, out);
+                            }
+                            out.push(b']');
+                        }
+                        scalar => out.extend(::serde_json::to_vec(scalar).expect("scalars serialize")),
+                    }
+                }
+                let value = ::serde_json::to_value(self).expect("variant serializes to JSON");
+                let mut out = ::std::vec::Vec::new();
+                write(&value, &mut out);
+                out
+            }
+        }
+    }
+}
+
+/// `validate()` for a variant with invariants; fields an invariant cannot check are reported.
+/// With `error_messages`, the message comes from the module's function named after the invariant.
+#[allow(clippy::too_many_arguments)]
+fn validate_impl(
+    vis: &Visibility,
+    variant_ident: &Ident,
+    context: &Ident,
+    generics: &syn::Generics,
+    invariants: &[Invariant],
+    present_fields: &[(&Ident, bool)],
+    exclusions: &[(&Ident, Exclusion)],
+    messages: Option<&syn::Path>,
+    instrument: Option<&TokenStream2>,
+) -> TokenStream2 {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let checks = invariants.iter().map(|invariant| {
+        for field in invariant.fields() {
+            match present_fields.iter().find(|(ident, _)| *ident == field) {
+                None => match exclusions.iter().find(|(ident, _)| *ident == field) {
+                    Some((_, reason)) => emit_error!(
+                        field.span(),
+                        "field '{}' in {}() is not part of {}: it is {}", field, invariant.method_name(), variant_ident, reason.describe()
+                    ),
+                    None => emit_error!(
+                        field.span(),
+                        "field '{}' in {}() is not part of {}", field, invariant.method_name(), variant_ident
+                    ),
+                },
+                Some((_, false)) => emit_error!(
+                    field.span(),
+                    "field '{}' in {}() is required in {}, so the check always passes", field, invariant.method_name(), variant_ident;
+                    help = "list only optional fields"
+                ),
+                Some(_) => {}
+            }
+        }
+        let fields = invariant.fields();
+        let names: Vec<String> = fields.iter().map(|f| format!("`{}`", f)).collect();
+        let english = match invariant {
+            Invariant::AtLeastOneOf { .. } => format!("at least one of {} must be set", names.join(", ")),
+            Invariant::OneOf { .. } => format!("exactly one of {} must be set", names.join(", ")),
+        };
+        let message = match messages {
+            Some(module) => {
+                let function = Ident::new(invariant.method_name(), Span::call_site());
+                let field_names = fields.iter().map(|f| f.to_string());
+                let context_name = context.to_string();
+                quote! { #module::#function(&[#(#field_names),*], #context_name) }
+            }
+            None => quote! { ::std::string::String::from(#english) },
+        };
+        let violated = match invariant {
+            Invariant::AtLeastOneOf { .. } => quote! { #(self.#fields.is_none())&&* },
+            Invariant::OneOf { .. } => quote! { #(usize::from(self.#fields.is_some()))+* != 1 },
+        };
+        quote! {
+            if #violated {
+                return ::core::result::Result::Err(#message);
+            }
+        }
+    });
+    quote! {
+        impl #impl_generics #variant_ident #ty_generics #where_clause {
+            /// Check the invariants declared for this context
+            #instrument
+            #vis fn validate(&self) -> ::core::result::Result<(), ::std::string::String> {
+                #(#checks)*
+                ::core::result::Result::Ok(())
+            }
+        }
+    }
+}
+
+/// Impl of the user's `any_variant` trait exposing a variant's metadata.
+fn any_variant_impl(trait_path: &syn::Path, variant_ident: &Ident, context: &Ident, generics: &syn::Generics, required: &[&FieldSpec]) -> TokenStream2 {
+    let mut impl_generics = generics.clone();
+    for param in impl_generics.type_params_mut() {
+        param.bounds.push(syn::parse_quote!(::serde::Serialize));
+    }
+    let (impl_generics, _, where_clause) = impl_generics.split_for_impl();
+    let (_, ty_generics, _) = generics.split_for_impl();
+
+    let variant_name = variant_ident.to_string();
+    let context_name = context.to_string();
+    let required_names = required.iter().map(|fs| fs.ident.unraw().to_string());
+    quote! {
+        impl #impl_generics #trait_path for #variant_ident #ty_generics #where_clause {
+            fn variant_name(&self) -> &'static str {
+                #variant_name
+            }
+            fn context(&self) -> &'static str {
+                #context_name
+            }
+            fn required_fields(&self) -> &'static [&'static str] {
+                &[#(#required_names),*]
+            }
+            fn to_json(&self) -> ::serde_json::Result<::serde_json::Value> {
+                ::serde_json::to_value(self)
+            }
+        }
+    }
+}
+
+/// Impl of the user's `same_context` trait, plus `From`, moving every field into the
+/// related family's variant. A field set that differs fails to compile at the struct literal.
+fn same_context_impl(trait_path: &syn::Path, variant_ident: &Ident, other: &Ident, generics: &syn::Generics, fields: &[&Ident]) -> TokenStream2 {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    quote! {
+        impl #impl_generics #trait_path<#other #ty_generics> for #variant_ident #ty_generics #where_clause {
+            fn into_same(self) -> #other #ty_generics {
+                #other {
+                    #(#fields: self.#fields,)*
+                }
+            }
+        }
+
+        impl #impl_generics ::core::convert::From<#variant_ident #ty_generics> for #other #ty_generics #where_clause {
+            fn from(value: #variant_ident #ty_generics) -> Self {
+                #trait_path::into_same(value)
+            }
+        }
+    }
+}
+
+/// Report each message of `err` through proc-macro-error, alongside the errors emitted directly
+fn emit_syn_error(err: syn::Error) {
+    for message in err {
+        emit_error!(message.span(), "{}", message);
+    }
+}
+
+/// Remove `name` from every `#[derive(...)]`, returning whether it was derived.
+fn strip_derive(attrs: &mut Vec<Attribute>, name: &str) -> Result<bool, syn::Error> {
+    let mut found = false;
+    let mut kept_attrs = Vec::with_capacity(attrs.len());
+    for attr in attrs.drain(..) {
+        if !attr.path().is_ident("derive") {
+            kept_attrs.push(attr);
+            continue;
+        }
+        let paths = attr.parse_args_with(syn::punctuated::Punctuated::<syn::Path, syn::Token![,]>::parse_terminated)?;
+        let kept: Vec<&syn::Path> = paths.iter()
+            .filter(|path| path.segments.last().is_none_or(|last| last.ident != name))
+            .collect();
+        if kept.len() == paths.len() {
+            kept_attrs.push(attr);
+            continue;
+        }
+        found = true;
+        if !kept.is_empty() {
+            kept_attrs.push(syn::parse_quote! { #[derive(#(#kept),*)] });
+        }
+    }
+    *attrs = kept_attrs;
+    Ok(found)
+}
+
+/// Whether `attrs` derive a trait whose last path segment is `name`
+fn has_derive(attrs: &[Attribute], name: &str) -> bool {
+    attrs.iter()
+        .filter(|attr| attr.path().is_ident("derive"))
+        .filter_map(|attr| attr.parse_args_with(syn::punctuated::Punctuated::<syn::Path, syn::Token![,]>::parse_terminated).ok())
+        .flatten()
+        .any(|path| path.segments.last().is_some_and(|last| last.ident == name))
+}
+
+/// Span of a `#[repr(C)]` attribute, if the struct has one
+fn repr_c_span(attrs: &[Attribute]) -> Option<Span> {
+    attrs.iter().filter(|attr| attr.path().is_ident("repr")).find_map(|attr| {
+        let reprs = attr.parse_args_with(syn::punctuated::Punctuated::<Meta, syn::Token![,]>::parse_terminated).ok()?;
+        reprs.iter().any(|repr| repr.path().is_ident("C")).then(|| attr.span())
+    })
+}
@@ -0,0 +1,1311 @@
+//! Parsing and expansion logic behind the `context_variants` attribute macros.
+//!
+//! The `context_variants` proc-macro crate is a thin wrapper over [`expand_variants`] and
+//! [`expand_defaults`]. The same steps are available separately for tools and tests:
+//!
+//! - [`VariantList::parse`] reads `#[variants(...)]` arguments
+//! - [`VariantList::analyze`] resolves every field of a struct against the contexts, as [`FieldSpec`]s
+//! - [`expand`] generates the base struct and its variants
+//!
+//! Diagnostics other than the returned `syn::Error`s are reported with `proc-macro-error`,
+//! so these functions must run inside a `#[proc_macro_error]` entry point when the input
+//! may be invalid.
+
+use proc_macro2::{Ident, Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::{ext::IdentExt, spanned::Spanned, Attribute, DeriveInput, Field, Fields, Meta, Type, Visibility, parse::ParseStream};
+use proc_macro_error::emit_error;
+use indexmap::IndexMap;
+
+mod codegen;
+mod parse;
+mod resolve;
+
+pub use codegen::expand;
+use parse::*;
+use resolve::*;
+
+
+/// Field reference within a group definition - can be a field name or all_fields() with exceptions
+#[derive(Debug, Clone, PartialEq)]
+enum GroupFieldRef {
+    /// Regular field name
+    Field(Ident),
+    /// all_fields() with optional exceptions
+    AllFields { except: Vec<Ident> },
+}
+
+/// A named field group as declared in `groups = (...)`, before expansion
+#[derive(Debug, Clone)]
+struct GroupDef {
+    /// Group name, kept for its span in diagnostics
+    name: Ident,
+    fields: Vec<GroupFieldRef>,
+}
+
+
+/// Parsed top-level attribute arguments.
+#[derive(Debug, Default)]
+pub struct VariantList {
+    variants: Vec<Ident>,
+    prefix: Option<String>,
+    suffix: Option<String>,
+
+    /// Default attributes to apply to all optional fields
+    default_optional_attrs: Vec<Attribute>,
+    /// Default attributes to apply to all required fields  
+    default_required_attrs: Vec<Attribute>,
+    /// Extra optional-field attributes per context, from `optional_attrs(Update) = [...]`
+    scoped_optional_attrs: Vec<(Ident, Vec<Attribute>)>,
+    /// Extra required-field attributes per context, from `required_attrs(Update) = [...]`
+    scoped_required_attrs: Vec<(Ident, Vec<Attribute>)>,
+    /// Attributes added to the base struct only, from `base_attrs = [...]`
+    base_struct_attrs: Vec<Attribute>,
+    /// Where the deprecated `preserve_attr_text = [...]` was given, to warn about it
+    preserve_attr_text: Option<Span>,
+    /// Document each field's resolved role per context on the base struct, from `explain`
+    explain: bool,
+
+    /// NEW: Fluent context definitions (Context: requires(field1, field2))
+    fluent_contexts: Vec<FluentContext>,
+    /// Global default behavior for unspecified fields
+    global_default: Option<DefaultBehavior>,
+    /// Named sets of contexts, e.g. `write(Create, Update)`
+    context_sets: IndexMap<String, ContextSet>,
+    /// Default behavior per context, resolved from `defaults = { ... }`
+    context_defaults: IndexMap<String, DefaultBehavior>,
+    /// Generic wrapper applied to required fields per context, from `required_wrapper`
+    required_wrappers: IndexMap<String, syn::Path>,
+    /// Contexts each per-context flag is on in, from `name = true` or `name = { ... }`
+    flags: FlagContexts,
+    /// Name of the `{Variant}Page` cursor field, from `list_cursor`
+    list_cursor: Option<Ident>,
+    /// Marker attributes recording each field's role, from `emit_markers`
+    emit_markers: Option<MarkerStyle>,
+    /// How field docs carry over to variants, from `copy_field_docs`
+    copy_field_docs: FieldDocs,
+    /// Whether to emit `for_each_{struct}_context!`, from `for_each_macro`
+    for_each_macro: Option<ForEachMacro>,
+    /// How selectors match field names, from `field_match`
+    field_match: FieldMatch,
+    /// Keep `Option` fields required by `default(required)` without a warning, from `allow_option_required`
+    allow_option_required: bool,
+    /// Warn about `when_*` attributes that can never apply, from `warn_dead_attrs`
+    warn_dead_attrs: bool,
+    /// Report generated structs, fields and impls as a compile-time warning, from `report_stats`
+    report_stats: bool,
+    /// Emit later variants identical to an earlier one as type aliases, from `dedup_variants`
+    dedup_variants: bool,
+    /// Order of constructor parameters and builder fields, from `ctor_order`
+    ctor_order: CtorOrder,
+    /// Whether variants convert into variants whose fields they cover, from `cross_conversions`
+    cross_conversions: bool,
+    /// Most public visibility for generated items and fields, from `max_visibility = pub(crate)`
+    max_visibility: Option<Visibility>,
+    /// Generate under max_visibility even if the base is more public, from `allow_public = true`
+    allow_public: bool,
+    /// Context whose variant `Base::diff(&self, &Base)` returns, from `diff = Context`
+    diff: Option<Ident>,
+    /// Crate whose `instrument` attribute goes on generated runtime methods, from `instrument = tracing`
+    instrument: Option<syn::Path>,
+    /// Whether variants get a `Default` impl built from the base struct's, from `inherit_defaults`
+    inherit_defaults: bool,
+    /// User attribute applied to every emitted struct, from `post_process = path`
+    post_process: Option<syn::Path>,
+    /// User macro invoked once per context to register the variant, from `registry = path`
+    registry: Option<syn::Path>,
+    /// Module whose functions build `validate()` error messages, from `error_messages = path`
+    error_messages: Option<syn::Path>,
+    /// User trait implemented for every variant with its metadata, from `any_variant = path`
+    any_variant: Option<syn::Path>,
+    /// One `Has{Field}` accessor trait per field, implemented where it is required, from `field_traits = true`
+    field_traits: bool,
+    /// Untagged `{Struct}Any` enum and `{Struct}::parse_any(&str)`, from `parse_any = true`
+    parse_any: bool,
+    /// Enum with one serde-tagged case per context, from `enum_wrapper = "UserPayload"`
+    enum_wrapper: Option<Ident>,
+    /// Deserialize the base through the `enum_wrapper` enum, from `serde_try_from = true`
+    serde_try_from: bool,
+    /// User trait linking every variant to its base and context, from `context_variant = path`
+    context_variant: Option<syn::Path>,
+    /// User conversion trait implemented between context families, from `same_context = path`
+    same_context: Option<syn::Path>,
+    /// Name templates of related variant families, from `same_context_families = ["Admin{}Form"]`
+    same_context_families: Vec<syn::LitStr>,
+    /// Field type replacements per context, from `type_map = { From => To in [...] }`
+    type_mappings: Vec<TypeMapping>,
+    /// Fields typed `Secret<T>` or marked `#[ctx_secret]`, filled in once the struct is known
+    secret_fields: Vec<Ident>,
+    /// Markers whose fields `all_fields()` still selects, from `all_fields_includes = [cfg]`
+    all_fields_includes: Vec<SkipMarker>,
+    /// Fields `all_fields()` skips for a marker, filled in once the struct is known
+    skipped_fields: Vec<(Ident, SkipMarker)>,
+    /// Fields whose base type is `Option<T>`, for `respect_base_optionality()`
+    option_fields: Vec<Ident>,
+    /// Named field groups for reuse, in declaration order
+    field_groups: IndexMap<String, Vec<Ident>>,
+    /// Temporary storage for group definitions that need expansion
+    group_field_refs: IndexMap<String, GroupDef>,
+    /// Whether to generate the base struct (defaults to true)
+    build_base: bool,
+    /// Name of the emitted base struct in place of the annotated one, from `base_name = "UserRecord"`
+    base_name: Option<Ident>,
+    /// Whether to make all fields in the base struct optional (defaults to false)
+    optional_base: bool,
+}
+
+/// Contexts each per-context flag of [`VariantList`] is on in. The `*_named` lists hold the contexts
+/// named in `name = { ... }`, where a variant the flag can't apply to is an error rather than skipped.
+#[derive(Debug, Default)]
+struct FlagContexts {
+    /// Contexts in which heap-heavy fields are shared behind `Arc`, from `share_heavy_fields`
+    share_heavy_in: Vec<Ident>,
+    /// Contexts whose variants get a compile-time `Send + Sync` check, from `assert_auto_traits`
+    assert_auto_traits_in: Vec<Ident>,
+    /// Whether the base struct gets the `Send + Sync` check (`assert_auto_traits = true`)
+    assert_auto_traits_base: bool,
+    /// Contexts whose variants get a `{Variant}Presence` bit mask, from `presence`
+    presence_in: Vec<Ident>,
+    /// Contexts whose variants get a struct-of-arrays `{Variant}Columns`, from `columns`
+    columns_in: Vec<Ident>,
+    /// Contexts whose variants get a `{Variant}Page` listing wrapper, from `list`
+    list_in: Vec<Ident>,
+    /// Contexts whose variants get `from_value()`, from `from_value`
+    from_value_in: Vec<Ident>,
+    /// Contexts whose variants iterate over their fields as JSON values, from `field_iter`
+    field_iter_in: Vec<Ident>,
+    /// Contexts whose variants serialize their required fields alone, from `serialize_required`
+    serialize_required_in: Vec<Ident>,
+    /// Contexts whose variants list their field names in constants, from `field_consts`
+    field_consts_in: Vec<Ident>,
+    /// Contexts whose variants get `From<&Base>`, from `from_ref`
+    from_ref_in: Vec<Ident>,
+    /// Contexts whose variants get `From<Variant> for Base` where possible, from `conversions`
+    conversions_in: Vec<Ident>,
+    /// Contexts listed by name in `conversions = { ... }`, which must convert
+    conversions_named: Vec<Ident>,
+    /// Contexts converting to and from the base through `From`, `TryFrom` or `ToString` for changed types, from `as_conversions`
+    as_conversions_in: Vec<Ident>,
+    /// Contexts named in `as_conversions = { ... }`, where a variant that can't convert is an error
+    as_conversions_named: Vec<Ident>,
+    /// Contexts whose variant plus its excluded fields builds the base with `Base::from_{context}`, from `from_parts`
+    from_parts_in: Vec<Ident>,
+    /// Contexts named in `from_parts = { ... }`, where failing to build the base is an error
+    from_parts_named: Vec<Ident>,
+    /// Contexts whose variants get `TryFrom<Base>` reporting missing fields, from `try_from`
+    try_from_in: Vec<Ident>,
+    /// Contexts whose variants get `apply_to(&self, &mut Base)`, from `apply_to`
+    apply_to_in: Vec<Ident>,
+    /// Contexts whose variants get `new(required fields...)`, from `constructors`
+    constructors_in: Vec<Ident>,
+    /// Contexts whose variants get a `{Variant}Builder`, from `builder`
+    builder_in: Vec<Ident>,
+    /// Contexts whose all-optional variants get `merge` and `merge_from`, from `merge`
+    merge_in: Vec<Ident>,
+    /// Contexts listed by name in `merge = { ... }`, which must be all-optional
+    merge_named: Vec<Ident>,
+    /// Contexts whose variants get `canonical_bytes()`, from `canonical_json`
+    canonical_json_in: Vec<Ident>,
+}
+
+/// Represents a fluent context definition like "Create: requires(name, email)"
+/// Field reference that can be either a regular field name or all_fields() with exceptions
+#[derive(Debug, Clone, PartialEq)]
+enum FieldRef {
+    /// Regular field name
+    Field(Ident),
+    /// Field with variant type specification (field_name: Type)
+    FieldWithType { field: Ident, variant_type: Type },
+    /// all_fields() with optional exceptions, and groups whose members are excepted too
+    AllFields { except: Vec<Ident>, except_groups: Vec<Ident> },
+    /// Named group reference with exceptions
+    GroupWithExcept { group: Ident, except: Vec<Ident> },
+    /// A single field pulled in by expanding a named group
+    GroupMember { field: Ident, group: Ident, except: Vec<Ident> },
+    /// `fields_required_in(Context)` with exceptions; `fields` is filled in once that context is resolved
+    RequiredIn { context: Ident, except: Vec<Ident>, fields: Option<Vec<Ident>> },
+    /// `serde("wire_name")`, optionally with `as Type`; replaced by the field it names before matching
+    SerdeName { key: syn::LitStr, variant_type: Option<Type> },
+    /// `matching("*_at")`: every selectable field whose name fits the glob pattern
+    Matching { pattern: syn::LitStr },
+}
+
+impl FieldRef {
+    /// Check if this field reference matches a given field name
+    fn matches_field(&self, field_name: &Ident, all_struct_fields: &[Ident], field_groups: &IndexMap<String, Vec<Ident>>) -> bool {
+        match self {
+            FieldRef::Field(name) => name == field_name,
+            FieldRef::FieldWithType { field, .. } => field == field_name,
+            FieldRef::GroupMember { field, .. } => field == field_name,
+            FieldRef::AllFields { except, except_groups } => {
+                // Match if field is in all_struct_fields but not in exceptions or excepted groups
+                all_struct_fields.contains(field_name) && !except.contains(field_name)
+                    && !except_groups.iter().any(|group| field_groups.get(&group.to_string()).is_some_and(|members| members.contains(field_name)))
+            }
+            FieldRef::GroupWithExcept { group, except } => {
+                // Match if field is in the named group but not in exceptions
+                if let Some(group_fields) = field_groups.get(&group.to_string()) {
+                    group_fields.contains(field_name) && !except.contains(field_name)
+                } else {
+                    false
+                }
+            }
+            FieldRef::RequiredIn { except, fields, .. } => {
+                fields.as_ref().is_some_and(|fields| fields.contains(field_name)) && !except.contains(field_name)
+            }
+            FieldRef::SerdeName { .. } => false,
+            FieldRef::Matching { pattern } => {
+                all_struct_fields.contains(field_name) && glob_matches(&pattern.value(), &field_name.unraw().to_string())
+            }
+        }
+    }
+
+    /// Describe the selector for diagnostics, e.g. "`id`" or "group `auth`"
+    fn describe(&self) -> String {
+        fn with_except(base: String, except: &[Ident]) -> String {
+            if except.is_empty() {
+                base
+            } else {
+                let names: Vec<String> = except.iter().map(|e| e.to_string()).collect();
+                format!("{}.except({})", base, names.join(", "))
+            }
+        }
+        match self {
+            FieldRef::Field(name) => format!("`{}`", name),
+            FieldRef::FieldWithType { field, variant_type } => format!("`{} as {}`", field, quote!(#variant_type)),
+            FieldRef::AllFields { except, except_groups } if except_groups.is_empty() => with_except("all_fields()".to_string(), except),
+            FieldRef::AllFields { except, except_groups } => {
+                let groups: Vec<String> = except_groups.iter().map(|group| group.to_string()).collect();
+                format!("{}.except_groups({})", with_except("all_fields()".to_string(), except), groups.join(", "))
+            }
+            FieldRef::GroupWithExcept { group, except } | FieldRef::GroupMember { group, except, .. } => {
+                with_except(format!("group `{}`", group), except)
+            }
+            FieldRef::RequiredIn { context, except, .. } => with_except(format!("fields_required_in({})", context), except),
+            FieldRef::SerdeName { key, .. } => format!("serde({:?})", key.value()),
+            FieldRef::Matching { pattern } => format!("matching({:?})", pattern.value()),
+        }
+    }
+
+    /// Get the variant type for this field reference, if specified
+    fn get_variant_type(&self) -> Option<&Type> {
+        match self {
+            FieldRef::FieldWithType { variant_type, .. } | FieldRef::SerdeName { variant_type: Some(variant_type), .. } => Some(variant_type),
+            _ => None,
+        }
+    }
+
+}
+
+/// One context declared as `Name: requires(...).optional(...)`
+#[derive(Debug, Clone)]
+pub struct FluentContext {
+    name: Ident,
+    required_fields: Vec<FieldRef>,
+    optional_fields: Vec<FieldRef>, 
+    excluded_fields: Vec<FieldRef>,
+    /// Fields that must not appear in the variant, from `.forbid(...)`
+    forbidden_fields: Vec<FieldRef>,
+    /// Runtime checks generated into the variant's `validate()`
+    invariants: Vec<Invariant>,
+    /// Key-value metadata from `.tag(key = "value")`, surfaced as `TAG_KEY` consts
+    tags: Vec<(Ident, syn::Lit)>,
+    /// Fields of the `{Variant}Key` projection, from `.key(...)`
+    key_fields: Vec<Ident>,
+    /// Response envelope from `.envelope(ApiResponse)` or `.envelope(ApiResponse, Read)`,
+    /// with the context whose variant it wraps
+    envelope: Option<(syn::Path, Option<Ident>)>,
+    /// Fields only this variant has, from `.verbatim_field(...)`
+    verbatim_fields: Vec<Field>,
+    /// Fields computed from the base in conversions, from `.computed(name: Type = expr)`
+    computed_fields: Vec<(Ident, Type, syn::Expr)>,
+    /// Struct name replacing prefix + context + suffix, from `.name("UserCreationPayload")`
+    custom_name: Option<Ident>,
+    /// Derives only this variant gets, from `.derive(Default, Hash)`
+    derives: Vec<syn::Path>,
+    /// Doc lines replacing the struct's docs on this variant, from `.doc("...")`
+    docs: Vec<syn::LitStr>,
+    /// What the variant is for, from `.role(input | output)`
+    role: Option<ContextRole>,
+    /// Defaults for unmentioned fields matching selectors, from `.default_for(selectors, behavior)`; the first match wins
+    default_rules: Vec<(Vec<FieldRef>, DefaultBehavior)>,
+    default_behavior: Option<DefaultBehavior>,
+    /// Span of the end of the expression (for better error positioning)
+    end_span: Span,
+}
+
+/// A runtime invariant over a context's optional fields
+#[derive(Debug, Clone)]
+enum Invariant {
+    /// `.at_least_one_of(a, b)`: not every listed field may be `None`
+    AtLeastOneOf { fields: Vec<Ident> },
+    /// `.one_of(a, b)`: exactly one listed field is `Some`
+    OneOf { fields: Vec<Ident> },
+}
+
+impl Invariant {
+    fn fields(&self) -> &[Ident] {
+        match self {
+            Invariant::AtLeastOneOf { fields } | Invariant::OneOf { fields } => fields,
+        }
+    }
+
+    fn fields_mut(&mut self) -> &mut Vec<Ident> {
+        match self {
+            Invariant::AtLeastOneOf { fields } | Invariant::OneOf { fields } => fields,
+        }
+    }
+
+    fn method_name(&self) -> &'static str {
+        match self {
+            Invariant::AtLeastOneOf { .. } => "at_least_one_of",
+            Invariant::OneOf { .. } => "one_of",
+        }
+    }
+
+    /// Parse the arguments of an invariant method, which must be plain field or group names
+    fn parse(method: &str, fields: Vec<FieldRef>, span: Span) -> Result<Self, syn::Error> {
+        let mut names = Vec::new();
+        for field_ref in fields {
+            match field_ref {
+                FieldRef::Field(name) => names.push(name),
+                _ => return Err(syn::Error::new(span, format!("{}() expects field or group names", method))),
+            }
+        }
+        if names.len() < 2 {
+            return Err(syn::Error::new(span, format!("{}() expects at least two fields", method)));
+        }
+        match method {
+            "at_least_one_of" => Ok(Invariant::AtLeastOneOf { fields: names }),
+            "one_of" => Ok(Invariant::OneOf { fields: names }),
+            _ => unreachable!("not an invariant method: {}", method),
+        }
+    }
+}
+
+/// What a context's variant is for, from `.role(input)` or `.role(output)`
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ContextRole {
+    /// Received from callers: `Deserialize` and a `new()` constructor
+    Input,
+    /// Sent to callers: `Serialize` and `From<&Base>`
+    Output,
+}
+
+impl ContextRole {
+    /// The derive a variant in this role needs, by the derive's last path segment
+    fn serde_derive(self) -> (&'static str, syn::Path) {
+        match self {
+            ContextRole::Input => ("Deserialize", syn::parse_quote!(::serde::Deserialize)),
+            ContextRole::Output => ("Serialize", syn::parse_quote!(::serde::Serialize)),
+        }
+    }
+}
+
+/// Default behavior for unspecified fields
+#[derive(Debug, Clone, PartialEq)]
+enum DefaultBehavior {
+    Required,
+    Optional,
+    Exclude,
+    /// From `.respect_base_optionality()`: base `Option` fields are optional, all others required
+    RespectBase,
+}
+
+impl DefaultBehavior {
+    /// Parse a behavior name like `optional`, reporting errors at `span`
+    fn from_name(name: &str, span: Span) -> Result<Self, syn::Error> {
+        match name {
+            "required" => Ok(DefaultBehavior::Required),
+            "optional" => Ok(DefaultBehavior::Optional),
+            "exclude" => Ok(DefaultBehavior::Exclude),
+            _ => Err(syn::Error::new(span, "expected 'required', 'optional', or 'exclude'")),
+        }
+    }
+
+    /// The fluent call that sets this behavior, for diagnostics
+    fn call(&self) -> &'static str {
+        match self {
+            DefaultBehavior::Required => "default(required)",
+            DefaultBehavior::Optional => "default(optional)",
+            DefaultBehavior::Exclude => "default(exclude)",
+            DefaultBehavior::RespectBase => "respect_base_optionality()",
+        }
+    }
+}
+
+/// Why a field is missing from a variant
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Exclusion {
+    /// Listed in `.excludes(...)`
+    Explicit,
+    /// Unmentioned, and the context's default is `exclude`
+    Default,
+    /// Listed in `.forbid(...)`
+    Forbidden,
+    /// A secret field the context does not name
+    Secret,
+    /// A field `all_fields()` skips, which the context does not name
+    Skipped(SkipMarker),
+}
+
+impl Exclusion {
+    /// How the field was excluded, for diagnostics
+    fn describe(&self) -> &'static str {
+        match self {
+            Exclusion::Explicit => "excluded by excludes(...)",
+            Exclusion::Default => "excluded by the context's default(exclude)",
+            Exclusion::Forbidden => "forbidden by forbid(...)",
+            Exclusion::Secret => "a secret field the context does not name",
+            Exclusion::Skipped(SkipMarker::Passthrough) => "a #[ctx_passthrough] field the context does not name",
+            Exclusion::Skipped(SkipMarker::Cfg) => "a #[cfg(...)] field the context does not name",
+            Exclusion::Skipped(SkipMarker::SerdeSkip) => "a #[serde(skip)] field the context does not name",
+        }
+    }
+}
+
+/// Field markers that keep a field out of `all_fields()` and `matching(...)`
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SkipMarker {
+    /// `#[ctx_passthrough]`
+    Passthrough,
+    /// `#[cfg(...)]`, which may compile the field out
+    Cfg,
+    /// `#[serde(skip)]`
+    SerdeSkip,
+}
+
+impl SkipMarker {
+    /// Parse `all_fields_includes = [passthrough, cfg, serde_skip]`
+    fn parse_list(value: &syn::Expr) -> Result<Vec<Self>, syn::Error> {
+        let syn::Expr::Array(array) = value else {
+            return Err(syn::Error::new(value.span(), "expected a list like [passthrough, cfg, serde_skip]"));
+        };
+        array.elems.iter()
+            .map(|elem| match elem {
+                syn::Expr::Path(path) if path.path.is_ident("passthrough") => Ok(SkipMarker::Passthrough),
+                syn::Expr::Path(path) if path.path.is_ident("cfg") => Ok(SkipMarker::Cfg),
+                syn::Expr::Path(path) if path.path.is_ident("serde_skip") => Ok(SkipMarker::SerdeSkip),
+                _ => Err(syn::Error::new(elem.span(), "expected 'passthrough', 'cfg', or 'serde_skip'")),
+            })
+            .collect()
+    }
+
+    /// The first marker on a field
+    fn of_field(attrs: &[Attribute]) -> Option<Self> {
+        let serde_skip = |attr: &Attribute| attr.path().is_ident("serde")
+            && attr.parse_args_with(syn::punctuated::Punctuated::<Meta, syn::Token![,]>::parse_terminated)
+                .is_ok_and(|items| items.iter().any(|item| item.path().is_ident("skip")));
+        attrs.iter().find_map(|attr| {
+            if is_macro_attr(attr, "ctx_passthrough") {
+                Some(SkipMarker::Passthrough)
+            } else if attr.path().is_ident("cfg") {
+                Some(SkipMarker::Cfg)
+            } else if serde_skip(attr) {
+                Some(SkipMarker::SerdeSkip)
+            } else {
+                None
+            }
+        })
+    }
+}
+
+/// How fluent selectors are matched against field names, from `field_match`
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+enum FieldMatch {
+    /// Names must match exactly
+    #[default]
+    Exact,
+    /// Names, including `serde("...")` names, match ignoring ASCII case
+    CaseInsensitive,
+}
+
+impl FieldMatch {
+    /// Parse `field_match = exact | case_insensitive`
+    fn parse(value: &syn::Expr) -> Result<Self, syn::Error> {
+        match value {
+            syn::Expr::Path(path) if path.path.is_ident("exact") => Ok(FieldMatch::Exact),
+            syn::Expr::Path(path) if path.path.is_ident("case_insensitive") => Ok(FieldMatch::CaseInsensitive),
+            _ => Err(syn::Error::new(value.span(), "expected 'exact' or 'case_insensitive'")),
+        }
+    }
+}
+
+/// Order of `new()` parameters and builder fields, from `ctor_order`
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+enum CtorOrder {
+    /// The variant's field order
+    #[default]
+    Declaration,
+    /// Fields required in the context first, then optional ones, each in the variant's field order
+    RequiredFirst,
+}
+
+impl CtorOrder {
+    /// Parse `ctor_order = declaration | required_first`
+    fn parse(value: &syn::Expr) -> Result<Self, syn::Error> {
+        match value {
+            syn::Expr::Path(path) if path.path.is_ident("declaration") => Ok(CtorOrder::Declaration),
+            syn::Expr::Path(path) if path.path.is_ident("required_first") => Ok(CtorOrder::RequiredFirst),
+            _ => Err(syn::Error::new(value.span(), "expected 'declaration' or 'required_first'")),
+        }
+    }
+
+    /// `fields` in this order; `entry_fields` tells which are required in the context
+    fn arrange<'a, 'f>(
+        self,
+        fields: &'a [(&'f Ident, &'f Visibility, TokenStream2)],
+        entry_fields: &[(&FieldSpec, bool)],
+    ) -> Vec<&'a (&'f Ident, &'f Visibility, TokenStream2)> {
+        let mut arranged: Vec<_> = fields.iter().collect();
+        if self == CtorOrder::RequiredFirst {
+            let optional = |ident: &Ident| entry_fields.iter().any(|(fs, required)| fs.ident == *ident && !required);
+            arranged.sort_by_key(|(ident, _, _)| optional(ident));
+        }
+        arranged
+    }
+}
+
+/// How a field's doc comments carry over to the variants, from `copy_field_docs`
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+enum FieldDocs {
+    /// Every variant repeats the docs
+    #[default]
+    Copy,
+    /// Variant fields are undocumented
+    Skip,
+    /// A one-line link to the base field replaces the docs
+    Link,
+}
+
+impl FieldDocs {
+    /// Parse `copy_field_docs = true | false | link`
+    fn parse(value: &syn::Expr) -> Result<Self, syn::Error> {
+        match value {
+            syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Bool(b), .. }) => Ok(if b.value { FieldDocs::Copy } else { FieldDocs::Skip }),
+            syn::Expr::Path(path) if path.path.is_ident("link") => Ok(FieldDocs::Link),
+            _ => Err(syn::Error::new(value.span(), "expected 'true', 'false', or 'link'")),
+        }
+    }
+}
+
+/// Visibility of the `for_each_{struct}_context!` helper, from `for_each_macro`
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ForEachMacro {
+    /// Usable below the invocation in the same crate
+    Local,
+    /// `#[macro_export]`ed at the crate root, for downstream crates
+    Export,
+}
+
+impl ForEachMacro {
+    /// Parse `for_each_macro = true | false | export`
+    fn parse(value: &syn::Expr) -> Result<Option<Self>, syn::Error> {
+        match value {
+            syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Bool(b), .. }) => Ok(b.value.then_some(ForEachMacro::Local)),
+            syn::Expr::Path(path) if path.path.is_ident("export") => Ok(Some(ForEachMacro::Export)),
+            _ => Err(syn::Error::new(value.span(), "expected 'true', 'false', or 'export'")),
+        }
+    }
+}
+
+/// How `emit_markers` records a field's role on generated fields
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum MarkerStyle {
+    /// `#[cfg_attr(any(), ctx_optional(Update))]`, inert but visible to attribute macros and source tools
+    CfgAttr,
+    /// `#[doc = "ctx_optional(Update)"]`, which survives into derive macro input and rustdoc
+    Doc,
+}
+
+impl MarkerStyle {
+    /// Parse `emit_markers = true | false | cfg_attr | doc`
+    fn parse(value: &syn::Expr) -> Result<Option<Self>, syn::Error> {
+        let name = match value {
+            syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Bool(b), .. }) => b.value.to_string(),
+            syn::Expr::Path(path) if path.path.get_ident().is_some() => path.path.get_ident().expect("checked above").to_string(),
+            _ => String::new(),
+        };
+        match name.as_str() {
+            "true" | "cfg_attr" => Ok(Some(MarkerStyle::CfgAttr)),
+            "doc" => Ok(Some(MarkerStyle::Doc)),
+            "false" => Ok(None),
+            _ => Err(syn::Error::new(value.span(), "expected 'true', 'false', 'cfg_attr', or 'doc'")),
+        }
+    }
+
+    /// Marker attribute for a field that is required or optional in `context`
+    fn marker(&self, required: bool, context: &Ident) -> Attribute {
+        let role = if required { "ctx_required" } else { "ctx_optional" };
+        match self {
+            MarkerStyle::CfgAttr => {
+                let role = Ident::new(role, Span::call_site());
+                syn::parse_quote! { #[cfg_attr(any(), #role(#context))] }
+            }
+            MarkerStyle::Doc => {
+                let text = format!("{}({})", role, context);
+                syn::parse_quote! { #[doc = #text] }
+            }
+        }
+    }
+}
+
+/// A `type_map` rule such as `DateTime<Utc> => String in [Create, Update]`
+#[derive(Debug, Clone)]
+struct TypeMapping {
+    from: Type,
+    to: Type,
+    /// Contexts the rule applies to; every context when `in [...]` is omitted
+    contexts: Vec<Ident>,
+}
+
+/// A named set of contexts as declared in `context_sets = (...)`
+#[derive(Debug, Clone)]
+struct ContextSet {
+    /// Set name, kept for its span in diagnostics
+    name: Ident,
+    contexts: Vec<Ident>,
+}
+
+impl VariantList {
+    /// Default behavior for fields a context does not mention: the context's own
+    /// `.default(...)`, then its entry in `defaults = { ... }`, then `default = ...`
+    fn default_for<'a>(&'a self, fluent_ctx: &'a FluentContext) -> Option<&'a DefaultBehavior> {
+        fluent_ctx.default_behavior.as_ref()
+            .or_else(|| self.context_defaults.get(&fluent_ctx.name.to_string()))
+            .or(self.global_default.as_ref())
+    }
+
+    /// Default behavior for one unmentioned field: the first `.default_for(...)` rule
+    /// whose selectors match it, then `default_for(fluent_ctx)`
+    fn default_for_field<'a>(&'a self, fluent_ctx: &'a FluentContext, field: &Ident, selectable_fields: &[Ident]) -> Option<&'a DefaultBehavior> {
+        fluent_ctx.default_rules.iter()
+            .find(|(selectors, _)| selectors.iter().any(|field_ref| field_ref.matches_field(field, selectable_fields, &self.field_groups)))
+            .map(|(_, behavior)| behavior)
+            .or_else(|| self.default_for(fluent_ctx))
+    }
+
+    /// Fields that `all_fields()` may select; secret and skipped fields must be named explicitly
+    fn selectable_fields(&self, all_field_names: &[Ident]) -> Vec<Ident> {
+        all_field_names.iter()
+            .filter(|name| !self.named_only(name))
+            .cloned()
+            .collect()
+    }
+
+    /// Whether the field only appears in contexts that name it
+    fn named_only(&self, field: &Ident) -> bool {
+        self.secret_fields.contains(field) || self.skipped_fields.iter().any(|(name, _)| name == field)
+    }
+}
+
+/// Struct representing the processed information for each field of the source struct.
+#[derive(Debug)]
+pub struct FieldSpec {
+    ident: Ident,
+    ty: Type,
+    vis: Visibility,
+    /// Visibility in the variants, capped by `max_visibility`
+    variant_vis: Visibility,
+    /// Original attributes excluding our macro-specific attributes.
+    attrs: Vec<Attribute>,
+    required_in: Vec<Ident>,
+    optional_in: Vec<Ident>,
+    never_in: Vec<Ident>,
+    /// Whether the type is already Option<T> (so we avoid wrapping again).
+    is_option: bool,
+    /// Attributes to apply when field is optional in a context
+    optional_attrs: Vec<Attribute>,
+    /// Attributes to apply when field is required in a context  
+    required_attrs: Vec<Attribute>,
+    /// Attributes that only appear on the base struct (from when_base)
+    base_attrs: Vec<Attribute>,
+    /// Variant-specific types for this field (variant_name -> Type)
+    variant_types: std::collections::HashMap<String, Type>,
+    /// Variants in which the field is held behind `Arc` (from ctx_shared / share_heavy_fields)
+    shared_in: Vec<Ident>,
+    /// Field type per variant after applying `type_map` (variant_name -> Type)
+    mapped_types: std::collections::HashMap<String, Type>,
+    /// Whether the field holds a secret (`Secret<T>` or `#[ctx_secret]`)
+    is_secret: bool,
+    /// Module providing `Encrypted<T>` and its conversions, from `#[ctx_encrypt(with = "...")]`
+    encrypt_with: Option<syn::Path>,
+    /// Variants in which the field is stored as `Encrypted<T>`
+    encrypted_in: Vec<Ident>,
+    /// `when_*` attributes that can never apply, with the warning to report at each
+    dead_attrs: Vec<(Span, String)>,
+    /// Variants that made the field required only through `default(required)`
+    required_by_default: Vec<Ident>,
+    /// Why the field is missing from each variant in `never_in` (variant_name -> reason)
+    exclusions: std::collections::HashMap<String, Exclusion>,
+    /// Whether the base struct wraps the field in `Option` (optional_base, #[ctx_base_required/optional])
+    base_wrapped: bool,
+    /// Where `#[ctx_base_optional]` was written, for the #[repr(C)] check
+    base_optional_span: Option<Span>,
+    /// Position in the generated variants, from `#[ctx_order(n)]`
+    order: Option<syn::LitInt>,
+    /// Leave the field's docs off every variant, from `#[ctx_skip_variant_field_docs]`
+    skip_variant_docs: bool,
+    /// The nested struct's fields that replace the field in some variants, from `#[ctx_flatten_into(...)]`
+    flatten_into: Option<FlattenInto>,
+}
+
+/// A struct-typed field whose own fields are hoisted into some variants
+#[derive(Debug)]
+struct FlattenInto {
+    /// Variants that carry the listed fields in place of the field
+    contexts: Vec<Ident>,
+    /// The field's type as written in a struct expression, e.g. `Db::<T>`
+    path: syn::Path,
+    /// The nested struct's fields, as listed in `fields(...)`
+    fields: Vec<(Ident, Type)>,
+}
+
+impl VariantList {
+    /// Parse the arguments of `#[variants(...)]`
+    pub fn parse(args: TokenStream2) -> Result<Self, syn::Error> {
+        parse_mixed_args(args)
+    }
+
+    /// Resolve every field of `input` against the contexts: selectors, groups, defaults and
+    /// field attributes. Conflicts between contexts are reported through proc-macro-error.
+    pub fn analyze(&mut self, input: &DeriveInput) -> Result<Vec<FieldSpec>, syn::Error> {
+        let fields = match input.data {
+            syn::Data::Struct(ref data) => {
+                match data.fields {
+                    Fields::Named(ref named) => named.named.iter().collect::<Vec<_>>(),
+                    _ => {
+                        return Err(syn::Error::new(data.fields.span(), "context_variants only supports structs with named fields"));
+                    }
+                }
+            }
+            _ => {
+                return Err(syn::Error::new(input.ident.span(), "context_variants can only be applied to structs"));
+            }
+        };
+
+        // Collect all field names for all_fields() resolution and validation
+        let all_field_names: Vec<Ident> = fields.iter()
+            .filter_map(|f| f.ident.as_ref().cloned())
+            .collect();
+
+        // Secret fields stay out of blanket selectors and defaults
+        self.secret_fields = fields.iter()
+            .filter(|f| is_secret_type(&f.ty) || f.attrs.iter().any(|attr| is_macro_attr(attr, "ctx_secret")))
+            .filter_map(|f| f.ident.clone())
+            .collect();
+        self.skipped_fields = fields.iter()
+            .filter_map(|f| Some((f.ident.clone()?, SkipMarker::of_field(&f.attrs)?)))
+            .filter(|(_, marker)| !self.all_fields_includes.contains(marker))
+            .collect();
+        self.option_fields = fields.iter()
+            .filter(|f| is_option_type(&f.ty))
+            .filter_map(|f| f.ident.clone())
+            .collect();
+        let selectable_fields = self.selectable_fields(&all_field_names);
+
+        // Selectors written as serde names or in another case refer to the fields by their Rust names
+        resolve_field_names(self, &fields)?;
+
+        // Now expand group field references (includes all_fields() resolution)
+        expand_group_field_references(self, &all_field_names)?;
+        resolve_required_in(self, &all_field_names, &selectable_fields)?;
+
+        // Validate fluent contexts for field conflicts and coverage
+        validate_fluent_contexts(self, &all_field_names);
+
+        // For each field, collect rules and remove our macro-specific attributes.
+        let mut processed_fields = Vec::new();
+        let mut errors = None;
+        for f in fields {
+            match process_field(f, self, &selectable_fields) {
+                Ok(field_spec) => processed_fields.push(field_spec),
+                Err(err) => push_error(&mut errors, err),
+            }
+        }
+
+        // Validate fluent context variant names exist in the variant list.
+        for field_spec in &processed_fields {
+            let rules = [
+                ("required", &field_spec.required_in),
+                ("optional", &field_spec.optional_in),
+                ("excluded", &field_spec.never_in),
+            ];
+            for (category, contexts) in rules {
+                for ctx in contexts {
+                    if !self.variants.iter().any(|v| v == ctx) {
+                        push_error(&mut errors, syn::Error::new(ctx.span(), format!("unknown variant '{}' for {} field", ctx, category)));
+                    }
+                }
+            }
+        }
+        // Explicit positions must be unique
+        for (index, fs) in processed_fields.iter().enumerate() {
+            let Some(order) = &fs.order else { continue };
+            if let Some(earlier) = processed_fields[..index].iter().find(|other| other.order.as_ref().is_some_and(|o| o.base10_digits() == order.base10_digits())) {
+                let mut err = syn::Error::new(order.span(), format!("#[ctx_order({})] on '{}' is already used by '{}'", order, fs.ident, earlier.ident));
+                err.combine(syn::Error::new(earlier.order.as_ref().expect("found by order").span(), "first used here"));
+                push_error(&mut errors, err);
+            }
+        }
+        if let Some(err) = errors {
+            return Err(err);
+        }
+        Ok(processed_fields)
+    }
+
+    /// Context names, in declaration order
+    pub fn contexts(&self) -> &[Ident] {
+        &self.variants
+    }
+
+    /// Generated type name for `context`: its `.name(...)`, or the context with the prefix and suffix applied
+    pub fn variant_ident(&self, context: &Ident) -> Ident {
+        if let Some(name) = self.fluent_contexts.iter().find(|ctx| ctx.name == *context).and_then(|ctx| ctx.custom_name.as_ref()) {
+            let mut name = name.clone();
+            name.set_span(context.span());
+            return name;
+        }
+        Ident::new(&format!("{}{}{}", self.prefix.as_deref().unwrap_or_default(), context, self.suffix.as_deref().unwrap_or_default()), context.span())
+    }
+
+    /// Contexts declared with the fluent syntax, in declaration order
+    pub fn fluent_contexts(&self) -> &[FluentContext] {
+        &self.fluent_contexts
+    }
+}
+
+impl FluentContext {
+    /// The context's name, e.g. `Create`
+    pub fn name(&self) -> &Ident {
+        &self.name
+    }
+}
+
+impl FieldSpec {
+    /// The field's name
+    pub fn ident(&self) -> &Ident {
+        &self.ident
+    }
+
+    /// The field's declared type
+    pub fn ty(&self) -> &Type {
+        &self.ty
+    }
+
+    /// Whether `context`'s variant has the field
+    pub fn is_in(&self, context: &Ident) -> bool {
+        !self.never_in.contains(context)
+    }
+
+    /// Whether the field is required in `context`'s variant
+    pub fn is_required_in(&self, context: &Ident) -> bool {
+        self.is_in(context) && !self.optional_in.contains(context) && self.required_in.contains(context)
+    }
+}
+
+/// Check if an attribute matches our macro attribute name.
+fn is_macro_attr(attr: &Attribute, name: &str) -> bool {
+    attr.path().is_ident(name)
+}
+
+/// Order of visibilities from private (0) to `pub` (3); `pub(super)` and `pub(in path)` rank below `pub(crate)`
+fn visibility_rank(vis: &Visibility) -> u8 {
+    match vis {
+        Visibility::Public(_) => 3,
+        Visibility::Restricted(restricted) if restricted.path.is_ident("crate") => 2,
+        Visibility::Restricted(restricted) if restricted.path.is_ident("self") => 0,
+        Visibility::Restricted(_) => 1,
+        Visibility::Inherited => 0,
+    }
+}
+
+/// `vis`, or `max` if `vis` is more public
+fn clamp_visibility(vis: &Visibility, max: &Visibility) -> Visibility {
+    if visibility_rank(vis) > visibility_rank(max) { max.clone() } else { vis.clone() }
+}
+
+/// Search alias carried by every generated base struct, recognized by `is_expanded_marker`
+const EXPANDED_MARKER: &str = "__context_variants_expanded";
+
+/// Whether `attr` is the `#[doc(alias = "__context_variants_expanded")]` marker on a generated base
+fn is_expanded_marker(attr: &Attribute) -> bool {
+    attr.path().is_ident("doc")
+        && attr.parse_args::<syn::MetaNameValue>().is_ok_and(|meta| {
+            meta.path.is_ident("alias")
+                && matches!(&meta.value, syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(lit), .. }) if lit.value() == EXPANDED_MARKER)
+        })
+}
+
+/// How serde treats a field on deserialization, from its `#[serde(...)]` attributes
+struct SerdeFieldInfo {
+    /// Key in the serialized form
+    key: String,
+    /// The field may be absent (`default`, `skip`, `flatten`)
+    may_be_absent: bool,
+    /// The field's own `Deserialize` impl does not decode it (`with`, `deserialize_with`, `flatten`, `skip`)
+    custom: bool,
+}
+
+impl SerdeFieldInfo {
+    fn from_field(fs: &FieldSpec) -> Self {
+        Self::from_attrs(&fs.ident, &fs.attrs)
+    }
+
+    fn from_attrs(ident: &Ident, attrs: &[Attribute]) -> Self {
+        let mut info = SerdeFieldInfo { key: ident.unraw().to_string(), may_be_absent: false, custom: false };
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+            let Ok(items) = attr.parse_args_with(syn::punctuated::Punctuated::<Meta, syn::Token![,]>::parse_terminated) else {
+                continue;
+            };
+            for item in &items {
+                let path = item.path();
+                if path.is_ident("rename") && let Meta::NameValue(nv) = item
+                    && let Ok(key) = parse_string_value(&nv.value) {
+                    info.key = key;
+                } else if path.is_ident("default") {
+                    info.may_be_absent = true;
+                } else if path.is_ident("with") || path.is_ident("deserialize_with") {
+                    info.custom = true;
+                } else if path.is_ident("flatten") || path.is_ident("skip") || path.is_ident("skip_deserializing") {
+                    info.may_be_absent = true;
+                    info.custom = true;
+                }
+            }
+        }
+        info
+    }
+}
+
+/// Owned heap collections that `share_heavy_fields` moves behind `Arc`.
+fn is_heavy_type(ty: &Type) -> bool {
+    let ty = option_inner_type(ty).unwrap_or(ty);
+    if let Type::Path(type_path) = ty
+        && let Some(last) = type_path.path.segments.last() {
+        return matches!(
+            last.ident.to_string().as_str(),
+            "String" | "Vec" | "HashMap" | "BTreeMap" | "HashSet" | "BTreeSet" | "VecDeque"
+        );
+    }
+    false
+}
+
+/// Types from the `secrecy` crate, optionally wrapped in `Option`.
+fn is_secret_type(ty: &Type) -> bool {
+    let ty = option_inner_type(ty).unwrap_or(ty);
+    if let Type::Path(type_path) = ty
+        && let Some(last) = type_path.path.segments.last() {
+        return matches!(
+            last.ident.to_string().as_str(),
+            "Secret" | "SecretBox" | "SecretString" | "SecretSlice"
+        );
+    }
+    false
+}
+
+/// Whether `name` fits a `matching(...)` pattern, where `*` stands for any run of characters and `?` for one
+fn glob_matches(pattern: &str, name: &str) -> bool {
+    match pattern.chars().next() {
+        None => name.is_empty(),
+        Some('*') => (0..=name.len()).filter(|at| name.is_char_boundary(*at)).any(|at| glob_matches(&pattern[1..], &name[at..])),
+        Some(expected) => {
+            let mut rest = name.chars();
+            rest.next().is_some_and(|actual| expected == '?' || actual == expected)
+                && glob_matches(&pattern[expected.len_utf8()..], rest.as_str())
+        }
+    }
+}
+
+/// Whether a field type matches a `type_map` pattern. Paths are compared by their
+/// last segment, so `chrono::DateTime<Utc>` matches a field declared `DateTime<Utc>`.
+fn type_matches(pattern: &Type, ty: &Type) -> bool {
+    if let (Type::Path(pattern), Type::Path(ty)) = (pattern, ty)
+        && let (Some(pattern), Some(ty)) = (pattern.path.segments.last(), ty.path.segments.last()) {
+        let (pattern_args, ty_args) = (&pattern.arguments, &ty.arguments);
+        return pattern.ident == ty.ident && quote!(#pattern_args).to_string() == quote!(#ty_args).to_string();
+    }
+    quote!(#pattern).to_string() == quote!(#ty).to_string()
+}
+
+/// Return `T` for a type of the form `Option<T>`.
+fn option_inner_type(ty: &Type) -> Option<&Type> {
+    if let Type::Path(type_path) = ty
+        && let Some(last) = type_path.path.segments.last()
+        && last.ident == "Option"
+        && let syn::PathArguments::AngleBracketed(args) = &last.arguments
+        && let Some(syn::GenericArgument::Type(inner)) = args.args.first() {
+        return Some(inner);
+    }
+    None
+}
+
+/// Determine if the provided type is of the form `Option<...>`. This is used to avoid wrapping
+/// `Option` types in another `Option` when generating optional fields.
+fn is_option_type(ty: &Type) -> bool {
+    if let Type::Path(type_path) = ty {
+        let path = &type_path.path;
+        // Check if last segment ident is Option
+        if let Some(last) = path.segments.last()
+            && last.ident == "Option" {
+            return true;
+        }
+    }
+    false
+}
+
+/// Expand `#[variants(args)]` on `input`, turning errors into `compile_error!` tokens
+pub fn expand_variants(args: TokenStream2, input: TokenStream2) -> TokenStream2 {
+    let result = VariantList::parse(args)
+        .and_then(|cfg| Ok((cfg, syn::parse2::<DeriveInput>(input)?)))
+        .and_then(|(cfg, input_struct)| expand(cfg, input_struct));
+    result.unwrap_or_else(|err| err.into_compile_error())
+}
+
+/// Expand `#[defaults(args)]` on an inline module, turning errors into `compile_error!` tokens
+pub fn expand_defaults(args: TokenStream2, input: TokenStream2) -> TokenStream2 {
+    let result = syn::parse2::<syn::ItemMod>(input).and_then(|mut module| {
+        check_default_args(args.clone())?;
+        let Some((_, items)) = &mut module.content else {
+            return Err(syn::Error::new(
+                module.ident.span(),
+                "defaults applies to inline modules; write `mod name { ... }` (a crate-level `#![...]` attribute macro is not stable Rust)",
+            ));
+        };
+        if !args.is_empty() {
+            seed_variant_defaults(items, &args);
+        }
+        Ok(quote! { #module })
+    });
+    result.unwrap_or_else(|err| err.into_compile_error())
+}
+
+/// Defaults may only hold parameters; contexts are declared per struct.
+fn check_default_args(args: TokenStream2) -> syn::Result<()> {
+    let parsed = syn::parse::Parser::parse2(syn::punctuated::Punctuated::<MixedArg, syn::Token![,]>::parse_terminated, args)?;
+    for arg in parsed {
+        match arg {
+            MixedArg::FluentContext { name, .. } | MixedArg::Path { name } => {
+                return Err(syn::Error::new(name.span(), format!("defaults cannot declare context '{}'; declare contexts on each struct", name)));
+            }
+            MixedArg::NameValue { .. } | MixedArg::Keyed { .. } | MixedArg::Scoped { .. } | MixedArg::TypeMap { .. } | MixedArg::Config { .. } => {}
+        }
+    }
+    Ok(())
+}
+
+/// Put `args` before the arguments of each `#[variants(...)]` in `items`, recursing into inline modules.
+/// A nested `#[defaults(...)]` module receives `args` ahead of its own, so the inner defaults win.
+fn seed_variant_defaults(items: &mut [syn::Item], args: &TokenStream2) {
+    let prepend = |attr: &mut Attribute| {
+        if let Meta::List(list) = &mut attr.meta {
+            let own = &list.tokens;
+            list.tokens = if own.is_empty() { args.clone() } else { quote! { #args, #own } };
+        } else if let Meta::Path(path) = &attr.meta {
+            let path = path.clone();
+            *attr = syn::parse_quote! { #[#path(#args)] };
+        }
+    };
+    let is_named = |attr: &Attribute, name: &str| attr.path().segments.last().is_some_and(|last| last.ident == name);
+    for item in items {
+        match item {
+            syn::Item::Struct(item) => {
+                for attr in item.attrs.iter_mut().filter(|attr| is_named(attr, "variants")) {
+                    prepend(attr);
+                }
+            }
+            syn::Item::Mod(module) => {
+                if let Some(attr) = module.attrs.iter_mut().find(|attr| is_named(attr, "defaults")) {
+                    prepend(attr);
+                } else if let Some((_, items)) = &mut module.content {
+                    seed_variant_defaults(items, args);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quote::ToTokens;
+
+    /// Expand the first `#[variants(...)]` struct of a source file, one generated item per line.
+    fn expand_source(source: &str) -> String {
+        let file = syn::parse_file(source).expect("golden input parses");
+        let input = file.items.into_iter().find_map(|item| match item {
+            syn::Item::Struct(item) => Some(item),
+            _ => None,
+        }).expect("golden input has a struct");
+        expand_item(quote! { #input })
+    }
+
+    fn expand_item(item: TokenStream2) -> String {
+        let input: DeriveInput = syn::parse2(item).expect("struct parses");
+        let args = input.attrs.iter()
+            .find(|attr| is_macro_attr(attr, "variants"))
+            .map(|attr| match &attr.meta {
+                Meta::List(list) => list.tokens.clone(),
+                _ => TokenStream2::new(),
+            })
+            .expect("struct has #[variants(...)]");
+        let cfg = VariantList::parse(args).expect("arguments parse");
+        let output = expand(cfg, input).expect("struct expands");
+        let file: syn::File = syn::parse2(output).expect("expansion is a list of items");
+        file.items.iter().map(|item| format!("{}\n", quote! { #item })).collect()
+    }
+
+    // Each tests/golden/*.rs expands to the sibling .expanded file; GOLDEN=overwrite rewrites them
+    #[test]
+    fn golden_expansions() {
+        let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden");
+        let mut inputs: Vec<_> = std::fs::read_dir(&dir).expect("tests/golden exists")
+            .map(|entry| entry.expect("readable entry").path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "rs"))
+            .collect();
+        inputs.sort();
+        assert!(!inputs.is_empty());
+        for input in inputs {
+            let expanded = expand_source(&std::fs::read_to_string(&input).expect("readable input"));
+            let golden = input.with_extension("expanded");
+            if std::env::var("GOLDEN").as_deref() == Ok("overwrite") {
+                std::fs::write(&golden, &expanded).expect("writable golden file");
+                continue;
+            }
+            let expected = std::fs::read_to_string(&golden).unwrap_or_default();
+            assert!(expanded == expected, "{} no longer expands to {}; rerun with GOLDEN=overwrite if the change is intended\n{}",
+                input.display(), golden.display(), expanded);
+        }
+    }
+
+    #[test]
+    fn expansion_is_deterministic() {
+        let source = std::fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden/basic.rs")).expect("readable input");
+        assert_eq!(expand_source(&source), expand_source(&source));
+    }
+
+    #[test]
+    fn generated_base_expands_to_itself() {
+        let source = std::fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden/basic.rs")).expect("readable input");
+        let expanded = expand_source(&source);
+        let base = expanded.lines().next().expect("the base comes first");
+        let args = quote! { Create: requires(name), suffix = "Form" };
+        let restacked = expand_item(quote! { #[variants(#args)] #[derive(Debug)] }.into_iter().chain(base.parse::<TokenStream2>().expect("base tokens")).collect());
+        assert_eq!(restacked.lines().count(), 1);
+        assert!(restacked.contains("struct User"));
+        assert!(!restacked.contains("# [variants"));
+    }
+
+    #[test]
+    fn analysis_resolves_each_field() {
+        let args = quote! {
+            Create: requires(name).excludes(id).default(optional),
+            Update: requires(id).optional(all_fields().except(id)),
+            suffix = "Form"
+        };
+        let mut cfg = VariantList::parse(args.clone()).expect("arguments parse");
+        let input: DeriveInput = syn::parse_quote! {
+            struct User { id: u64, name: String, email: String }
+        };
+        let fields = cfg.analyze(&input).expect("fields resolve");
+        let [create, update] = cfg.contexts() else { panic!("two contexts") };
+        assert_eq!(cfg.variant_ident(create), "CreateForm");
+        assert_eq!(cfg.fluent_contexts().iter().map(|ctx| ctx.name().to_string()).collect::<Vec<_>>(), ["Create", "Update"]);
+
+        let roles: Vec<(String, bool, bool, bool)> = fields.iter()
+            .map(|fs| (fs.ident().to_string(), fs.is_in(create), fs.is_required_in(create), fs.is_required_in(update)))
+            .collect();
+        assert_eq!(roles, [
+            ("id".to_string(), false, false, true),
+            ("name".to_string(), true, true, false),
+            ("email".to_string(), true, false, false),
+        ]);
+        // Analyzing first leaves the expansion unchanged
+        let fresh = VariantList::parse(args).expect("arguments parse");
+        let expanded = |cfg, input| expand(cfg, input).expect("struct expands").to_string();
+        assert_eq!(expanded(cfg, input.clone()), expanded(fresh, input));
+    }
+
+    #[test]
+    fn context_docs_replace_struct_docs() {
+        let cfg = VariantList::parse(quote! {
+            Create: requires(name).excludes(id).doc("Payload accepted by POST /users").doc("Send it as JSON."),
+            Read: requires(id, name),
+            suffix = "Form"
+        }).expect("arguments parse");
+        let input: DeriveInput = syn::parse_quote! {
+            /// A registered user
+            #[doc(alias = "account")]
+            struct User { id: u64, name: String }
+        };
+        let file: syn::File = syn::parse2(expand(cfg, input).expect("struct expands")).expect("expansion parses");
+        let docs_of = |name: &str| -> Vec<String> {
+            let item = file.items.iter().find_map(|item| match item {
+                syn::Item::Struct(item) if item.ident == name => Some(item),
+                _ => None,
+            }).expect("struct is generated");
+            item.attrs.iter().filter(|attr| attr.path().is_ident("doc")).map(|attr| attr.meta.to_token_stream().to_string()).collect()
+        };
+        assert_eq!(docs_of("CreateForm"), [
+            r#"doc = "Payload accepted by POST /users""#,
+            r#"doc = "Send it as JSON.""#,
+            r#"doc (alias = "account")"#,
+            r#"doc (alias = "Create")"#,
+            r#"doc (alias = "UserCreate")"#,
+        ]);
+        // Contexts without .doc(...) keep the struct's docs
+        assert!(docs_of("ReadForm").contains(&r#"doc = r" A registered user""#.to_string()));
+    }
+
+    #[test]
+    fn max_visibility_caps_every_generated_item() {
+        let cfg = VariantList::parse(quote! {
+            Create: requires(name).optional(email).excludes(id).tag(http = "POST /users"),
+            Read: requires(id, name, email).key(id),
+            Update: requires(id).optional(name, email).at_least_one_of(name, email),
+            presence = true,
+            columns = true,
+            list = { Read: true },
+            from_value = { Create: true },
+            canonical_json = true,
+            field_iter = true,
+            field_consts = true,
+            constructors = true,
+            builder = true,
+            as_conversions = { Read: true },
+            max_visibility = pub(crate),
+            allow_public = true,
+            suffix = "Form"
+        }).expect("arguments parse");
+        let input: DeriveInput = syn::parse_quote! {
+            #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+            pub struct User { pub id: u64, pub name: String, pub email: String }
+        };
+        let file: syn::File = syn::parse2(expand(cfg, input).expect("struct expands")).expect("expansion parses");
+        let is_pub = |vis: &Visibility| matches!(vis, Visibility::Public(_));
+        let mut leaked = Vec::new();
+        for item in &file.items {
+            match item {
+                // The base keeps its own visibility
+                syn::Item::Struct(item) if item.ident == "User" => {}
+                syn::Item::Struct(item) if is_pub(&item.vis) || item.fields.iter().any(|field| is_pub(&field.vis)) => {
+                    leaked.push(item.ident.to_string());
+                }
+                syn::Item::Enum(item) if is_pub(&item.vis) => leaked.push(item.ident.to_string()),
+                syn::Item::Trait(item) if is_pub(&item.vis) => leaked.push(item.ident.to_string()),
+                syn::Item::Type(item) if is_pub(&item.vis) => leaked.push(item.ident.to_string()),
+                syn::Item::Impl(item) => {
+                    for impl_item in &item.items {
+                        match impl_item {
+                            syn::ImplItem::Fn(f) if is_pub(&f.vis) => leaked.push(f.sig.ident.to_string()),
+                            syn::ImplItem::Const(c) if is_pub(&c.vis) => leaked.push(c.ident.to_string()),
+                            _ => {}
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        assert!(leaked.is_empty(), "pub items under max_visibility = pub(crate): {:?}", leaked);
+    }
+}
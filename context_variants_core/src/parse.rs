@@ -0,0 +1,1859 @@
+//! Reading `#[variants(...)]` arguments, fluent chains and field attributes
+
+use super::*;
+
+/// One call in a fluent chain, e.g. `requires(name, email)`; arguments are parsed per method
+#[derive(Debug)]
+pub(crate) struct FluentCall {
+    method: Ident,
+    args: TokenStream2,
+}
+
+/// Parse a fluent chain: requires(name, email).optional(metadata).excludes(password)
+fn parse_fluent_chain(input: ParseStream) -> syn::Result<Vec<FluentCall>> {
+    let mut calls = Vec::new();
+    loop {
+        if !input.peek(syn::Ident) || !input.peek2(syn::token::Paren) {
+            return Err(input.error("expected function call like 'requires(field1, field2)'"));
+        }
+        let method: Ident = input.parse()?;
+        let content;
+        syn::parenthesized!(content in input);
+        calls.push(FluentCall { method, args: content.parse()? });
+        if !input.peek(syn::Token![.]) {
+            return Ok(calls);
+        }
+        let _: syn::Token![.] = input.parse()?;
+    }
+}
+
+/// Helper to parse fluent context expressions
+struct FluentContextParser;
+
+impl FluentContextParser {
+    /// Build a context from its parsed chain: requires(name, email).optional(metadata).excludes(password)
+    fn parse_fluent_calls(context_name: Ident, calls: Vec<FluentCall>) -> Result<FluentContext, syn::Error> {
+        let mut context = FluentContext {
+            name: context_name,
+            required_fields: Vec::new(),
+            optional_fields: Vec::new(),
+            excluded_fields: Vec::new(),
+            forbidden_fields: Vec::new(),
+            invariants: Vec::new(),
+            tags: Vec::new(),
+            key_fields: Vec::new(),
+            envelope: None,
+            verbatim_fields: Vec::new(),
+            computed_fields: Vec::new(),
+            custom_name: None,
+            derives: Vec::new(),
+            docs: Vec::new(),
+            role: None,
+            default_rules: Vec::new(),
+            default_behavior: None,
+            // Errors about the whole context point at its first method
+            end_span: calls[0].method.span(),
+        };
+        
+        for FluentCall { method, args } in calls {
+            let method_name = method.to_string();
+            match method_name.as_str() {
+                "requires" => context.required_fields.extend(Self::parse_field_list(args)?),
+                "optional" => context.optional_fields.extend(Self::parse_field_list(args)?),
+                "excludes" => context.excluded_fields.extend(Self::parse_field_list(args)?),
+                "forbid" => context.forbidden_fields.extend(Self::parse_field_list(args)?),
+                "at_least_one_of" | "one_of" => {
+                    let fields = Self::parse_field_list(args)?;
+                    context.invariants.push(Invariant::parse(&method_name, fields, method.span())?);
+                }
+                "tag" => {
+                    // Parse metadata: .tag(http = "POST /users", auth = "admin")
+                    let entries = syn::parse::Parser::parse2(syn::punctuated::Punctuated::<syn::MetaNameValue, syn::Token![,]>::parse_terminated, args)?;
+                    for entry in entries {
+                        let key = entry.path.get_ident().cloned()
+                            .ok_or_else(|| syn::Error::new(entry.path.span(), "expected tag name"))?;
+                        let syn::Expr::Lit(syn::ExprLit { lit, .. }) = entry.value else {
+                            return Err(syn::Error::new(entry.value.span(), "expected a literal tag value"));
+                        };
+                        if !matches!(lit, syn::Lit::Str(_) | syn::Lit::Int(_) | syn::Lit::Float(_) | syn::Lit::Bool(_)) {
+                            return Err(syn::Error::new(lit.span(), "expected a string, integer, float or bool tag value"));
+                        }
+                        if context.tags.iter().any(|(existing, _)| *existing == key) {
+                            return Err(syn::Error::new(key.span(), format!("duplicate tag '{}'", key)));
+                        }
+                        context.tags.push((key, lit));
+                    }
+                }
+                "key" => {
+                    // Parse key fields: .key(id, tenant_id)
+                    if !context.key_fields.is_empty() {
+                        return Err(syn::Error::new(method.span(), "key() can only be given once per context"));
+                    }
+                    let fields = syn::parse::Parser::parse2(|input: ParseStream| parse_ident_list(input, "key()"), args)?;
+                    if fields.is_empty() {
+                        return Err(syn::Error::new(method.span(), "key() expects at least one field"));
+                    }
+                    context.key_fields = fields;
+                }
+                "name" => {
+                    // Parse a custom struct name: .name("UserCreationPayload")
+                    if context.custom_name.is_some() {
+                        return Err(syn::Error::new(method.span(), "name() can only be given once per context"));
+                    }
+                    let lit = syn::parse2::<syn::LitStr>(args)
+                        .map_err(|_| syn::Error::new(method.span(), "expected name(\"TypeName\")"))?;
+                    let mut ident = lit.parse::<Ident>()
+                        .map_err(|_| syn::Error::new(lit.span(), "name() expects a type name, e.g. name(\"UserCreationPayload\")"))?;
+                    ident.set_span(lit.span());
+                    context.custom_name = Some(ident);
+                }
+                "envelope" => {
+                    // Parse the response envelope: .envelope(ApiResponse) or .envelope(ApiResponse, Read)
+                    if context.envelope.is_some() {
+                        return Err(syn::Error::new(method.span(), "envelope() can only be given once per context"));
+                    }
+                    let parser = |input: ParseStream| -> syn::Result<(syn::Path, Option<Ident>)> {
+                        let envelope: syn::Path = input.parse()?;
+                        let wrapped = if input.peek(syn::Token![,]) {
+                            let _: syn::Token![,] = input.parse()?;
+                            Some(input.parse::<Ident>()?.unraw())
+                        } else {
+                            None
+                        };
+                        let _: Option<syn::Token![,]> = input.parse()?;
+                        Ok((envelope, wrapped))
+                    };
+                    let envelope = syn::parse::Parser::parse2(parser, args)
+                        .map_err(|_| syn::Error::new(method.span(), "expected envelope(Type) or envelope(Type, Context)"))?;
+                    context.envelope = Some(envelope);
+                }
+                "verbatim_field" => {
+                    // Parse a raw field: .verbatim_field(#[serde(flatten)] pub extra: Map) or the same as a string
+                    let tokens = match syn::parse2::<syn::LitStr>(args.clone()) {
+                        Ok(lit) => lit.parse::<TokenStream2>()?,
+                        Err(_) => args,
+                    };
+                    let field = syn::parse::Parser::parse2(Field::parse_named, tokens)
+                        .map_err(|err| syn::Error::new(method.span(), format!("expected a named field like `pub extra: Type`: {}", err)))?;
+                    context.verbatim_fields.push(field);
+                }
+                "computed" => {
+                    // Parse derived fields: .computed(display_name: String = format!("{} {}", self.first, self.last)),
+                    // where the expression may also be given as a string
+                    let parser = |input: ParseStream| -> syn::Result<Vec<(Ident, Type, syn::Expr)>> {
+                        let mut fields = Vec::new();
+                        while !input.is_empty() {
+                            let name: Ident = input.parse()?;
+                            let _: syn::Token![:] = input.parse()?;
+                            let ty: Type = input.parse()?;
+                            let _: syn::Token![=] = input.parse()?;
+                            let value = match input.parse::<syn::Expr>()? {
+                                syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(code), .. }) => code.parse()?,
+                                value => value,
+                            };
+                            fields.push((name, ty, value));
+                            if !input.is_empty() {
+                                let _: syn::Token![,] = input.parse()?;
+                            }
+                        }
+                        Ok(fields)
+                    };
+                    let fields = syn::parse::Parser::parse2(parser, args)
+                        .map_err(|err| syn::Error::new(err.span(), format!("expected computed(name: Type = expression): {}", err)))?;
+                    context.computed_fields.extend(fields);
+                }
+                "default" => {
+                    // Parse default behavior: .default(optional), .default(required), .default(exclude)
+                    let behaviors = syn::parse::Parser::parse2(syn::punctuated::Punctuated::<Ident, syn::Token![,]>::parse_terminated, args)
+                        .map_err(|_| syn::Error::new(method.span(), "expected field name for default behavior"))?;
+                    if behaviors.len() != 1 {
+                        return Err(syn::Error::new(method.span(), "default() expects exactly one argument"));
+                    }
+                    if context.default_behavior == Some(DefaultBehavior::RespectBase) {
+                        return Err(syn::Error::new(method.span(), "default() conflicts with respect_base_optionality(); use only one"));
+                    }
+                    context.default_behavior = Some(DefaultBehavior::from_name(&behaviors[0].to_string(), method.span())?);
+                }
+                "doc" => {
+                    // Parse a doc line: .doc("Payload accepted by POST /users"); may be repeated
+                    let line = syn::parse2::<syn::LitStr>(args)
+                        .map_err(|_| syn::Error::new(method.span(), "expected doc(\"text\")"))?;
+                    context.docs.push(line);
+                }
+                "derive" => {
+                    // Parse extra derives: .derive(Default, Hash); may be repeated
+                    let derives = syn::parse::Parser::parse2(syn::punctuated::Punctuated::<syn::Path, syn::Token![,]>::parse_terminated, args)
+                        .map_err(|_| syn::Error::new(method.span(), "expected derive(Trait, ...)"))?;
+                    context.derives.extend(derives);
+                }
+                "role" => {
+                    // Parse .role(input) or .role(output)
+                    let role = syn::parse2::<Ident>(args)
+                        .ok()
+                        .and_then(|role| match role.to_string().as_str() {
+                            "input" => Some(ContextRole::Input),
+                            "output" => Some(ContextRole::Output),
+                            _ => None,
+                        })
+                        .ok_or_else(|| syn::Error::new(method.span(), "role() expects input or output"))?;
+                    if context.role.is_some() {
+                        return Err(syn::Error::new(method.span(), "role() may be given once per context"));
+                    }
+                    context.role = Some(role);
+                }
+                "default_for" => {
+                    // Parse .default_for(matching("*_at"), exclude): selectors, then the behavior for them
+                    let mut selectors = Self::parse_field_list(args)?;
+                    let behavior = match selectors.pop() {
+                        Some(FieldRef::Field(behavior)) if !selectors.is_empty() => DefaultBehavior::from_name(&behavior.to_string(), behavior.span())?,
+                        _ => return Err(syn::Error::new(method.span(), "expected default_for(selectors, behavior), e.g. default_for(matching(\"*_at\"), exclude)")),
+                    };
+                    if behavior == DefaultBehavior::RespectBase {
+                        return Err(syn::Error::new(method.span(), "default_for() takes required, optional or exclude"));
+                    }
+                    if let Some(FieldRef::RequiredIn { context, .. }) = selectors.iter().find(|field_ref| matches!(field_ref, FieldRef::RequiredIn { .. })) {
+                        return Err(syn::Error::new(context.span(), "fields_required_in() is not supported in default_for(); list the fields or use matching(...)"));
+                    }
+                    context.default_rules.push((selectors, behavior));
+                }
+                "respect_base_optionality" => {
+                    // Unmentioned fields follow the base: Option fields are optional, the rest required
+                    if !args.is_empty() {
+                        return Err(syn::Error::new(method.span(), "respect_base_optionality() takes no arguments"));
+                    }
+                    if context.default_behavior.is_some() {
+                        return Err(syn::Error::new(method.span(), "respect_base_optionality() conflicts with default(); use only one"));
+                    }
+                    context.default_behavior = Some(DefaultBehavior::RespectBase);
+                }
+                _ => {
+                    let fluent_methods = fluent_methods();
+                    let quoted: Vec<String> = fluent_methods.iter().map(|name| format!("'{}'", name)).collect();
+                    let (last, rest) = quoted.split_last().expect("there are fluent methods");
+                    let expected = format!("expected {}, or {}", rest.join(", "), last);
+                    let message = match suggest_name(&method_name, &fluent_methods) {
+                        Some(suggestion) => format!("{}; did you mean '{}'?", expected, suggestion),
+                        None => expected,
+                    };
+                    return Err(syn::Error::new(method.span(), message));
+                }
+            }
+        }
+        
+        Ok(context)
+    }
+    
+    fn parse_field_list(args: TokenStream2) -> Result<Vec<FieldRef>, syn::Error> {
+        let fields = syn::parse::Parser::parse2(syn::punctuated::Punctuated::<FieldRef, syn::Token![,]>::parse_terminated, args)?;
+        Ok(fields.into_iter().collect())
+    }
+}
+
+impl syn::parse::Parse for FieldRef {
+    /// One selector: `name`, `name as Type`, `all_fields()`, `all_fields().except(a)`, `group.except(a)`
+    /// or `fields_required_in(Context)`
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if !input.peek(syn::Ident) {
+            return Err(input.error("expected field name, all_fields() function, or group name"));
+        }
+        let name: Ident = input.parse()?;
+        
+        if name == "fields_required_in" && input.peek(syn::token::Paren) {
+            // fields_required_in(Create), optionally followed by .except(c)
+            let content;
+            syn::parenthesized!(content in input);
+            let context = content.parse::<Ident>()
+                .map_err(|_| syn::Error::new(name.span(), "fields_required_in() expects one context name"))?
+                .unraw();
+            if !content.is_empty() {
+                return Err(syn::Error::new(name.span(), "fields_required_in() expects one context name"));
+            }
+            let except = if input.peek(syn::Token![.]) { parse_except_call(input)? } else { Vec::new() };
+            return Ok(FieldRef::RequiredIn { context, except, fields: None });
+        }
+        
+        if name == "serde" && input.peek(syn::token::Paren) {
+            // serde("user_name") selects the field serialized under that name
+            let content;
+            syn::parenthesized!(content in input);
+            let key: syn::LitStr = content.parse()
+                .map_err(|_| syn::Error::new(name.span(), "serde() expects the field's serialized name as a string"))?;
+            if !content.is_empty() {
+                return Err(syn::Error::new(name.span(), "serde() expects the field's serialized name as a string"));
+            }
+            let variant_type = if input.peek(syn::Token![as]) {
+                let _: syn::Token![as] = input.parse()?;
+                Some(input.parse()?)
+            } else {
+                None
+            };
+            return Ok(FieldRef::SerdeName { key, variant_type });
+        }
+
+        if name == "matching" && input.peek(syn::token::Paren) {
+            // matching("*_at") selects fields by a glob over their names
+            let content;
+            syn::parenthesized!(content in input);
+            let pattern: syn::LitStr = content.parse()
+                .map_err(|_| syn::Error::new(name.span(), "matching() expects a name pattern like \"*_at\""))?;
+            if !content.is_empty() {
+                return Err(syn::Error::new(name.span(), "matching() expects a name pattern like \"*_at\""));
+            }
+            return Ok(FieldRef::Matching { pattern });
+        }
+
+        if name == "all_fields" && input.peek(syn::token::Paren) {
+            // all_fields() or all_fields(a, b), optionally followed by .except(c) and .except_groups(g)
+            let content;
+            syn::parenthesized!(content in input);
+            let mut except = parse_ident_list(&content, "all_fields()")?;
+            let mut except_groups = Vec::new();
+            while input.peek(syn::Token![.]) {
+                let fork = input.fork();
+                let _: syn::Token![.] = fork.parse()?;
+                if fork.parse::<Ident>().is_ok_and(|method| method == "except_groups") {
+                    let _: syn::Token![.] = input.parse()?;
+                    let _: Ident = input.parse()?;
+                    let content;
+                    syn::parenthesized!(content in input);
+                    except_groups.extend(parse_ident_list(&content, "except_groups()")?);
+                } else {
+                    except.extend(parse_except_call(input)?);
+                }
+            }
+            return Ok(FieldRef::AllFields { except, except_groups });
+        }
+        if input.peek(syn::token::Paren) {
+            return Err(syn::Error::new(name.span(), "unsupported function call"));
+        }
+        
+        if input.peek(syn::Token![as]) {
+            // Handle "field_name as Type" syntax; the type is parsed with the full type grammar
+            let _: syn::Token![as] = input.parse()?;
+            let variant_type: Type = input.parse()?;
+            if !input.is_empty() && !input.peek(syn::Token![,]) {
+                return Err(input.error(format!("expected `,` after the type of '{}'", name)));
+            }
+            return Ok(FieldRef::FieldWithType { field: name, variant_type });
+        }
+        
+        if input.peek(syn::Token![.]) {
+            // Handle group.except(field1, field2)
+            let except = parse_except_call(input)?;
+            return Ok(FieldRef::GroupWithExcept { group: name, except });
+        }
+        
+        Ok(FieldRef::Field(name))
+    }
+}
+
+/// Parse `.except(a, b)` following a selector
+fn parse_except_call(input: ParseStream) -> syn::Result<Vec<Ident>> {
+    let _: syn::Token![.] = input.parse()?;
+    let method: Ident = input.parse()?;
+    if method != "except" || !input.peek(syn::token::Paren) {
+        return Err(syn::Error::new(method.span(), "unsupported method call"));
+    }
+    let content;
+    syn::parenthesized!(content in input);
+    parse_ident_list(&content, "except()")
+}
+
+/// Parse comma-separated field names inside `all_fields(...)` or `except(...)`
+fn parse_ident_list(input: ParseStream, context: &str) -> syn::Result<Vec<Ident>> {
+    let mut names = Vec::new();
+    while !input.is_empty() {
+        if !input.peek(syn::Ident) {
+            return Err(input.error(format!("expected field name in {} arguments", context)));
+        }
+        names.push(input.parse()?);
+        if !input.is_empty() {
+            let _: syn::Token![,] = input.parse()?;
+        }
+    }
+    Ok(names)
+}
+
+/// Parse `#[ctx_shared]` or `#[ctx_shared(Read, read_set)]` into the variants it applies to
+pub(crate) fn parse_ctx_shared_attribute(attr: &Attribute, cfg: &VariantList) -> Result<Vec<Ident>, syn::Error> {
+    match &attr.meta {
+        Meta::Path(_) => Ok(cfg.variants.clone()),
+        Meta::List(list) => {
+            let names = list.parse_args_with(syn::punctuated::Punctuated::<Ident, syn::Token![,]>::parse_terminated)?;
+            let mut contexts = Vec::new();
+            for name in &names {
+                contexts.extend(resolve_keyed_contexts(&name.unraw(), &cfg.context_sets, &cfg.variants)?);
+            }
+            Ok(contexts)
+        }
+        Meta::NameValue(nv) => Err(syn::Error::new(nv.span(), "expected #[ctx_shared] or #[ctx_shared(Context, ...)]")),
+    }
+}
+
+/// Parse `#[ctx_flatten_into(Create, write_set, fields(host: String, port: u16))]` on a field of type `ty`;
+/// without a context it applies to every variant
+pub(crate) fn parse_ctx_flatten_into_attribute(attr: &Attribute, ty: &Type, cfg: &VariantList) -> Result<FlattenInto, syn::Error> {
+    let usage = "expected #[ctx_flatten_into(Context, ..., fields(name: Type, ...))]";
+    let Meta::List(list) = &attr.meta else {
+        return Err(syn::Error::new(attr.span(), usage));
+    };
+    let mut names: Vec<Ident> = Vec::new();
+    let mut fields: Vec<(Ident, Type)> = Vec::new();
+    let parser = |input: ParseStream| -> syn::Result<()> {
+        while !input.is_empty() {
+            let name: Ident = input.parse()?;
+            if name == "fields" && input.peek(syn::token::Paren) {
+                let content;
+                syn::parenthesized!(content in input);
+                while !content.is_empty() {
+                    let field: Ident = content.parse()?;
+                    let _: syn::Token![:] = content.parse()?;
+                    let ty: Type = content.parse()?;
+                    if fields.iter().any(|(existing, _)| *existing == field) {
+                        return Err(syn::Error::new(field.span(), format!("'{}' is listed twice in fields(...)", field)));
+                    }
+                    fields.push((field, ty));
+                    if !content.is_empty() {
+                        let _: syn::Token![,] = content.parse()?;
+                    }
+                }
+            } else {
+                names.push(name);
+            }
+            if !input.is_empty() {
+                let _: syn::Token![,] = input.parse()?;
+            }
+        }
+        Ok(())
+    };
+    list.parse_args_with(parser)?;
+    if fields.is_empty() {
+        return Err(syn::Error::new(list.span(), usage));
+    }
+    // Conversions name the type in a struct expression, so it must be a plain path
+    let mut path = match ty {
+        Type::Path(type_path) if type_path.qself.is_none() && !is_option_type(ty) => type_path.path.clone(),
+        other => return Err(syn::Error::new(other.span(), "#[ctx_flatten_into] needs a struct type")),
+    };
+    for segment in &mut path.segments {
+        if let syn::PathArguments::AngleBracketed(args) = &mut segment.arguments {
+            args.colon2_token = Some(Default::default());
+        }
+    }
+    let mut contexts = Vec::new();
+    for name in &names {
+        contexts.extend(resolve_keyed_contexts(&name.unraw(), &cfg.context_sets, &cfg.variants)?);
+    }
+    if contexts.is_empty() {
+        contexts = cfg.variants.clone();
+    }
+    Ok(FlattenInto { contexts, path, fields })
+}
+
+/// Parse `#[ctx_on_convert(Create = "path::to::hook", ...)]` into a hook per context; a context set names each of its contexts
+pub(crate) fn parse_ctx_on_convert_attribute(attr: &Attribute, cfg: &VariantList) -> Result<Vec<(Ident, syn::Path)>, syn::Error> {
+    let entries = attr.parse_args_with(syn::punctuated::Punctuated::<syn::MetaNameValue, syn::Token![,]>::parse_terminated)?;
+    let mut hooks = Vec::new();
+    for entry in entries {
+        let key = entry.path.get_ident()
+            .ok_or_else(|| syn::Error::new(entry.path.span(), "expected #[ctx_on_convert(Context = \"path::to::hook\")]"))?;
+        let hook = parse_path_value(&entry.value)?;
+        for ctx in resolve_keyed_contexts(&key.unraw(), &cfg.context_sets, &cfg.variants)? {
+            hooks.push((ctx, hook.clone()));
+        }
+    }
+    Ok(hooks)
+}
+
+/// Parse `#[ctx_encrypt(with = "path")]` or `#[ctx_encrypt(Read, storage, with = "path")]`
+/// into the module path and the variants that store ciphertext
+pub(crate) fn parse_ctx_encrypt_attribute(attr: &Attribute, cfg: &VariantList) -> Result<(syn::Path, Vec<Ident>), syn::Error> {
+    let usage = "expected #[ctx_encrypt(with = \"path::to::module\")] or #[ctx_encrypt(Context, ..., with = \"path::to::module\")]";
+    let Meta::List(list) = &attr.meta else {
+        return Err(syn::Error::new(attr.span(), usage));
+    };
+    let items = list.parse_args_with(syn::punctuated::Punctuated::<Meta, syn::Token![,]>::parse_terminated)?;
+    let mut module = None;
+    let mut contexts = Vec::new();
+    for item in &items {
+        match item {
+            Meta::Path(path) if path.get_ident().is_some() => {
+                let name = path.get_ident().expect("checked above").unraw();
+                contexts.extend(resolve_keyed_contexts(&name, &cfg.context_sets, &cfg.variants)?);
+            }
+            Meta::NameValue(nv) if nv.path.is_ident("with") => {
+                let lit = parse_string_value(&nv.value)?;
+                module = Some(syn::parse_str::<syn::Path>(&lit).map_err(|_| {
+                    syn::Error::new(nv.value.span(), format!("'{}' is not a module path", lit))
+                })?);
+            }
+            other => return Err(syn::Error::new(other.span(), usage)),
+        }
+    }
+    let module = module.ok_or_else(|| syn::Error::new(list.span(), usage))?;
+    if contexts.is_empty() {
+        contexts = cfg.variants.clone();
+    }
+    Ok((module, contexts))
+}
+
+/// Parse `max_visibility = pub(crate)`, `pub(super)`, `pub` or `private`
+fn parse_visibility_value(value: &syn::Expr) -> Result<Visibility, syn::Error> {
+    let error = || syn::Error::new(value.span(), "expected a visibility like pub(crate), pub(super), pub or private");
+    match value {
+        syn::Expr::Verbatim(tokens) => syn::parse2(tokens.clone()).map_err(|_| error()),
+        syn::Expr::Path(path) if path.path.is_ident("private") => Ok(Visibility::Inherited),
+        _ => Err(error()),
+    }
+}
+
+/// Parse when_* attribute to extract the inner attribute.
+/// Example: #[when_optional(serde(skip_serializing_if = "Option::is_none"))]
+/// Should extract: #[serde(skip_serializing_if = "Option::is_none")]
+pub(crate) fn parse_ctx_attr_attribute(attr: &Attribute) -> Result<Attribute, syn::Error> {
+    let meta = attr.meta.clone();
+    match meta {
+        Meta::List(list) => {
+            // The inner attribute's arguments are kept as written, whatever their syntax
+            let inner_meta = list.parse_args_with(parse_raw_meta)?;
+            
+            // Convert Meta back to Attribute
+            Ok(Attribute {
+                pound_token: syn::Token![#](attr.span()),
+                style: syn::AttrStyle::Outer,
+                bracket_token: syn::token::Bracket(attr.span()),
+                meta: inner_meta,
+            })
+        }
+        _ => {
+            Err(syn::Error::new(meta.span(), "expected a list with inner attribute"))
+        }
+    }
+}
+
+/// Parse one attribute body (`name`, `name(...)` or `name = value`) without parsing its arguments.
+/// A value that is not a Rust expression is kept as verbatim tokens.
+fn parse_raw_meta(input: ParseStream) -> syn::Result<Meta> {
+    let path = input.call(syn::Path::parse_mod_style)?;
+    if input.is_empty() || input.peek(syn::Token![,]) {
+        return Ok(Meta::Path(path));
+    }
+    if input.peek(syn::Token![=]) {
+        let eq_token: syn::Token![=] = input.parse()?;
+        let mut tokens = TokenStream2::new();
+        while !input.is_empty() && !input.peek(syn::Token![,]) {
+            tokens.extend(std::iter::once(input.parse::<proc_macro2::TokenTree>()?));
+        }
+        if tokens.is_empty() {
+            return Err(syn::Error::new(eq_token.span, "expected a value after `=`"));
+        }
+        let value = syn::parse2(tokens.clone()).unwrap_or(syn::Expr::Verbatim(tokens));
+        return Ok(Meta::NameValue(syn::MetaNameValue { path, eq_token, value }));
+    }
+    let group: proc_macro2::Group = input.parse()
+        .map_err(|_| syn::Error::new(input.span(), "expected an attribute like `name`, `name(...)` or `name = value`"))?;
+    let delimiter = match group.delimiter() {
+        proc_macro2::Delimiter::Bracket => syn::MacroDelimiter::Bracket(syn::token::Bracket(group.delim_span())),
+        proc_macro2::Delimiter::Brace => syn::MacroDelimiter::Brace(syn::token::Brace(group.delim_span())),
+        _ => syn::MacroDelimiter::Paren(syn::token::Paren(group.delim_span())),
+    };
+    Ok(Meta::List(syn::MetaList { path, delimiter, tokens: group.stream() }))
+}
+
+/// Parse mixed syntax: fluent contexts (Create: requires(name)) and traditional (suffix = "Form")
+pub(crate) fn parse_mixed_args(args: TokenStream2) -> Result<VariantList, syn::Error> {
+    let mut variants = Vec::new();
+    let mut fluent_contexts = Vec::new();
+    let mut prefix = None;
+    let mut suffix = None;
+    let mut global_default = None;
+    let mut field_groups = IndexMap::new();
+    let mut default_optional_attrs: Vec<Attribute> = Vec::new();
+    let mut base_struct_attrs: Vec<Attribute> = Vec::new();
+    let mut explain = false;
+    let mut preserve_attr_text = None;
+    let mut default_required_attrs: Vec<Attribute> = Vec::new();
+    let mut scoped_optional_entries = Vec::new();
+    let mut scoped_required_entries = Vec::new();
+    let mut build_base = true;
+    let mut base_name = None;
+    let mut optional_base = false;
+    let mut context_sets = IndexMap::new();
+    let mut set_defaults = Vec::new();
+    let mut global_required_wrapper = None;
+    let mut flags: IndexMap<&str, ContextFlag> = context_flag_names().map(|name| (name, ContextFlag::default())).collect();
+    let mut wrapper_entries = Vec::new();
+    let mut list_cursor = None;
+    let mut ctor_order = CtorOrder::Declaration;
+    let mut cross_conversions = false;
+    let mut max_visibility = None;
+    let mut allow_public = false;
+    let mut diff = None;
+    let mut instrument = None;
+    let mut dedup_variants = false;
+    let mut report_stats = false;
+    let mut allow_option_required = false;
+    let mut warn_dead_attrs = false;
+    let mut emit_markers = None;
+    let mut for_each_macro = None;
+    let mut copy_field_docs = FieldDocs::Copy;
+    let mut field_match = FieldMatch::Exact;
+    let mut all_fields_includes: Vec<SkipMarker> = Vec::new();
+    let mut type_mappings = Vec::new();
+    let mut any_variant = None;
+    let mut context_variant = None;
+    let mut enum_wrapper = None;
+    let mut serde_try_from = false;
+    let mut parse_any = false;
+    let mut field_traits = false;
+    let mut same_context = None;
+    let mut same_context_families = Vec::new();
+    let mut post_process = None;
+    let mut registry = None;
+    let mut error_messages = None;
+    let mut inherit_defaults = false;
+
+    let input = syn::parse::Parser::parse2(
+        syn::punctuated::Punctuated::<MixedArg, syn::Token![,]>::parse_terminated,
+        args,
+    )?;
+    
+    // Process the parsed items, collecting every error so they are reported together
+    let mut errors = None;
+    let items = input.into_iter().flat_map(|item| match item {
+        MixedArg::Config { args } => args,
+        other => vec![other],
+    });
+    for item in items {
+        if let MixedArg::NameValue { name, .. } | MixedArg::Keyed { name, .. } | MixedArg::Scoped { name, .. } = &item
+            && let Err(err) = check_feature_enabled(name) {
+            push_error(&mut errors, err);
+            continue;
+        }
+        let result = match item {
+            MixedArg::Path { name } => {
+                variants.push(name);
+                Ok(())
+            }
+            MixedArg::NameValue { name, value } => {
+                match name.to_string().as_str() {
+                    // Parse prefix = "Api" and suffix = "Request"
+                    "prefix" => parse_string_value(&value).map(|value| prefix = Some(value)),
+                    "suffix" => parse_string_value(&value).map(|value| suffix = Some(value)),
+                    // Parse global default: default = optional, default = "required", default = exclude
+                    "default" => parse_default_value(&value).map(|value| global_default = Some(value)),
+                    // Parse required_wrapper = Validated (or "Validated")
+                    "required_wrapper" => parse_path_value(&value).map(|value| global_required_wrapper = Some(value)),
+                    // Parse context_sets = (write(Create, Update), read(Read))
+                    "context_sets" => parse_context_sets(&value).map(|value| context_sets = value),
+                    // Parse groups = (auth(user_id, token), contact(name, email)); repeated parameters are merged
+                    "groups" => parse_groups_expression(&value)
+                        .and_then(|groups| groups.into_values().try_for_each(|group| insert_group(&mut field_groups, group))),
+                    // Parse optional_attrs = [serde(skip_serializing_if = "Option::is_none"), serde(default)]
+                    "optional_attrs" => parse_attribute_array(&value).map(|value| default_optional_attrs = value),
+                    // Parse required_attrs = [serde(deny_unknown_fields = false)]
+                    "required_attrs" => parse_attribute_array(&value).map(|value| default_required_attrs = value),
+                    // Parse base_attrs = [derive(sqlx::FromRow), serde(deny_unknown_fields)]
+                    "base_attrs" => parse_attribute_array(&value).map(|value| base_struct_attrs = value),
+                    // Parse explain = true
+                    "explain" => parse_bool_value(&value).map(|value| explain = value),
+                    // Parse build_base = true or build_base = false
+                    "build_base" => parse_bool_value(&value).map(|value| build_base = value),
+                    // Parse base_name = "UserRecord"
+                    "base_name" => parse_string_value(&value).and_then(|name| syn::parse_str::<Ident>(&name)
+                        .map(|mut ident| {
+                            ident.set_span(value.span());
+                            base_name = Some(ident);
+                        })
+                        .map_err(|_| syn::Error::new(value.span(), "base_name expects a type name, e.g. \"UserRecord\""))),
+                    // Parse optional_base = true or optional_base = false
+                    "optional_base" => parse_bool_value(&value).map(|value| optional_base = value),
+                    // Parse list_cursor = "next_page_token"
+                    "list_cursor" => parse_string_value(&value)
+                        .and_then(|name| syn::parse_str::<Ident>(&name)
+                            .map_err(|_| syn::Error::new(value.span(), "expected a field name like \"next_page_token\"")))
+                        .map(|name| list_cursor = Some(name)),
+                    // Parse emit_markers = true / doc
+                    "emit_markers" => MarkerStyle::parse(&value).map(|value| emit_markers = value),
+                    // Parse for_each_macro = true / export
+                    "for_each_macro" => ForEachMacro::parse(&value).map(|value| for_each_macro = value),
+                    // Parse copy_field_docs = false / link
+                    "copy_field_docs" => FieldDocs::parse(&value).map(|value| copy_field_docs = value),
+                    // Parse field_match = case_insensitive
+                    "field_match" => FieldMatch::parse(&value).map(|value| field_match = value),
+                    // Parse all_fields_includes = [passthrough, cfg, serde_skip]
+                    "all_fields_includes" => SkipMarker::parse_list(&value).map(|value| all_fields_includes = value),
+                    // Parse preserve_attr_text = [builder], deprecated now that every attribute is passed through as written
+                    "preserve_attr_text" => parse_attr_names(&value).map(|_| preserve_attr_text = Some(name.span())),
+                    // Parse allow_option_required = true
+                    "allow_option_required" => parse_bool_value(&value).map(|value| allow_option_required = value),
+                    // Parse warn_dead_attrs = true
+                    "warn_dead_attrs" => parse_bool_value(&value).map(|value| warn_dead_attrs = value),
+                    // Parse report_stats = true
+                    "report_stats" => parse_bool_value(&value).map(|value| report_stats = value),
+                    // Parse dedup_variants = true
+                    "dedup_variants" => parse_bool_value(&value).map(|value| dedup_variants = value),
+                    // Parse instrument = tracing
+                    "instrument" => parse_path_value(&value).map(|path| instrument = Some(path)),
+                    // Parse ctor_order = required_first
+                    "ctor_order" => CtorOrder::parse(&value).map(|value| ctor_order = value),
+                    // Parse cross_conversions = true
+                    "cross_conversions" => parse_bool_value(&value).map(|value| cross_conversions = value),
+                    // Parse max_visibility = pub(crate) and allow_public = true
+                    "max_visibility" => parse_visibility_value(&value).map(|value| max_visibility = Some(value)),
+                    "allow_public" => parse_bool_value(&value).map(|value| allow_public = value),
+                    // Parse diff = Update
+                    "diff" => parse_path_value(&value)
+                        .and_then(|path| path.get_ident().cloned()
+                            .ok_or_else(|| syn::Error::new(value.span(), "expected a context name like `diff = Update`")))
+                        .map(|context| diff = Some(context)),
+                    // Parse inherit_defaults = true
+                    "inherit_defaults" => parse_bool_value(&value).map(|value| inherit_defaults = value),
+                    // Parse post_process = my_macros::audit_dto
+                    "post_process" => parse_path_value(&value).map(|value| post_process = Some(value)),
+                    // Parse registry = crate::register_dto
+                    "registry" => parse_path_value(&value).map(|value| registry = Some(value)),
+                    // Parse error_messages = my_crate::messages
+                    "error_messages" => parse_path_value(&value).map(|value| error_messages = Some(value)),
+                    // Parse any_variant = crate::AnyVariant
+                    "any_variant" => parse_path_value(&value).map(|value| any_variant = Some(value)),
+                    // Parse field_traits = true
+                    "field_traits" => parse_bool_value(&value).map(|value| field_traits = value),
+                    // Parse parse_any = true
+                    "parse_any" => parse_bool_value(&value).map(|value| parse_any = value),
+                    // Parse serde_try_from = true
+                    "serde_try_from" => parse_bool_value(&value).map(|value| serde_try_from = value),
+                    // Parse enum_wrapper = "UserPayload"
+                    "enum_wrapper" => parse_string_value(&value).and_then(|name| syn::parse_str::<Ident>(&name)
+                        .map(|mut ident| {
+                            ident.set_span(value.span());
+                            enum_wrapper = Some(ident);
+                        })
+                        .map_err(|_| syn::Error::new(value.span(), "enum_wrapper expects a type name, e.g. \"UserPayload\""))),
+                    // Parse context_variant = crate::ContextVariant
+                    "context_variant" => parse_path_value(&value).map(|value| context_variant = Some(value)),
+                    // Parse same_context = crate::SameContext
+                    "same_context" => parse_path_value(&value).map(|value| same_context = Some(value)),
+                    // Parse same_context_families = ["Admin{}Form", "{}TenantForm"]
+                    "same_context_families" => parse_name_templates(&value).map(|templates| same_context_families.extend(templates)),
+                    // Parse uuid_as_string = [Create, Update], shorthand for type_map = { Uuid => String in [...] }
+                    "uuid_as_string" => parse_context_list(&value).map(|contexts| {
+                        type_mappings.push(TypeMapping {
+                            from: syn::parse_quote! { Uuid },
+                            to: syn::parse_quote! { ::std::string::String },
+                            contexts,
+                        });
+                    }),
+                    "type_map" => Err(syn::Error::new(value.span(), "expected type_map = { From => To in [Context, ...] }")),
+                    // Parse from_ref = true and the other context flags
+                    other if flags.contains_key(other) => parse_bool_value(&value).map(|value| flags[other].everywhere = value),
+                    other => Err(syn::Error::new(name.span(), match suggest_name(other, &parameter_names()) {
+                        Some(suggestion) => format!("unknown parameter; did you mean '{}'?", suggestion),
+                        None => "unknown parameter".to_string(),
+                    })),
+                }
+            }
+            MixedArg::FluentContext { name, calls } => {
+                // Build the fluent context; the name is a variant either way
+                variants.push(name.clone());
+                FluentContextParser::parse_fluent_calls(name, calls).map(|fluent_ctx| fluent_contexts.push(fluent_ctx))
+            }
+            MixedArg::TypeMap { mappings } => {
+                type_mappings.extend(mappings);
+                Ok(())
+            }
+            MixedArg::Config { .. } => unreachable!("config blocks are flattened before processing"),
+            MixedArg::Scoped { name, contexts, value } => {
+                match name.to_string().as_str() {
+                    // Parse optional_attrs(Update) = [serde(skip_serializing_if = "Option::is_none")]
+                    "optional_attrs" => parse_attribute_array(&value).map(|attrs| scoped_optional_entries.push((contexts, attrs))),
+                    "required_attrs" => parse_attribute_array(&value).map(|attrs| scoped_required_entries.push((contexts, attrs))),
+                    _ => Err(syn::Error::new(name.span(), format!("'{}' cannot be scoped to contexts; only optional_attrs and required_attrs take `(Context, ...)`", name))),
+                }
+            }
+            MixedArg::Keyed { name, entries } => {
+                match name.to_string().as_str() {
+                    "defaults" => {
+                        set_defaults.extend(entries);
+                        Ok(())
+                    }
+                    "required_wrapper" => {
+                        wrapper_entries.extend(entries);
+                        Ok(())
+                    }
+                    // Parse from_ref = { Read: true } and the other context flags
+                    other if flags.contains_key(other) => {
+                        flags[other].entries.extend(entries);
+                        Ok(())
+                    }
+                    _ => Err(syn::Error::new(name.span(), format!("'{}' does not accept per-context values", name))),
+                }
+            }
+        };
+        if let Err(err) = result {
+            push_error(&mut errors, err);
+        }
+    }
+    
+    if variants.is_empty() {
+        push_error(&mut errors, syn::Error::new(proc_macro2::Span::call_site(), "no variants specified"));
+    }
+
+    // Context sets and `defaults = { ... }` may appear anywhere, so resolve them once all contexts are known
+    for set in context_sets.values() {
+        for ctx in &set.contexts {
+            if !variants.iter().any(|v| v == ctx) {
+                push_error(&mut errors, syn::Error::new(ctx.span(), format!("unknown context '{}' in context set '{}'", ctx, set.name)));
+            }
+        }
+    }
+    // Parameters that need the base struct, each with what it does with it
+    let call_site = proc_macro2::Span::call_site();
+    let needs_base = [
+        (flags["from_ref"].is_set().then_some(call_site), "from_ref converts from the base struct"),
+        ((copy_field_docs == FieldDocs::Link).then_some(call_site), "copy_field_docs = link points at the base struct"),
+        (flags["conversions"].is_set().then_some(call_site), "conversions convert into the base struct"),
+        (flags["as_conversions"].is_set().then_some(call_site), "as_conversions convert to and from the base struct"),
+        (flags["from_parts"].is_set().then_some(call_site), "from_parts builds the base struct"),
+        (flags["try_from"].is_set().then_some(call_site), "try_from converts from the base struct"),
+        (diff.as_ref().map(Ident::span), "diff compares base structs"),
+        (flags["apply_to"].is_set().then_some(call_site), "apply_to writes into the base struct"),
+        (explain.then_some(call_site), "explain documents the base struct"),
+        (base_name.as_ref().map(Ident::span), "base_name renames the base struct"),
+        (base_struct_attrs.first().map(|attr| attr.span()), "base_attrs needs the base struct"),
+        (inherit_defaults.then_some(call_site), "inherit_defaults needs the base struct"),
+        (serde_try_from.then_some(call_site), "serde_try_from deserializes the base struct"),
+        (parse_any.then_some(call_site), "parse_any is an associated function of the base struct"),
+        (context_variant.as_ref().map(|path| path.span()), "context_variant names the base struct as `Base`"),
+    ];
+    if !build_base {
+        for (span, what) in needs_base.into_iter().filter_map(|(span, what)| Some((span?, what))) {
+            push_error(&mut errors, syn::Error::new(span, format!("{}; remove build_base = false", what)));
+        }
+    }
+    if let Some(context) = diff.as_ref().filter(|context| !variants.contains(context)) {
+        push_error(&mut errors, syn::Error::new(context.span(), format!("unknown context '{}' in diff", context)));
+    }
+    if serde_try_from && enum_wrapper.is_none() {
+        push_error(&mut errors, syn::Error::new(call_site, "serde_try_from deserializes through the enum_wrapper enum; set enum_wrapper = \"...\""));
+    }
+    match (&same_context, same_context_families.first()) {
+        (Some(path), None) => push_error(&mut errors, syn::Error::new(path.span(), "same_context needs the related families; add same_context_families = [\"Admin{}Form\"]")),
+        (None, Some(template)) => push_error(&mut errors, syn::Error::new(template.span(), "same_context_families needs the conversion trait; add same_context = path::to::SameContext")),
+        _ => {}
+    }
+    // Contexts a flag is on for, and the contexts named for it in `name = { ... }`; a flag
+    // that fails to resolve counts as off so the remaining checks still run
+    let enabled_in = |errors: &mut Option<syn::Error>, name: &str| or_push_error(errors, resolve_context_flag(&flags[name], &context_sets, &variants));
+    let named_in = |errors: &mut Option<syn::Error>, name: &str| or_push_error(errors, resolve_context_flag(&flags[name].named(), &context_sets, &variants));
+    let context_defaults = or_push_error(&mut errors, resolve_context_defaults(&set_defaults, &context_sets, &variants));
+    let required_wrappers = or_push_error(&mut errors, resolve_required_wrappers(global_required_wrapper, &wrapper_entries, &context_sets, &variants));
+    let share_heavy_in = enabled_in(&mut errors, "share_heavy_fields");
+    let assert_auto_traits_in = enabled_in(&mut errors, "assert_auto_traits");
+    let presence_in = enabled_in(&mut errors, "presence");
+    let columns_in = enabled_in(&mut errors, "columns");
+    let list_in = enabled_in(&mut errors, "list");
+    let scoped_optional_attrs = or_push_error(&mut errors, resolve_scoped_attrs(scoped_optional_entries, &context_sets, &variants));
+    let scoped_required_attrs = or_push_error(&mut errors, resolve_scoped_attrs(scoped_required_entries, &context_sets, &variants));
+    let canonical_json_in = enabled_in(&mut errors, "canonical_json");
+    let from_value_in = enabled_in(&mut errors, "from_value");
+    let field_iter_in = enabled_in(&mut errors, "field_iter");
+    let serialize_required_in = enabled_in(&mut errors, "serialize_required");
+    let field_consts_in = enabled_in(&mut errors, "field_consts");
+    let mut from_ref_in = enabled_in(&mut errors, "from_ref");
+    let conversions_in = enabled_in(&mut errors, "conversions");
+    let conversions_named = named_in(&mut errors, "conversions");
+    let from_parts_in = enabled_in(&mut errors, "from_parts");
+    let from_parts_named = named_in(&mut errors, "from_parts");
+    let as_conversions_in = enabled_in(&mut errors, "as_conversions");
+    let as_conversions_named = named_in(&mut errors, "as_conversions");
+    let try_from_in = enabled_in(&mut errors, "try_from");
+    // Both directions overlap with the From/TryFrom impls of conversions and try_from
+    for ctx in &as_conversions_in {
+        for (other, other_in) in [("conversions", &conversions_in), ("try_from", &try_from_in)] {
+            if other_in.contains(ctx) {
+                push_error(&mut errors, syn::Error::new(ctx.span(), format!("as_conversions and {} both convert {} to or from the base; enable only one of them for {}", other, ctx, ctx)));
+            }
+        }
+    }
+    // serde_try_from turns every case of the wrapper into the base
+    if serde_try_from && build_base
+        && let Some(ctx) = variants.iter().find(|ctx| !conversions_in.contains(ctx) && !as_conversions_in.contains(ctx)) {
+        push_error(&mut errors, syn::Error::new(ctx.span(), format!("serde_try_from converts every context into the base; enable conversions or as_conversions for {}", ctx)));
+    }
+    let apply_to_in = enabled_in(&mut errors, "apply_to");
+    let mut constructors_in = enabled_in(&mut errors, "constructors");
+    let builder_in = enabled_in(&mut errors, "builder");
+    // Roles switch on their extras in addition to whatever was configured explicitly
+    for fluent_ctx in &fluent_contexts {
+        let implied = match fluent_ctx.role {
+            Some(ContextRole::Input) => &mut constructors_in,
+            Some(ContextRole::Output) if build_base => &mut from_ref_in,
+            _ => continue,
+        };
+        if !implied.contains(&fluent_ctx.name) {
+            implied.push(fluent_ctx.name.clone());
+        }
+    }
+    let merge_in = enabled_in(&mut errors, "merge");
+    let merge_named = named_in(&mut errors, "merge");
+    for mapping in &mut type_mappings {
+        mapping.contexts = if mapping.contexts.is_empty() {
+            variants.clone()
+        } else {
+            let mut contexts = Vec::new();
+            for key in &mapping.contexts {
+                contexts.extend(or_push_error(&mut errors, resolve_keyed_contexts(key, &context_sets, &variants)));
+            }
+            contexts
+        };
+    }
+    if let Some(err) = errors {
+        return Err(err);
+    }
+    
+    Ok(VariantList {
+        variants,
+        prefix,
+        suffix,
+        default_optional_attrs,
+        base_struct_attrs,
+        explain,
+        preserve_attr_text,
+        default_required_attrs,
+        scoped_optional_attrs,
+        scoped_required_attrs,
+        fluent_contexts,
+        global_default,
+        context_sets,
+        context_defaults,
+        required_wrappers,
+        flags: FlagContexts {
+            share_heavy_in,
+            assert_auto_traits_in,
+            assert_auto_traits_base: flags["assert_auto_traits"].everywhere,
+            presence_in,
+            columns_in,
+            list_in,
+            from_value_in,
+            field_iter_in,
+            serialize_required_in,
+            field_consts_in,
+            from_ref_in,
+            conversions_in,
+            conversions_named,
+            as_conversions_in,
+            as_conversions_named,
+            from_parts_in,
+            from_parts_named,
+            try_from_in,
+            apply_to_in,
+            constructors_in,
+            builder_in,
+            merge_in,
+            merge_named,
+            canonical_json_in,
+        },
+        list_cursor,
+        ctor_order,
+        cross_conversions,
+        max_visibility,
+        allow_public,
+        diff,
+        instrument,
+        dedup_variants,
+        report_stats,
+        allow_option_required,
+        warn_dead_attrs,
+        emit_markers,
+        for_each_macro,
+        copy_field_docs,
+        field_match,
+        inherit_defaults,
+        post_process,
+        registry,
+        error_messages,
+        any_variant,
+        context_variant,
+        enum_wrapper,
+        serde_try_from,
+        parse_any,
+        field_traits,
+        same_context,
+        same_context_families,
+        type_mappings,
+        secret_fields: Vec::new(),
+        all_fields_includes,
+        skipped_fields: Vec::new(),
+        option_fields: Vec::new(),
+        field_groups: IndexMap::new(), // Will be populated later after expansion
+        group_field_refs: field_groups, // Store the unexpanded group field references
+        build_base,
+        base_name,
+        optional_base,
+    })
+}
+
+/// Parse groups expression: auth(user_id, token), contact(name, email)
+/// Returns the group definitions in declaration order; they are expanded later
+fn parse_groups_expression(expr: &syn::Expr) -> Result<IndexMap<String, GroupDef>, syn::Error> {
+    let mut groups = IndexMap::new();
+    
+    match expr {
+        syn::Expr::Call(call) => {
+            // Single group: auth(user_id, token)
+            insert_group(&mut groups, parse_single_group(call)?)?;
+        }
+        syn::Expr::Paren(paren) => {
+            // Single parenthesized group: (auth(user_id, token))
+            return parse_groups_expression(&paren.expr);
+        }
+        syn::Expr::Tuple(tuple) => {
+            // Multiple groups: (auth(user_id, token), contact(name, email))
+            for elem in &tuple.elems {
+                if let syn::Expr::Call(call) = elem {
+                    insert_group(&mut groups, parse_single_group(call)?)?;
+                } else {
+                    return Err(syn::Error::new(elem.span(), "expected group definition like 'auth(user_id, token)'"));
+                }
+            }
+        }
+        _ => {
+            return Err(syn::Error::new(expr.span(), "expected group definition like 'auth(user_id, token)' or tuple of groups"));
+        }
+    }
+    
+    Ok(groups)
+}
+
+/// Add a group definition, rejecting a name that was already declared
+fn insert_group(groups: &mut IndexMap<String, GroupDef>, group: GroupDef) -> Result<(), syn::Error> {
+    let key = group.name.to_string();
+    if let Some(existing) = groups.get(&key) {
+        let mut err = syn::Error::new(group.name.span(), format!("duplicate field group '{}'", key));
+        err.combine(syn::Error::new(existing.name.span(), format!("field group '{}' first defined here", key)));
+        return Err(err);
+    }
+    groups.insert(key, group);
+    Ok(())
+}
+
+/// Expand group field references to concrete field lists
+/// This resolves all_fields() and all_fields().except() within group definitions
+pub(crate) fn expand_group_field_refs(
+    group_field_refs: &[GroupFieldRef], 
+    all_struct_fields: &[Ident]
+) -> Vec<Ident> {
+    let mut result = Vec::new();
+    
+    for field_ref in group_field_refs {
+        match field_ref {
+            GroupFieldRef::Field(field_name) => {
+                result.push(field_name.clone());
+            }
+            GroupFieldRef::AllFields { except } => {
+                // Add all struct fields except those in the except list
+                for struct_field in all_struct_fields {
+                    if !except.contains(struct_field) {
+                        result.push(struct_field.clone());
+                    }
+                }
+            }
+        }
+    }
+    
+    result
+}
+
+/// Parse a single group: auth(user_id, token)
+fn parse_single_group(call: &syn::ExprCall) -> Result<GroupDef, syn::Error> {
+    // Get group name
+    let group_name = match call.func.as_ref() {
+        syn::Expr::Path(path) => {
+            path.path.get_ident()
+                .ok_or_else(|| syn::Error::new(path.span(), "expected group name"))?
+                .clone()
+        }
+        _ => return Err(syn::Error::new(call.func.span(), "expected group name")),
+    };
+    
+    // Parse field list - now supports both field names and all_fields() references
+    let mut fields = Vec::new();
+    for arg in &call.args {
+        match arg {
+            syn::Expr::Path(path) => {
+                if let Some(ident) = path.path.get_ident() {
+                    fields.push(GroupFieldRef::Field(ident.clone()));
+                } else {
+                    return Err(syn::Error::new(arg.span(), "expected field name"));
+                }
+            }
+            syn::Expr::Call(call) => {
+                // Handle all_fields() call
+                if let syn::Expr::Path(path) = call.func.as_ref() {
+                    if let Some(ident) = path.path.get_ident() {
+                        if ident == "all_fields" {
+                            fields.push(GroupFieldRef::AllFields { except: Vec::new() });
+                        } else {
+                            return Err(syn::Error::new(arg.span(), "expected field name or all_fields()"));
+                        }
+                    } else {
+                        return Err(syn::Error::new(arg.span(), "expected field name or all_fields()"));
+                    }
+                } else {
+                    return Err(syn::Error::new(arg.span(), "expected field name or all_fields()"));
+                }
+            }
+            syn::Expr::MethodCall(method_call) => {
+                // Handle all_fields().except(...) method call
+                if let syn::Expr::Call(call) = method_call.receiver.as_ref() {
+                    if let syn::Expr::Path(path) = call.func.as_ref() {
+                        if let Some(ident) = path.path.get_ident() {
+                            if ident == "all_fields" && method_call.method == "except" {
+                                // Parse the except arguments
+                                let mut except_fields = Vec::new();
+                                for except_arg in &method_call.args {
+                                    if let syn::Expr::Path(except_path) = except_arg {
+                                        if let Some(except_ident) = except_path.path.get_ident() {
+                                            except_fields.push(except_ident.clone());
+                                        } else {
+                                            return Err(syn::Error::new(except_arg.span(), "expected field name in except clause"));
+                                        }
+                                    } else {
+                                        return Err(syn::Error::new(except_arg.span(), "expected field name in except clause"));
+                                    }
+                                }
+                                fields.push(GroupFieldRef::AllFields { except: except_fields });
+                            } else {
+                                return Err(syn::Error::new(arg.span(), "expected all_fields().except(...)"));
+                            }
+                        } else {
+                            return Err(syn::Error::new(arg.span(), "expected all_fields().except(...)"));
+                        }
+                    } else {
+                        return Err(syn::Error::new(arg.span(), "expected all_fields().except(...)"));
+                    }
+                } else {
+                    return Err(syn::Error::new(arg.span(), "expected field name, all_fields(), or all_fields().except(...)"));
+                }
+            }
+            _ => return Err(syn::Error::new(arg.span(), "expected field name, all_fields(), or all_fields().except(...)")),
+        }
+    }
+    
+    Ok(GroupDef { name: group_name, fields })
+}
+
+#[derive(Debug)]
+pub(crate) enum MixedArg {
+    Path { name: Ident },
+    NameValue { name: Ident, value: syn::Expr },
+    FluentContext { name: Ident, calls: Vec<FluentCall> },
+    /// `name = { key: value, ... }` where each key is a context set or a context
+    Keyed { name: Ident, entries: Vec<(Ident, syn::Expr)> },
+    /// `name(Context, ...) = value`, a parameter applied only in the listed contexts or context sets
+    Scoped { name: Ident, contexts: Vec<Ident>, value: syn::Expr },
+    /// `type_map = { From => To in [Context, ...], ... }`
+    TypeMap { mappings: Vec<TypeMapping> },
+    /// `config(naming(...), base(...), ...)`, already translated to flat parameters
+    Config { args: Vec<MixedArg> },
+}
+
+impl syn::parse::Parse for MixedArg {
+    /// One macro argument: "Create: requires(name)", "suffix = "Form"", "defaults = { ... }" or "Create"
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name: Ident = input.parse()?;
+        if name == "config" && input.peek(syn::token::Paren) {
+            // Structured configuration: "config(naming(prefix = "Api"), codegen(presence))"
+            Ok(MixedArg::Config { args: parse_config_block(input)? })
+        } else if input.peek(syn::Token![:]) {
+            // This is fluent syntax: "Create: requires(name)"
+            let _: syn::Token![:] = input.parse()?;
+            // Raw names allow contexts like `r#default` or `r#type`
+            Ok(MixedArg::FluentContext { name: name.unraw(), calls: parse_fluent_chain(input)? })
+        } else if input.peek(syn::token::Paren) && input.peek2(syn::Token![=]) {
+            // Scoped parameter: "optional_attrs(Update) = [serde(default)]"
+            let content;
+            syn::parenthesized!(content in input);
+            let contexts = syn::punctuated::Punctuated::<Ident, syn::Token![,]>::parse_terminated(&content)?;
+            let _: syn::Token![=] = input.parse()?;
+            Ok(MixedArg::Scoped {
+                name,
+                contexts: contexts.into_iter().map(|ctx| ctx.unraw()).collect(),
+                value: parse_value_or_bracketed_tokens(input)?,
+            })
+        } else if input.peek(syn::Token![=]) {
+            // This is traditional syntax: "suffix = "Form""
+            let _: syn::Token![=] = input.parse()?;
+            if name == "type_map" && input.peek(syn::token::Brace) {
+                // Type rules: "type_map = { DateTime<Utc> => String in [Create, Update] }"
+                Ok(MixedArg::TypeMap { mappings: parse_type_map_block(input)? })
+            } else if input.peek(syn::token::Brace) {
+                // Per-set values: "defaults = { write: exclude, read: optional }"
+                Ok(MixedArg::Keyed { name, entries: parse_keyed_block(input)? })
+            } else {
+                Ok(MixedArg::NameValue { name, value: parse_value_or_bracketed_tokens(input)? })
+            }
+        } else {
+            // This is just a variant name: "Create"
+            Ok(MixedArg::Path { name: name.unraw() })
+        }
+    }
+}
+
+/// Parse a parameter value. A visibility, or a bracketed list that is not an expression such as
+/// `optional_attrs = [validate(custom DSL)]`, is kept as verbatim tokens.
+fn parse_value_or_bracketed_tokens(input: ParseStream) -> syn::Result<syn::Expr> {
+    if input.peek(syn::Token![pub]) {
+        // A visibility, for max_visibility = pub(crate)
+        let vis: Visibility = input.parse()?;
+        return Ok(syn::Expr::Verbatim(quote! { #vis }));
+    }
+    if input.peek(syn::token::Bracket) {
+        let fork = input.fork();
+        let parsed = fork.parse::<syn::Expr>();
+        if parsed.is_err() || !(fork.is_empty() || fork.peek(syn::Token![,])) {
+            let group: proc_macro2::Group = input.parse()?;
+            return Ok(syn::Expr::Verbatim(quote! { #group }));
+        }
+    }
+    input.parse()
+}
+
+/// Parse the braced body of `defaults = { write: exclude, read: optional }`
+fn parse_keyed_block(input: ParseStream) -> syn::Result<Vec<(Ident, syn::Expr)>> {
+    let content;
+    syn::braced!(content in input);
+    let mut entries = Vec::new();
+    while !content.is_empty() {
+        let key = content.parse::<Ident>()?.unraw();
+        let _: syn::Token![:] = content.parse()?;
+        let value: syn::Expr = content.parse()?;
+        entries.push((key, value));
+        if !content.is_empty() {
+            let _: syn::Token![,] = content.parse()?;
+        }
+    }
+    Ok(entries)
+}
+
+/// Parse `config(section(key = value, flag, ...), ...)` into the equivalent flat parameters.
+/// A bare key is a flag and means `key = true`.
+fn parse_config_block(input: ParseStream) -> syn::Result<Vec<MixedArg>> {
+    let content;
+    syn::parenthesized!(content in input);
+    let section_names = config_sections();
+    let mut args = Vec::new();
+    while !content.is_empty() {
+        let section: Ident = content.parse()?;
+        let Some(section_name) = section_names.iter().find(|name| section == **name) else {
+            return Err(syn::Error::new(section.span(), match suggest_name(&section.to_string(), &section_names) {
+                Some(suggestion) => format!("unknown config section; did you mean '{}'?", suggestion),
+                None => format!("unknown config section; expected one of {}", section_names.join(", ")),
+            }));
+        };
+        let keys = config_keys(section_name);
+        let entries;
+        syn::parenthesized!(entries in content);
+        for entry in syn::punctuated::Punctuated::<MixedArg, syn::Token![,]>::parse_terminated(&entries)? {
+            let (key, arg) = match entry {
+                MixedArg::Path { name } => {
+                    let value = syn::parse_quote_spanned!(name.span()=> true);
+                    (name.clone(), MixedArg::NameValue { name, value })
+                }
+                MixedArg::NameValue { name, value } => (name.clone(), MixedArg::NameValue { name, value }),
+                MixedArg::Keyed { name, entries } => (name.clone(), MixedArg::Keyed { name, entries }),
+                MixedArg::TypeMap { mappings } if keys.iter().any(|(key, _)| *key == "type_map") => {
+                    args.push(MixedArg::TypeMap { mappings });
+                    continue;
+                }
+                _ => return Err(syn::Error::new(section.span(), format!("expected `key = value` or a flag in config section '{}'", section))),
+            };
+            let key_names: Vec<&str> = keys.iter().map(|(key, _)| *key).collect();
+            let Some((_, flat)) = keys.iter().find(|(name, _)| key == name) else {
+                return Err(syn::Error::new(key.span(), match suggest_name(&key.to_string(), &key_names) {
+                    Some(suggestion) => format!("unknown key in config section '{}'; did you mean '{}'?", section, suggestion),
+                    None => format!("unknown key in config section '{}'; expected one of {}", section, key_names.join(", ")),
+                }));
+            };
+            let flat_name = Ident::new(flat, key.span());
+            args.push(match arg {
+                MixedArg::NameValue { value, .. } => MixedArg::NameValue { name: flat_name, value },
+                MixedArg::Keyed { entries, .. } => MixedArg::Keyed { name: flat_name, entries },
+                other => other,
+            });
+        }
+        if !content.is_empty() {
+            let _: syn::Token![,] = content.parse()?;
+        }
+    }
+    Ok(args)
+}
+
+/// How a name of [`PARAMETERS`] is written
+#[derive(Clone, Copy, PartialEq)]
+enum ParameterKind {
+    /// A method of a fluent chain, `Context.requires(...)`
+    Fluent,
+    /// A flat parameter, `name = value` or `name = { ... }`
+    Value,
+    /// A parameter that is on for every context with `name = true`, or per set or context with
+    /// `name = { key: true, ... }`
+    ContextFlag,
+}
+
+/// A name the macro accepts, with the `config(section(key))` spelling of its parameter and the
+/// common misspellings too far from it for edit distance to catch
+struct Parameter {
+    name: &'static str,
+    kind: ParameterKind,
+    config: Option<(&'static str, &'static str)>,
+    synonyms: &'static [&'static str],
+}
+
+impl Parameter {
+    const fn fluent(name: &'static str, synonyms: &'static [&'static str]) -> Self {
+        Parameter { name, kind: ParameterKind::Fluent, config: None, synonyms }
+    }
+
+    const fn value(section: &'static str, name: &'static str) -> Self {
+        Self::value_as(section, name, name)
+    }
+
+    const fn value_as(section: &'static str, key: &'static str, name: &'static str) -> Self {
+        Parameter { name, kind: ParameterKind::Value, config: Some((section, key)), synonyms: &[] }
+    }
+
+    const fn flag(section: &'static str, name: &'static str) -> Self {
+        Parameter { name, kind: ParameterKind::ContextFlag, config: Some((section, name)), synonyms: &[] }
+    }
+}
+
+/// Every fluent method and parameter; the lists below are derived from it
+const PARAMETERS: &[Parameter] = &[
+    Parameter::fluent("requires", &["required", "required_fields"]),
+    Parameter::fluent("optional", &["optional_fields", "optionals"]),
+    Parameter::fluent("excludes", &["excluded", "excluded_fields"]),
+    Parameter::fluent("forbid", &["forbidden"]),
+    Parameter::fluent("at_least_one_of", &[]),
+    Parameter::fluent("one_of", &[]),
+    Parameter::fluent("tag", &[]),
+    Parameter::fluent("key", &[]),
+    Parameter::fluent("name", &[]),
+    Parameter::fluent("envelope", &[]),
+    Parameter::fluent("verbatim_field", &[]),
+    Parameter::fluent("computed", &[]),
+    Parameter::fluent("doc", &[]),
+    Parameter::fluent("derive", &[]),
+    Parameter::fluent("role", &[]),
+    Parameter::fluent("default", &[]),
+    Parameter::fluent("default_for", &[]),
+    Parameter::fluent("respect_base_optionality", &[]),
+    Parameter::value("naming", "prefix"),
+    Parameter::value("naming", "suffix"),
+    Parameter::value_as("base", "build", "build_base"),
+    Parameter::value_as("base", "optional", "optional_base"),
+    Parameter::value_as("base", "name", "base_name"),
+    Parameter::value_as("base", "attrs", "base_attrs"),
+    Parameter::value("base", "explain"),
+    Parameter::value("fields", "default"),
+    Parameter::value("fields", "defaults"),
+    Parameter::value("fields", "groups"),
+    Parameter::value("fields", "context_sets"),
+    Parameter::value("fields", "optional_attrs"),
+    Parameter::value("fields", "required_attrs"),
+    Parameter::value("fields", "required_wrapper"),
+    Parameter::value("fields", "field_match"),
+    Parameter::value("fields", "all_fields_includes"),
+    Parameter::value("fields", "copy_field_docs"),
+    Parameter::value("fields", "preserve_attr_text"),
+    Parameter::flag("fields", "share_heavy_fields"),
+    Parameter::value("fields", "allow_option_required"),
+    Parameter::value("fields", "warn_dead_attrs"),
+    Parameter::value("fields", "type_map"),
+    Parameter::value("fields", "uuid_as_string"),
+    Parameter::flag("codegen", "constructors"),
+    Parameter::flag("codegen", "builder"),
+    Parameter::value("codegen", "ctor_order"),
+    Parameter::flag("codegen", "presence"),
+    Parameter::flag("codegen", "columns"),
+    Parameter::flag("codegen", "list"),
+    Parameter::value("codegen", "list_cursor"),
+    Parameter::flag("codegen", "canonical_json"),
+    Parameter::flag("codegen", "from_value"),
+    Parameter::flag("codegen", "field_iter"),
+    Parameter::flag("codegen", "serialize_required"),
+    Parameter::flag("codegen", "field_consts"),
+    Parameter::flag("codegen", "from_ref"),
+    Parameter::flag("codegen", "conversions"),
+    Parameter::flag("codegen", "from_parts"),
+    Parameter::flag("codegen", "as_conversions"),
+    Parameter::value("codegen", "cross_conversions"),
+    Parameter::flag("codegen", "try_from"),
+    Parameter::flag("codegen", "apply_to"),
+    Parameter::flag("codegen", "merge"),
+    Parameter::value("codegen", "diff"),
+    Parameter::value("codegen", "instrument"),
+    Parameter::value("codegen", "max_visibility"),
+    Parameter::value("codegen", "allow_public"),
+    Parameter::value("codegen", "dedup_variants"),
+    Parameter::value("codegen", "emit_markers"),
+    Parameter::value("codegen", "for_each_macro"),
+    Parameter::value("codegen", "report_stats"),
+    Parameter::value("codegen", "inherit_defaults"),
+    Parameter::flag("codegen", "assert_auto_traits"),
+    Parameter::value("codegen", "any_variant"),
+    Parameter::value("codegen", "context_variant"),
+    Parameter::value("codegen", "enum_wrapper"),
+    Parameter::value("codegen", "serde_try_from"),
+    Parameter::value("codegen", "parse_any"),
+    Parameter::value("codegen", "field_traits"),
+    Parameter::value("codegen", "same_context"),
+    Parameter::value("codegen", "same_context_families"),
+    Parameter::value("codegen", "post_process"),
+    Parameter::value("codegen", "registry"),
+    Parameter::value("codegen", "error_messages"),
+];
+
+/// Methods accepted in a fluent chain
+fn fluent_methods() -> Vec<&'static str> {
+    PARAMETERS.iter().filter(|param| param.kind == ParameterKind::Fluent).map(|param| param.name).collect()
+}
+
+/// Parameters accepted as `name = value` or `name = { ... }`
+fn parameter_names() -> Vec<&'static str> {
+    PARAMETERS.iter().filter(|param| param.kind != ParameterKind::Fluent).map(|param| param.name).collect()
+}
+
+/// Parameters that are also per-context flags
+fn context_flag_names() -> impl Iterator<Item = &'static str> {
+    PARAMETERS.iter().filter(|param| param.kind == ParameterKind::ContextFlag).map(|param| param.name)
+}
+
+/// Sections of `config(...)`, in the order they are documented
+fn config_sections() -> Vec<&'static str> {
+    let mut sections: Vec<&str> = Vec::new();
+    for (section, _) in PARAMETERS.iter().filter_map(|param| param.config) {
+        if !sections.contains(&section) {
+            sections.push(section);
+        }
+    }
+    sections
+}
+
+/// Keys of a `config(...)` section and the flat parameter each stands for
+fn config_keys(section: &str) -> Vec<(&'static str, &'static str)> {
+    PARAMETERS.iter()
+        .filter_map(|param| param.config.filter(|(name, _)| *name == section).map(|(_, key)| (key, param.name)))
+        .collect()
+}
+
+/// Suggest the closest of `candidates` for a misspelled `name`, if any is close enough
+pub(crate) fn suggest_name<'a>(name: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    if let Some(target) = PARAMETERS.iter().find(|param| param.synonyms.contains(&name))
+        && let Some(candidate) = candidates.iter().find(|candidate| **candidate == target.name) {
+        return Some(candidate);
+    }
+    candidates.iter()
+        .map(|candidate| (edit_distance(name, candidate), *candidate))
+        .filter(|(distance, candidate)| *distance <= candidate.len().div_ceil(3))
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Levenshtein distance between two names
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let current = row[j + 1];
+            row[j + 1] = if ca == *cb { previous } else { 1 + previous.min(row[j]).min(current) };
+            previous = current;
+        }
+    }
+    row[b.len()]
+}
+
+/// Parameters whose generated code needs an optional integration, and the Cargo feature enabling it
+const FEATURE_GATED: &[(&str, &str)] = &[
+    ("canonical_json", "json"),
+    ("from_value", "json"),
+    ("field_iter", "json"),
+    ("any_variant", "json"),
+    ("parse_any", "json"),
+];
+
+/// Report a parameter whose integration was compiled out of this crate.
+fn check_feature_enabled(name: &Ident) -> Result<(), syn::Error> {
+    let enabled = |feature: &str| feature != "json" || cfg!(feature = "json");
+    match FEATURE_GATED.iter().find(|(param, _)| name == param) {
+        Some((param, feature)) if !enabled(feature) => Err(syn::Error::new(
+            name.span(),
+            format!("{} requires the `{}` feature of context_variants; enable it in Cargo.toml", param, feature),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Add `err` to the errors collected so far
+pub(crate) fn push_error(errors: &mut Option<syn::Error>, err: syn::Error) {
+    match errors {
+        Some(existing) => existing.combine(err),
+        None => *errors = Some(err),
+    }
+}
+
+/// The value of `result`, or the default with the error added to `errors`
+fn or_push_error<T: Default>(errors: &mut Option<syn::Error>, result: Result<T, syn::Error>) -> T {
+    result.unwrap_or_else(|err| {
+        push_error(errors, err);
+        T::default()
+    })
+}
+
+/// Parse a string literal value: prefix = "Api"
+pub(crate) fn parse_string_value(value: &syn::Expr) -> Result<String, syn::Error> {
+    match value {
+        syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(s), .. }) => Ok(s.value()),
+        _ => Err(syn::Error::new(value.span(), "expected string literal")),
+    }
+}
+
+/// Parse a default behavior value given as an identifier or string: default = optional
+fn parse_default_value(value: &syn::Expr) -> Result<DefaultBehavior, syn::Error> {
+    let default_str = match value {
+        syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(s), .. }) => s.value(),
+        syn::Expr::Path(path) => match path.path.get_ident() {
+            Some(ident) => ident.to_string(),
+            None => return Err(syn::Error::new(value.span(), "expected identifier or string literal")),
+        },
+        _ => return Err(syn::Error::new(value.span(), "expected string literal or identifier")),
+    };
+    DefaultBehavior::from_name(&default_str, value.span())
+}
+
+/// Parse a bracketed list of contexts or context sets: [Create, write]
+fn parse_context_list(expr: &syn::Expr) -> Result<Vec<Ident>, syn::Error> {
+    let syn::Expr::Array(array) = expr else {
+        return Err(syn::Error::new(expr.span(), "expected a list of contexts like [Create, Update]"));
+    };
+    let mut contexts = Vec::new();
+    for elem in &array.elems {
+        let ident = match elem {
+            syn::Expr::Path(path) => path.path.get_ident(),
+            _ => None,
+        };
+        match ident {
+            Some(ident) => contexts.push(ident.unraw()),
+            None => return Err(syn::Error::new(elem.span(), "expected context or context set name")),
+        }
+    }
+    if contexts.is_empty() {
+        return Err(syn::Error::new(expr.span(), "expected at least one context"));
+    }
+    Ok(contexts)
+}
+
+/// Parse a bracketed list of attribute names: [builder, serde]
+fn parse_attr_names(expr: &syn::Expr) -> Result<Vec<Ident>, syn::Error> {
+    let syn::Expr::Array(array) = expr else {
+        return Err(syn::Error::new(expr.span(), "expected a list of attribute names like [builder]"));
+    };
+    array.elems.iter()
+        .map(|elem| match elem {
+            syn::Expr::Path(path) if path.path.get_ident().is_some() => Ok(path.path.get_ident().unwrap().clone()),
+            _ => Err(syn::Error::new(elem.span(), "expected an attribute name")),
+        })
+        .collect()
+}
+
+/// Parse a bracketed list of variant name templates: ["Admin{}Form", "{}TenantForm"]
+fn parse_name_templates(expr: &syn::Expr) -> Result<Vec<syn::LitStr>, syn::Error> {
+    let syn::Expr::Array(array) = expr else {
+        return Err(syn::Error::new(expr.span(), "expected a list of name templates like [\"Admin{}Form\"]"));
+    };
+    let mut templates = Vec::new();
+    for elem in &array.elems {
+        match elem {
+            syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(template), .. }) if template.value().matches("{}").count() == 1 => {
+                templates.push(template.clone());
+            }
+            _ => return Err(syn::Error::new(elem.span(), "expected a string with one '{}' for the context name, like \"Admin{}Form\"")),
+        }
+    }
+    Ok(templates)
+}
+
+/// Parse the braced body of `type_map = { DateTime<Utc> => String in [Create, Update] }`
+fn parse_type_map_block(input: ParseStream) -> syn::Result<Vec<TypeMapping>> {
+    let content;
+    syn::braced!(content in input);
+    let mut mappings = Vec::new();
+    while !content.is_empty() {
+        let from: Type = content.parse()?;
+        let _: syn::Token![=>] = content.parse()?;
+        let to: Type = content.parse()?;
+        let mut contexts = Vec::new();
+        if content.peek(syn::Token![in]) {
+            let _: syn::Token![in] = content.parse()?;
+            let list;
+            syn::bracketed!(list in content);
+            contexts.extend(syn::punctuated::Punctuated::<Ident, syn::Token![,]>::parse_terminated(&list)?.iter().map(Ident::unraw));
+        }
+        mappings.push(TypeMapping { from, to, contexts });
+        if !content.is_empty() {
+            let _: syn::Token![,] = content.parse()?;
+        }
+    }
+    Ok(mappings)
+}
+
+/// Parse context sets: (write(Create, Update), read(Read)) or a single write(Create, Update)
+fn parse_context_sets(expr: &syn::Expr) -> Result<IndexMap<String, ContextSet>, syn::Error> {
+    let calls: Vec<&syn::Expr> = match expr {
+        syn::Expr::Tuple(tuple) => tuple.elems.iter().collect(),
+        syn::Expr::Paren(paren) => vec![paren.expr.as_ref()],
+        other => vec![other],
+    };
+
+    let mut sets: IndexMap<String, ContextSet> = IndexMap::new();
+    for elem in calls {
+        let call = match elem {
+            syn::Expr::Call(call) => call,
+            _ => return Err(syn::Error::new(elem.span(), "expected context set like 'write(Create, Update)'")),
+        };
+        let name = match call.func.as_ref() {
+            syn::Expr::Path(path) => path.path.get_ident()
+                .ok_or_else(|| syn::Error::new(path.span(), "expected context set name"))?
+                .unraw(),
+            _ => return Err(syn::Error::new(call.func.span(), "expected context set name")),
+        };
+        let mut contexts = Vec::new();
+        for arg in &call.args {
+            match arg {
+                syn::Expr::Path(path) => {
+                    if let Some(ident) = path.path.get_ident() {
+                        contexts.push(ident.unraw());
+                    } else {
+                        return Err(syn::Error::new(arg.span(), "expected context name"));
+                    }
+                }
+                _ => return Err(syn::Error::new(arg.span(), "expected context name")),
+            }
+        }
+        let key = name.to_string();
+        if let Some(existing) = sets.get(&key) {
+            let mut err = syn::Error::new(name.span(), format!("duplicate context set '{}'", key));
+            err.combine(syn::Error::new(existing.name.span(), format!("context set '{}' first defined here", key)));
+            return Err(err);
+        }
+        sets.insert(key, ContextSet { name, contexts });
+    }
+    Ok(sets)
+}
+
+/// Resolve the key of a `{ key: value }` entry to the contexts it names
+fn resolve_keyed_contexts(
+    key: &Ident,
+    context_sets: &IndexMap<String, ContextSet>,
+    variants: &[Ident],
+) -> Result<Vec<Ident>, syn::Error> {
+    if let Some(set) = context_sets.get(&key.to_string()) {
+        Ok(set.contexts.clone())
+    } else if variants.iter().any(|v| v == key) {
+        Ok(vec![key.clone()])
+    } else {
+        Err(syn::Error::new(key.span(), format!("'{}' is neither a context set nor a context", key)))
+    }
+}
+
+/// Resolve `optional_attrs(Context, ...) = [...]` entries into attributes per context
+fn resolve_scoped_attrs(
+    entries: Vec<(Vec<Ident>, Vec<Attribute>)>,
+    context_sets: &IndexMap<String, ContextSet>,
+    variants: &[Ident],
+) -> Result<Vec<(Ident, Vec<Attribute>)>, syn::Error> {
+    let mut resolved = Vec::new();
+    let mut errors = None;
+    for (keys, attrs) in entries {
+        for key in keys {
+            match resolve_keyed_contexts(&key, context_sets, variants) {
+                Ok(contexts) => resolved.extend(contexts.into_iter().map(|ctx| (ctx, attrs.clone()))),
+                Err(err) => push_error(&mut errors, err),
+            }
+        }
+    }
+    errors.map_or(Ok(resolved), Err)
+}
+
+/// Resolve `defaults = { ... }` entries into a default behavior per context name
+fn resolve_context_defaults(
+    entries: &[(Ident, syn::Expr)],
+    context_sets: &IndexMap<String, ContextSet>,
+    variants: &[Ident],
+) -> Result<IndexMap<String, DefaultBehavior>, syn::Error> {
+    let mut resolved: IndexMap<String, (DefaultBehavior, Ident)> = IndexMap::new();
+    let mut errors = None;
+    for (key, value) in entries {
+        let entry = match value {
+            syn::Expr::Path(path) => path.path.get_ident().map(|ident| ident.to_string()),
+            _ => None,
+        }
+        .ok_or_else(|| syn::Error::new(value.span(), "expected 'required', 'optional', or 'exclude'"))
+        .and_then(|behavior_name| DefaultBehavior::from_name(&behavior_name, value.span()))
+        .and_then(|behavior| Ok((behavior, resolve_keyed_contexts(key, context_sets, variants)?)));
+        let (behavior, contexts) = match entry {
+            Ok(entry) => entry,
+            Err(err) => {
+                push_error(&mut errors, err);
+                continue;
+            }
+        };
+        for ctx in contexts {
+            if let Some((existing, source)) = resolved.get(&ctx.to_string()) {
+                if *existing != behavior {
+                    push_error(&mut errors, syn::Error::new(
+                        key.span(),
+                        format!("context '{}' already gets a different default from '{}'", ctx, source),
+                    ));
+                }
+                continue;
+            }
+            resolved.insert(ctx.to_string(), (behavior.clone(), key.clone()));
+        }
+    }
+    errors.map_or(Ok(resolved.into_iter().map(|(ctx, (behavior, _))| (ctx, behavior)).collect()), Err)
+}
+
+/// Resolve `required_wrapper` into a wrapper type path per context name. A plain
+/// value applies to every context; `{ key: Wrapper }` entries override it per set or context.
+fn resolve_required_wrappers(
+    global: Option<syn::Path>,
+    entries: &[(Ident, syn::Expr)],
+    context_sets: &IndexMap<String, ContextSet>,
+    variants: &[Ident],
+) -> Result<IndexMap<String, syn::Path>, syn::Error> {
+    let mut resolved = IndexMap::new();
+    if let Some(wrapper) = global {
+        for variant in variants {
+            resolved.insert(variant.to_string(), wrapper.clone());
+        }
+    }
+    let mut errors = None;
+    for (key, value) in entries {
+        match parse_path_value(value).and_then(|wrapper| Ok((wrapper, resolve_keyed_contexts(key, context_sets, variants)?))) {
+            Ok((wrapper, contexts)) => {
+                for ctx in contexts {
+                    resolved.insert(ctx.to_string(), wrapper.clone());
+                }
+            }
+            Err(err) => push_error(&mut errors, err),
+        }
+    }
+    errors.map_or(Ok(resolved), Err)
+}
+
+/// Parse a boolean given as a literal or bare identifier: `true` / `false`
+fn parse_bool_value(value: &syn::Expr) -> Result<bool, syn::Error> {
+    match value {
+        syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Bool(b), .. }) => Ok(b.value),
+        syn::Expr::Path(path) => {
+            // Handle: flag = true/false (without quotes)
+            if let Some(ident) = path.path.get_ident() {
+                match ident.to_string().as_str() {
+                    "true" => Ok(true),
+                    "false" => Ok(false),
+                    _ => Err(syn::Error::new(value.span(), "expected 'true' or 'false'")),
+                }
+            } else {
+                Err(syn::Error::new(value.span(), "expected boolean literal or identifier"))
+            }
+        }
+        _ => Err(syn::Error::new(value.span(), "expected boolean literal")),
+    }
+}
+
+/// A context flag of [`PARAMETERS`] as written: `name = true` and any `name = { key: true }` entries
+#[derive(Debug, Clone, Default)]
+struct ContextFlag {
+    everywhere: bool,
+    entries: Vec<(Ident, syn::Expr)>,
+}
+
+impl ContextFlag {
+    /// Whether the flag was given at all, on or off
+    fn is_set(&self) -> bool {
+        self.everywhere || !self.entries.is_empty()
+    }
+
+    /// The flag with only its `{ key: ... }` entries, for contexts that were named explicitly
+    fn named(&self) -> ContextFlag {
+        ContextFlag { everywhere: false, entries: self.entries.clone() }
+    }
+}
+
+/// Resolve a flag that is either on for every context or enabled per set/context
+/// via `{ key: true }` entries, into the contexts it is enabled for
+fn resolve_context_flag(
+    flag: &ContextFlag,
+    context_sets: &IndexMap<String, ContextSet>,
+    variants: &[Ident],
+) -> Result<Vec<Ident>, syn::Error> {
+    let mut enabled: Vec<Ident> = if flag.everywhere { variants.to_vec() } else { Vec::new() };
+    let mut errors = None;
+    for (key, value) in &flag.entries {
+        match parse_bool_value(value).and_then(|on| Ok((on, resolve_keyed_contexts(key, context_sets, variants)?))) {
+            Ok((on, contexts)) => {
+                for ctx in contexts {
+                    enabled.retain(|v| v != &ctx);
+                    if on {
+                        enabled.push(ctx);
+                    }
+                }
+            }
+            Err(err) => push_error(&mut errors, err),
+        }
+    }
+    errors.map_or(Ok(enabled), Err)
+}
+
+/// Parse a type path given either bare (`Validated`) or as a string literal (`"Validated"`)
+fn parse_path_value(value: &syn::Expr) -> Result<syn::Path, syn::Error> {
+    match value {
+        syn::Expr::Path(path) => Ok(path.path.clone()),
+        syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(s), .. }) => s.parse(),
+        _ => Err(syn::Error::new(value.span(), "expected a type path like 'Validated' or \"Validated\"")),
+    }
+}
+
+
+/// Parse an array of attributes: [serde(skip_serializing_if = "Option::is_none"), serde(default)]
+/// Each element is taken token for token, so attributes need not be valid Rust expressions.
+fn parse_attribute_array(expr: &syn::Expr) -> Result<Vec<Attribute>, syn::Error> {
+    let is_array = match expr {
+        syn::Expr::Array(_) => true,
+        syn::Expr::Verbatim(tokens) => syn::parse2::<proc_macro2::Group>(tokens.clone())
+            .is_ok_and(|group| group.delimiter() == proc_macro2::Delimiter::Bracket),
+        _ => false,
+    };
+    if !is_array {
+        return Err(syn::Error::new(expr.span(), "expected array of attributes"));
+    }
+    let parser = |input: ParseStream| -> syn::Result<Vec<Attribute>> {
+        let content;
+        syn::bracketed!(content in input);
+        let metas = syn::punctuated::Punctuated::<Meta, syn::Token![,]>::parse_terminated_with(&content, parse_raw_meta)?;
+        Ok(metas.into_iter()
+            .map(|meta| Attribute {
+                pound_token: Default::default(),
+                style: syn::AttrStyle::Outer,
+                bracket_token: Default::default(),
+                meta,
+            })
+            .collect())
+    };
+    syn::parse::Parser::parse2(parser, quote! { #expr })
+}
@@ -0,0 +1,717 @@
+//! Resolving the parsed contexts against the fields of the annotated struct
+
+use super::*;
+
+/// Process a single field, extracting our macro-specific attributes and
+/// returning a `FieldSpec` with cleaned attributes.
+pub(crate) fn process_field(field: &Field, cfg: &VariantList, selectable_fields: &[Ident]) -> Result<FieldSpec, syn::Error> {
+    // Ensure field is named.
+    let ident = match &field.ident {
+        Some(id) => id.clone(),
+        None => return Err(syn::Error::new(field.span(), "context_variants does not support tuple structs")),
+    };
+    let mut required_in: Vec<Ident> = Vec::new();
+    let mut optional_in: Vec<Ident> = Vec::new();
+    let mut never_in: Vec<Ident> = Vec::new();
+    let mut optional_attrs = Vec::new();
+    let mut required_attrs = Vec::new();
+    let mut base_attrs: Vec<Attribute> = Vec::new();  // Attributes that only appear on base struct
+    let mut other_attrs = Vec::new();
+    let mut variant_types: std::collections::HashMap<String, Type> = std::collections::HashMap::new();
+    let mut shared_in: Vec<Ident> = Vec::new();
+    let mut encrypt_with = None;
+    let mut encrypted_in: Vec<Ident> = Vec::new();
+    let mut exclusions: std::collections::HashMap<String, Exclusion> = std::collections::HashMap::new();
+    let mut required_by_default: Vec<Ident> = Vec::new();
+    // Each when_* attribute and where it was written, to report those that never apply
+    let mut when_spans: Vec<(&str, Span)> = Vec::new();
+    let is_secret = cfg.secret_fields.contains(&ident);
+    let skip_marker = cfg.skipped_fields.iter().find(|(name, _)| *name == ident).map(|(_, marker)| *marker);
+    // Per-field overrides of optional_base
+    let mut base_required_span: Option<Span> = None;
+    let mut base_optional_span: Option<Span> = None;
+    let mut order = None;
+    let mut skip_variant_docs = false;
+    let mut flatten_into = None;
+    
+    // Process field attributes (fluent API only)
+    for attr in &field.attrs {
+        if is_macro_attr(attr, "when_optional") {
+            // Parse the inner attribute and add it to optional_attrs
+            let inner_attr = parse_ctx_attr_attribute(attr)?;
+            optional_attrs.push(inner_attr);
+            when_spans.push(("when_optional", attr.path().span()));
+        } else if is_macro_attr(attr, "when_required") {
+            // Parse the inner attribute and add it to required_attrs  
+            let inner_attr = parse_ctx_attr_attribute(attr)?;
+            required_attrs.push(inner_attr);
+            when_spans.push(("when_required", attr.path().span()));
+        } else if is_macro_attr(attr, "when_base") {
+            // Parse the inner attribute and add it to base_attrs (only for base struct)
+            let inner_attr = parse_ctx_attr_attribute(attr)?;
+            base_attrs.push(inner_attr);
+            when_spans.push(("when_base", attr.path().span()));
+        } else if is_macro_attr(attr, "ctx_secret") {
+            // Already recorded in cfg.secret_fields
+            continue;
+        } else if is_macro_attr(attr, "ctx_passthrough") {
+            // Already recorded in cfg.skipped_fields
+            attr.meta.require_path_only()?;
+            continue;
+        } else if is_macro_attr(attr, "ctx_order") {
+            // #[ctx_order(2)] positions the field in every generated variant
+            let position: syn::LitInt = attr.parse_args()
+                .map_err(|_| syn::Error::new(attr.path().span(), "expected #[ctx_order(n)] with a non-negative integer"))?;
+            position.base10_parse::<u32>()
+                .map_err(|_| syn::Error::new(position.span(), "expected #[ctx_order(n)] with a non-negative integer"))?;
+            order = Some(position);
+        } else if is_macro_attr(attr, "ctx_skip_variant_field_docs") {
+            // #[ctx_skip_variant_field_docs] documents the field on the base struct only
+            attr.meta.require_path_only()?;
+            skip_variant_docs = true;
+        } else if is_macro_attr(attr, "ctx_base_required") {
+            // #[ctx_base_required] keeps the field as declared in the base under optional_base = true
+            if !cfg.optional_base {
+                return Err(syn::Error::new(attr.path().span(), "#[ctx_base_required] has no effect without optional_base = true"));
+            }
+            attr.meta.require_path_only()?;
+            base_required_span = Some(attr.path().span());
+        } else if is_macro_attr(attr, "ctx_base_optional") {
+            // #[ctx_base_optional] wraps the field in Option in the base without optional_base
+            if cfg.optional_base {
+                return Err(syn::Error::new(attr.path().span(), "#[ctx_base_optional] has no effect with optional_base = true"));
+            }
+            if is_option_type(&field.ty) {
+                return Err(syn::Error::new(attr.path().span(), "#[ctx_base_optional] has no effect on a field that is already Option"));
+            }
+            attr.meta.require_path_only()?;
+            base_optional_span = Some(attr.path().span());
+        } else if is_macro_attr(attr, "ctx_encrypt") {
+            // #[ctx_encrypt(Read, storage, with = "crate::crypto")] stores ciphertext in the listed variants, or in all of them
+            let (module, contexts) = parse_ctx_encrypt_attribute(attr, cfg)?;
+            encrypt_with = Some(module);
+            encrypted_in = contexts;
+        } else if is_macro_attr(attr, "ctx_flatten_into") {
+            // #[ctx_flatten_into(Create, fields(host: String, port: u16))] hoists the listed fields into Create
+            flatten_into = Some((attr.path().span(), parse_ctx_flatten_into_attribute(attr, &field.ty, cfg)?));
+        } else if is_macro_attr(attr, "ctx_shared") {
+            // #[ctx_shared] shares the field in every variant, #[ctx_shared(Read, read_set)] in the listed ones
+            for ctx in parse_ctx_shared_attribute(attr, cfg)? {
+                if !shared_in.contains(&ctx) {
+                    shared_in.push(ctx);
+                }
+            }
+        } else {
+            // Keep attribute
+            other_attrs.push(attr.clone());
+        }
+    }
+    
+    // Process fluent context definitions (new syntax)
+    for fluent_ctx in &cfg.fluent_contexts {
+        // Forbidden fields are always excluded; validation reports any rule that would include them
+        if fluent_ctx.forbidden_fields.iter().any(|field_ref| field_ref.matches_field(&ident, selectable_fields, &cfg.field_groups)) {
+            never_in.push(fluent_ctx.name.clone());
+            exclusions.insert(fluent_ctx.name.to_string(), Exclusion::Forbidden);
+            continue;
+        }
+
+        // Check if this field matches any of the required fields
+        for field_ref in &fluent_ctx.required_fields {
+            if field_ref.matches_field(&ident, selectable_fields, &cfg.field_groups) {
+                required_in.push(fluent_ctx.name.clone());
+                // Store variant type if specified
+                if let Some(variant_type) = field_ref.get_variant_type() {
+                    variant_types.insert(fluent_ctx.name.to_string(), variant_type.clone());
+                }
+                break;
+            }
+        }
+        
+        // Check if this field matches any of the optional fields
+        for field_ref in &fluent_ctx.optional_fields {
+            if field_ref.matches_field(&ident, selectable_fields, &cfg.field_groups) {
+                optional_in.push(fluent_ctx.name.clone());
+                // Store variant type if specified
+                if let Some(variant_type) = field_ref.get_variant_type() {
+                    variant_types.insert(fluent_ctx.name.to_string(), variant_type.clone());
+                }
+                break;
+            }
+        }
+        
+        // Check if this field matches any of the excluded fields
+        for field_ref in &fluent_ctx.excluded_fields {
+            if field_ref.matches_field(&ident, selectable_fields, &cfg.field_groups) {
+                never_in.push(fluent_ctx.name.clone());
+                exclusions.insert(fluent_ctx.name.to_string(), Exclusion::Explicit);
+                // Store variant type if specified (though it won't be used since field is excluded)
+                if let Some(variant_type) = field_ref.get_variant_type() {
+                    variant_types.insert(fluent_ctx.name.to_string(), variant_type.clone());
+                }
+                break;
+            }
+        }
+    }
+    
+    // Apply default behaviors for fields not explicitly specified in fluent contexts
+    for fluent_ctx in &cfg.fluent_contexts {
+        let field_explicitly_mentioned = fluent_ctx.required_fields.iter().any(|field_ref| field_ref.matches_field(&ident, selectable_fields, &cfg.field_groups)) ||
+                                         fluent_ctx.optional_fields.iter().any(|field_ref| field_ref.matches_field(&ident, selectable_fields, &cfg.field_groups)) ||
+                                         fluent_ctx.excluded_fields.iter().any(|field_ref| field_ref.matches_field(&ident, selectable_fields, &cfg.field_groups)) ||
+                                         fluent_ctx.forbidden_fields.iter().any(|field_ref| field_ref.matches_field(&ident, selectable_fields, &cfg.field_groups));
+        
+        if !field_explicitly_mentioned && is_secret {
+            // Secret fields only appear where they are named
+            never_in.push(fluent_ctx.name.clone());
+            exclusions.insert(fluent_ctx.name.to_string(), Exclusion::Secret);
+        } else if !field_explicitly_mentioned && let Some(marker) = skip_marker {
+            // So do fields all_fields() skips for a marker
+            never_in.push(fluent_ctx.name.clone());
+            exclusions.insert(fluent_ctx.name.to_string(), Exclusion::Skipped(marker));
+        } else if !field_explicitly_mentioned {
+            // Apply default behavior for this context
+            let default_behavior = cfg.default_for_field(fluent_ctx, &ident, selectable_fields)
+                .unwrap_or(&DefaultBehavior::Optional); // Ultimate fallback
+            
+            match default_behavior {
+                DefaultBehavior::Required => {
+                    required_in.push(fluent_ctx.name.clone());
+                    required_by_default.push(fluent_ctx.name.clone());
+                }
+                DefaultBehavior::Optional => optional_in.push(fluent_ctx.name.clone()),
+                DefaultBehavior::Exclude => {
+                    never_in.push(fluent_ctx.name.clone());
+                    exclusions.insert(fluent_ctx.name.to_string(), Exclusion::Default);
+                }
+                DefaultBehavior::RespectBase if is_option_type(&field.ty) => optional_in.push(fluent_ctx.name.clone()),
+                DefaultBehavior::RespectBase => required_in.push(fluent_ctx.name.clone()),
+            }
+        }
+    }
+    
+    // Heap-heavy fields are shared wherever share_heavy_fields is enabled;
+    // secret fields keep their declared type so `ExposeSecret` still applies
+    if is_heavy_type(&field.ty) && !is_secret {
+        for ctx in &cfg.flags.share_heavy_in {
+            if !shared_in.contains(ctx) {
+                shared_in.push(ctx.clone());
+            }
+        }
+    }
+    
+    // The first matching type_map rule decides the field's type in each context
+    let mut mapped_types: std::collections::HashMap<String, Type> = std::collections::HashMap::new();
+    for mapping in &cfg.type_mappings {
+        let mapped = match option_inner_type(&field.ty) {
+            Some(inner) if type_matches(&mapping.from, inner) => {
+                let to = &mapping.to;
+                syn::parse_quote! { ::core::option::Option<#to> }
+            }
+            _ if type_matches(&mapping.from, &field.ty) => mapping.to.clone(),
+            _ => continue,
+        };
+        for ctx in &mapping.contexts {
+            mapped_types.entry(ctx.to_string()).or_insert_with(|| mapped.clone());
+        }
+    }
+    
+    // A when_* attribute is dead if the field never takes that role
+    let present: Vec<&Ident> = cfg.variants.iter().filter(|v| !never_in.contains(v)).collect();
+    let optional_somewhere = present.iter().any(|v| optional_in.contains(v) || !required_in.contains(v));
+    let required_somewhere = present.iter().any(|v| !optional_in.contains(v) && required_in.contains(v));
+    let dead_attrs = when_spans.into_iter().filter_map(|(name, span)| {
+        let reason = match name {
+            "when_optional" | "when_required" if present.is_empty() => "the field is excluded from every context",
+            "when_optional" if !optional_somewhere => "the field is optional in no context",
+            "when_required" if !required_somewhere => "the field is required in no context",
+            "when_base" if !cfg.build_base => "build_base = false emits no base struct",
+            _ => return None,
+        };
+        Some((span, format!("#[{}] on '{}' never applies: {}", name, ident, reason)))
+    }).collect();
+
+    // Determine if type is Option<...>
+    let is_option = is_option_type(&field.ty);
+    let base_wrapped = !is_option && if cfg.optional_base { base_required_span.is_none() } else { base_optional_span.is_some() };
+
+    // Conversions rebuild the nested struct from the hoisted fields, so it must be required as declared there
+    let flatten_into = match flatten_into {
+        Some((span, flatten)) => {
+            if base_wrapped {
+                return Err(syn::Error::new(span, format!("#[ctx_flatten_into] on '{}' needs the field unwrapped in the base", ident)));
+            }
+            for ctx in &flatten.contexts {
+                let key = ctx.to_string();
+                let reason = if never_in.contains(ctx) {
+                    "it is excluded there"
+                } else if optional_in.contains(ctx) || !required_in.contains(ctx) {
+                    "it is optional there"
+                } else if variant_types.contains_key(&key) || mapped_types.contains_key(&key) || shared_in.contains(ctx) || encrypted_in.contains(ctx) {
+                    "its type changes there"
+                } else {
+                    continue;
+                };
+                return Err(syn::Error::new(span, format!("#[ctx_flatten_into] cannot hoist '{}' into {}: {}", ident, ctx, reason)));
+            }
+            Some(flatten)
+        }
+        None => None,
+    };
+    Ok(FieldSpec {
+        ident,
+        ty: field.ty.clone(),
+        vis: field.vis.clone(),
+        variant_vis: match &cfg.max_visibility {
+            Some(max) => clamp_visibility(&field.vis, max),
+            None => field.vis.clone(),
+        },
+        attrs: other_attrs,
+        required_in,
+        optional_in,
+        never_in,
+        is_option,
+        optional_attrs,
+        required_attrs,
+        base_attrs,
+        variant_types,
+        shared_in,
+        mapped_types,
+        is_secret,
+        encrypt_with,
+        encrypted_in,
+        required_by_default,
+        dead_attrs,
+        exclusions,
+        base_wrapped,
+        base_optional_span,
+        order,
+        skip_variant_docs,
+        flatten_into,
+    })
+}
+
+/// Expand group field references now that we have access to struct fields
+pub(crate) fn expand_group_field_references(
+    variants_cfg: &mut VariantList, 
+    all_struct_fields: &[Ident]
+) -> Result<(), syn::Error> {
+    // First, expand group_field_refs to concrete field lists
+    let mut errors = None;
+    for (group_name, group) in &variants_cfg.group_field_refs {
+        if let Err(err) = validate_group_fields(group, all_struct_fields) {
+            push_error(&mut errors, err);
+            continue;
+        }
+        let expanded_fields = expand_group_field_refs(&group.fields, &variants_cfg.selectable_fields(all_struct_fields));
+        variants_cfg.field_groups.insert(group_name.clone(), expanded_fields);
+    }
+    if let Some(err) = errors {
+        return Err(err);
+    }
+    
+    // Now expand group references in fluent contexts
+    for fluent_ctx in &mut variants_cfg.fluent_contexts {
+        fluent_ctx.required_fields = expand_context_field_refs(&fluent_ctx.required_fields, &variants_cfg.field_groups)?;
+        fluent_ctx.optional_fields = expand_context_field_refs(&fluent_ctx.optional_fields, &variants_cfg.field_groups)?;
+        fluent_ctx.excluded_fields = expand_context_field_refs(&fluent_ctx.excluded_fields, &variants_cfg.field_groups)?;
+        fluent_ctx.forbidden_fields = expand_context_field_refs(&fluent_ctx.forbidden_fields, &variants_cfg.field_groups)?;
+        for (selectors, _) in &mut fluent_ctx.default_rules {
+            *selectors = expand_context_field_refs(selectors, &variants_cfg.field_groups)?;
+        }
+        for invariant in &mut fluent_ctx.invariants {
+            let fields = invariant.fields_mut();
+            *fields = std::mem::take(fields).into_iter()
+                .flat_map(|name| match variants_cfg.field_groups.get(&name.to_string()) {
+                    Some(members) => members.clone(),
+                    None => vec![name],
+                })
+                .collect();
+        }
+    }
+    
+    Ok(())
+}
+
+/// Fields a context makes required, following the same rules as `process_field`.
+/// Every `fields_required_in` selector of the context must already be resolved.
+fn context_required_fields(fluent_ctx: &FluentContext, cfg: &VariantList, all_field_names: &[Ident], selectable_fields: &[Ident]) -> Vec<Ident> {
+    let matches = |field_refs: &[FieldRef], field: &Ident| {
+        field_refs.iter().any(|field_ref| field_ref.matches_field(field, selectable_fields, &cfg.field_groups))
+    };
+    all_field_names.iter()
+        .filter(|field| {
+            if matches(&fluent_ctx.forbidden_fields, field) {
+                return false;
+            }
+            if matches(&fluent_ctx.required_fields, field) {
+                return true;
+            }
+            if matches(&fluent_ctx.optional_fields, field) || matches(&fluent_ctx.excluded_fields, field) || cfg.named_only(field) {
+                return false;
+            }
+            match cfg.default_for_field(fluent_ctx, field, selectable_fields) {
+                Some(DefaultBehavior::Required) => true,
+                Some(DefaultBehavior::RespectBase) => !cfg.option_fields.contains(field),
+                _ => false,
+            }
+        })
+        .cloned()
+        .collect()
+}
+
+/// Rewrite `serde("...")` selectors, and names in another case under `field_match = case_insensitive`,
+/// to the struct's field names, so later matching can compare identifiers exactly
+pub(crate) fn resolve_field_names(cfg: &mut VariantList, fields: &[&Field]) -> Result<(), syn::Error> {
+    // Each field's Rust name and serialized name
+    let names: Vec<(Ident, String)> = fields.iter()
+        .filter_map(|f| f.ident.as_ref().map(|ident| (ident.clone(), SerdeFieldInfo::from_attrs(ident, &f.attrs).key)))
+        .collect();
+    let group_names: Vec<String> = cfg.group_field_refs.keys().cloned().collect();
+    let case_insensitive = cfg.field_match == FieldMatch::CaseInsensitive;
+
+    // The one field whose name (or serde name) matches, honoring field_match
+    let find = |written: &str, span: Span, serde: bool| -> Result<Option<Ident>, syn::Error> {
+        let name_of = |(ident, key): &(Ident, String)| if serde { key.clone() } else { ident.unraw().to_string() };
+        if let Some(exact) = names.iter().find(|entry| name_of(entry) == written) {
+            return Ok(Some(exact.0.clone()));
+        }
+        if !case_insensitive {
+            return Ok(None);
+        }
+        let matches: Vec<&Ident> = names.iter().filter(|entry| name_of(entry).eq_ignore_ascii_case(written)).map(|(ident, _)| ident).collect();
+        match matches.as_slice() {
+            [] => Ok(None),
+            [ident] => Ok(Some((*ident).clone())),
+            _ => {
+                let listed: Vec<String> = matches.iter().map(|ident| format!("'{}'", ident)).collect();
+                Err(syn::Error::new(span, format!("'{}' matches several fields ignoring case: {}", written, listed.join(", "))))
+            }
+        }
+    };
+    let fix_ident = |ident: &mut Ident| -> Result<(), syn::Error> {
+        let written = ident.unraw().to_string();
+        if !group_names.contains(&written)
+            && let Some(mut field) = find(&written, ident.span(), false)? {
+            // Keeps raw fields like `r#type` raw
+            field.set_span(ident.span());
+            *ident = field;
+        }
+        Ok(())
+    };
+
+    let mut errors = None;
+    for fluent_ctx in &mut cfg.fluent_contexts {
+        let lists = [
+            &mut fluent_ctx.required_fields,
+            &mut fluent_ctx.optional_fields,
+            &mut fluent_ctx.excluded_fields,
+            &mut fluent_ctx.forbidden_fields,
+        ];
+        let rules = fluent_ctx.default_rules.iter_mut().flat_map(|(selectors, _)| selectors);
+        for field_ref in lists.into_iter().flatten().chain(rules) {
+            let result = match field_ref {
+                FieldRef::Field(field) | FieldRef::FieldWithType { field, .. } => fix_ident(field),
+                FieldRef::AllFields { except, .. } | FieldRef::GroupWithExcept { except, .. } | FieldRef::RequiredIn { except, .. } => {
+                    except.iter_mut().try_for_each(fix_ident)
+                }
+                FieldRef::GroupMember { .. } | FieldRef::Matching { .. } => Ok(()),
+                FieldRef::SerdeName { key, variant_type } => match find(&key.value(), key.span(), true) {
+                    Ok(Some(mut field)) => {
+                        field.set_span(key.span());
+                        *field_ref = match variant_type.take() {
+                            Some(variant_type) => FieldRef::FieldWithType { field, variant_type },
+                            None => FieldRef::Field(field),
+                        };
+                        Ok(())
+                    }
+                    Ok(None) => {
+                        let keys: Vec<&str> = names.iter().map(|(_, key)| key.as_str()).collect();
+                        Err(syn::Error::new(key.span(), match suggest_name(&key.value(), &keys) {
+                            Some(suggestion) => format!("no field is serialized as {:?} in {}; did you mean {:?}?", key.value(), fluent_ctx.name, suggestion),
+                            None => format!("no field is serialized as {:?} in {}", key.value(), fluent_ctx.name),
+                        }))
+                    }
+                    Err(err) => Err(err),
+                },
+            };
+            if let Err(err) = result {
+                push_error(&mut errors, err);
+            }
+        }
+    }
+    match errors {
+        Some(err) => Err(err),
+        None => Ok(()),
+    }
+}
+
+/// Resolve `fields_required_in(Context)` selectors, referenced contexts first
+pub(crate) fn resolve_required_in(cfg: &mut VariantList, all_field_names: &[Ident], selectable_fields: &[Ident]) -> Result<(), syn::Error> {
+    fn unresolved(fluent_ctx: &FluentContext) -> impl Iterator<Item = &Ident> {
+        fluent_ctx.required_fields.iter()
+            .chain(&fluent_ctx.optional_fields)
+            .chain(&fluent_ctx.excluded_fields)
+            .chain(&fluent_ctx.forbidden_fields)
+            .filter_map(|field_ref| match field_ref {
+                FieldRef::RequiredIn { context, fields: None, .. } => Some(context),
+                _ => None,
+            })
+    }
+
+    loop {
+        // Required fields of every referenced context that is itself fully resolved
+        let mut resolved: IndexMap<String, Vec<Ident>> = IndexMap::new();
+        let mut pending = None;
+        for fluent_ctx in &cfg.fluent_contexts {
+            for target in unresolved(fluent_ctx) {
+                let Some(target_ctx) = cfg.fluent_contexts.iter().find(|ctx| ctx.name == *target) else {
+                    let names: Vec<String> = cfg.fluent_contexts.iter().map(|ctx| ctx.name.to_string()).collect();
+                    let names: Vec<&str> = names.iter().map(String::as_str).collect();
+                    let message = match suggest_name(&target.to_string(), &names) {
+                        Some(suggestion) => format!("unknown context '{}' in fields_required_in(); did you mean '{}'?", target, suggestion),
+                        None => format!("unknown context '{}' in fields_required_in()", target),
+                    };
+                    return Err(syn::Error::new(target.span(), message));
+                };
+                if unresolved(target_ctx).next().is_none() {
+                    resolved.entry(target.to_string())
+                        .or_insert_with(|| context_required_fields(target_ctx, cfg, all_field_names, selectable_fields));
+                } else {
+                    pending.get_or_insert_with(|| (target.clone(), fluent_ctx.name.clone()));
+                }
+            }
+        }
+        if resolved.is_empty() {
+            // Nothing left, or every remaining reference waits on another one
+            return match pending {
+                None => Ok(()),
+                Some((target, context)) => Err(syn::Error::new(
+                    target.span(),
+                    format!("fields_required_in({}) in {} depends on itself through a cycle of contexts", target, context),
+                )),
+            };
+        }
+        for fluent_ctx in &mut cfg.fluent_contexts {
+            let lists = [
+                &mut fluent_ctx.required_fields,
+                &mut fluent_ctx.optional_fields,
+                &mut fluent_ctx.excluded_fields,
+                &mut fluent_ctx.forbidden_fields,
+            ];
+            for field_ref in lists.into_iter().flatten() {
+                if let FieldRef::RequiredIn { context, fields: fields @ None, .. } = field_ref
+                    && let Some(required) = resolved.get(&context.to_string()) {
+                    *fields = Some(required.clone());
+                }
+            }
+        }
+    }
+}
+
+/// Expand group references in one fluent field list into their member fields,
+/// remembering which group each member came from for diagnostics
+fn expand_context_field_refs(
+    field_refs: &[FieldRef],
+    field_groups: &IndexMap<String, Vec<Ident>>,
+) -> Result<Vec<FieldRef>, syn::Error> {
+    let mut expanded = Vec::new();
+    for field_ref in field_refs {
+        match field_ref {
+            FieldRef::Field(field_ident) => {
+                if let Some(group_fields) = field_groups.get(&field_ident.to_string()) {
+                    // This is a group name, expand it to individual fields
+                    for group_field in group_fields {
+                        expanded.push(FieldRef::GroupMember {
+                            field: group_field.clone(),
+                            group: field_ident.clone(),
+                            except: Vec::new(),
+                        });
+                    }
+                } else {
+                    // This is a regular field name
+                    expanded.push(field_ref.clone());
+                }
+            }
+            FieldRef::FieldWithType { .. } | FieldRef::GroupMember { .. } | FieldRef::RequiredIn { .. } | FieldRef::SerdeName { .. } | FieldRef::Matching { .. } => {
+                // Field with variant type or already-expanded member - keep as-is
+                expanded.push(field_ref.clone());
+            }
+            FieldRef::AllFields { except_groups, .. } => {
+                // Keep all_fields() as-is (it will be resolved later when we have struct field access)
+                if let Some(group) = except_groups.iter().find(|group| !field_groups.contains_key(&group.to_string())) {
+                    return Err(syn::Error::new(group.span(), format!("unknown field group '{}' in except_groups()", group)));
+                }
+                expanded.push(field_ref.clone());
+            }
+            FieldRef::GroupWithExcept { group, except } => {
+                // Expand group to individual fields, excluding specified ones
+                if let Some(group_fields) = field_groups.get(&group.to_string()) {
+                    for group_field in group_fields {
+                        if !except.contains(group_field) {
+                            expanded.push(FieldRef::GroupMember {
+                                field: group_field.clone(),
+                                group: group.clone(),
+                                except: except.clone(),
+                            });
+                        }
+                    }
+                } else {
+                    return Err(syn::Error::new(group.span(), format!("unknown field group '{}'", group)));
+                }
+            }
+        }
+    }
+    Ok(expanded)
+}
+
+/// Ensure every field named inside a group definition exists on the struct
+fn validate_group_fields(group: &GroupDef, all_struct_fields: &[Ident]) -> Result<(), syn::Error> {
+    for field_ref in &group.fields {
+        let names = match field_ref {
+            GroupFieldRef::Field(field_name) => std::slice::from_ref(field_name),
+            GroupFieldRef::AllFields { except } => except.as_slice(),
+        };
+        for name in names {
+            if !all_struct_fields.contains(name) {
+                let mut err = syn::Error::new(name.span(), format!("field '{}' in group '{}' not found in struct", name, group.name));
+                err.combine(syn::Error::new(group.name.span(), format!("group '{}' defined here", group.name)));
+                return Err(err);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Validate fluent contexts for field conflicts and complete coverage
+pub(crate) fn validate_fluent_contexts(cfg: &VariantList, all_field_names: &[Ident]) {
+    let selectable_fields = cfg.selectable_fields(all_field_names);
+    // Contexts without a default that leave each field out, reported at the field when there are several
+    let mut unhandled_in: IndexMap<&Ident, Vec<&Ident>> = IndexMap::new();
+    for fluent_ctx in &cfg.fluent_contexts {
+        // Every field named by a selector must exist on the struct
+        let selectors = fluent_ctx.required_fields.iter()
+            .chain(&fluent_ctx.optional_fields)
+            .chain(&fluent_ctx.excluded_fields)
+            .chain(&fluent_ctx.forbidden_fields)
+            .chain(fluent_ctx.default_rules.iter().flat_map(|(selectors, _)| selectors));
+        for field_ref in selectors {
+            let named: &[Ident] = match field_ref {
+                FieldRef::Field(name) | FieldRef::FieldWithType { field: name, .. } => std::slice::from_ref(name),
+                FieldRef::AllFields { except, .. } | FieldRef::GroupWithExcept { except, .. } | FieldRef::RequiredIn { except, .. } => except,
+                // Group members were checked when the group was expanded; serde names when they were resolved
+                FieldRef::GroupMember { .. } | FieldRef::SerdeName { .. } | FieldRef::Matching { .. } => &[],
+            };
+            for name in named.iter().filter(|name| !all_field_names.contains(name)) {
+                let field_names: Vec<String> = all_field_names.iter().map(|f| f.to_string()).collect();
+                let field_names: Vec<&str> = field_names.iter().map(String::as_str).collect();
+                match suggest_name(&name.to_string(), &field_names) {
+                    Some(suggestion) => emit_error!(
+                        name.span(), "unknown field '{}' in {}", name, fluent_ctx.name;
+                        help = "did you mean '{}'?", suggestion
+                    ),
+                    None => emit_error!(name.span(), "unknown field '{}' in {}", name, fluent_ctx.name),
+                }
+            }
+        }
+
+        // Check for field conflicts within each context
+        let mut field_mentions = IndexMap::new();
+        
+        // Track where each field is mentioned, along with the selector that mentioned it
+        let categories = [
+            ("required", &fluent_ctx.required_fields),
+            ("optional", &fluent_ctx.optional_fields),
+            ("excluded", &fluent_ctx.excluded_fields),
+        ];
+        for (category, field_refs) in categories {
+            for field_ref in field_refs {
+                for field_name in all_field_names {
+                    if field_ref.matches_field(field_name, &selectable_fields, &cfg.field_groups) {
+                        let mentions = field_mentions.entry(field_name.clone()).or_insert_with(Vec::new);
+                        mentions.push((category, format!("{} via {}", category, field_ref.describe())));
+                    }
+                }
+            }
+        }
+        
+        // Check for conflicts (field mentioned more than once)
+        for (field_name, mentions) in &field_mentions {
+            if mentions.len() > 1 {
+                let mentions: Vec<&str> = mentions.iter().map(|(_, mention)| mention.as_str()).collect();
+                emit_error!(
+                    fluent_ctx.end_span,
+                    "field '{}' mentioned multiple times: {}", field_name, mentions.join(", ");
+                    label = "conflicting field specifications here"
+                );
+            }
+        }
+
+        // Forbidden fields must not be included by any other rule, the default included
+        let forbidden_fields: Vec<&Ident> = all_field_names.iter()
+            .filter(|field_name| fluent_ctx.forbidden_fields.iter().any(|field_ref| field_ref.matches_field(field_name, &selectable_fields, &cfg.field_groups)))
+            .collect();
+        for field_name in &forbidden_fields {
+            let including: Vec<&str> = field_mentions.get(*field_name).into_iter().flatten()
+                .filter(|(category, _)| *category != "excluded")
+                .map(|(_, mention)| mention.as_str())
+                .collect();
+            if !including.is_empty() {
+                emit_error!(
+                    fluent_ctx.end_span,
+                    "field '{}' is forbidden in {} but included as {}", field_name, fluent_ctx.name, including.join(", ");
+                    help = "remove the field from these selectors, e.g. with .except({})", field_name;
+                    label = "forbidden field included here"
+                );
+            } else if !field_mentions.contains_key(*field_name)
+                && let Some(default) = cfg.default_for_field(fluent_ctx, field_name, &selectable_fields).filter(|default| **default != DefaultBehavior::Exclude) {
+                emit_error!(
+                    fluent_ctx.end_span,
+                    "field '{}' is forbidden in {} but {} would include it", field_name, fluent_ctx.name, default.call();
+                    help = "add .excludes({}) to exclude it explicitly", field_name;
+                    label = "forbidden field included by default here"
+                );
+            }
+        }
+        
+        // Check for complete coverage (every field is either explicitly mentioned or has a default)
+        let has_default = cfg.default_for(fluent_ctx).is_some();
+        
+        if !has_default {
+            // Unmentioned secret fields are excluded rather than reported
+            let unmentioned_fields: Vec<&Ident> = selectable_fields.iter()
+                .filter(|field_name| !field_mentions.contains_key(*field_name) && !forbidden_fields.contains(field_name))
+                .filter(|field_name| cfg.default_for_field(fluent_ctx, field_name, &selectable_fields).is_none())
+                .collect();
+                
+            for field_name in &unmentioned_fields {
+                unhandled_in.entry(*field_name).or_default().push(&fluent_ctx.name);
+            }
+            if !unmentioned_fields.is_empty() {
+                let field_list: Vec<String> = unmentioned_fields.iter().map(|f| f.to_string()).collect();
+                let suggestion = if field_list.len() == 1 {
+                    let field = &field_list[0];
+                    format!("add .requires({}), .optional({}), .excludes({}), or .default(optional/required/exclude)", field, field, field)
+                } else {
+                    format!("add .requires({}), .optional({}), .excludes({}), or .default(optional/required/exclude)", 
+                           field_list.join(","), field_list.join(","), field_list.join(","))
+                };
+                emit_error!(
+                    fluent_ctx.end_span,
+                    "missing fields: {}", field_list.join(", ");
+                    help = suggestion;
+                    label = "all fields must be specified here if `default(...)` is not set"
+                );
+            }
+        }
+    }
+
+    // A field added to a large struct is usually missing from several contexts at once
+    for (field_name, contexts) in unhandled_in.iter().filter(|(_, contexts)| contexts.len() > 1) {
+        let contexts: Vec<String> = contexts.iter().map(|ctx| ctx.to_string()).collect();
+        emit_error!(
+            field_name.span(), "field '{}' is not handled by {} contexts: {}", field_name, contexts.len(), contexts.join(", ");
+            help = "add '{}' to .requires(...), .optional(...) or .excludes(...) in each of them, or give them a .default(...)", field_name
+        );
+    }
+}
@@ -0,0 +1,14 @@
+use context_variants::variants;
+
+#[variants(
+    Create: requires(name, email).excludes(id),
+    Update: requires(id).optional(name, email),
+    suffix = "Form"
+)]
+#[derive(Debug, Clone)]
+pub struct User {
+    /// Database id
+    pub id: u64,
+    pub name: String,
+    pub email: String,
+}
@@ -0,0 +1,17 @@
+use context_variants::variants;
+
+#[variants(
+    Create: requires(name).optional(tags).excludes(id).key(name),
+    Read: requires(all_fields()),
+    optional_base = true,
+    presence = true,
+    try_from = true,
+    suffix = "Item"
+)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Item {
+    #[ctx_base_required]
+    pub id: u64,
+    pub name: String,
+    pub tags: Vec<String>,
+}
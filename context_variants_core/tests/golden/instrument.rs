@@ -0,0 +1,16 @@
+use context_variants::variants;
+
+#[variants(
+    Update: requires(id).optional(name, email).at_least_one_of(name, email),
+    try_from = true,
+    apply_to = true,
+    diff = Update,
+    instrument = tracing,
+    suffix = "Form"
+)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct User {
+    pub id: u64,
+    pub name: String,
+    pub email: String,
+}
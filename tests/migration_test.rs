@@ -332,6 +332,7 @@ struct UserEntity {
 }
 
 // Mock deserializer functions
+#[allow(dead_code)]
 fn deserialize_email_base<'de, D>(_deserializer: D) -> Result<String, D::Error>
 where D: serde::Deserializer<'de> {
     Ok("base@example.com".to_string())
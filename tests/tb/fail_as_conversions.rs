@@ -0,0 +1,14 @@
+use context_variants::variants;
+
+// A named context must convert both ways
+#[variants(
+    Read: requires(id as String, name),
+    Patch: requires(id).optional(name),
+    as_conversions = { Read: true, Patch: true }
+)]
+struct Player {
+    pub id: u64,
+    pub name: String,
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use context_variants::variants;
+
+#[variants(
+    Read: requires(id, map as HashMap<String, >>),
+    suffix = "View"
+)]
+struct Widget {
+    pub id: u64,
+    pub map: Vec<u8>,
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+// Test case: a field that is not Send + Sync should fail at the context definition
+use context_variants::variants;
+use std::rc::Rc;
+
+#[variants(
+    Create: requires(name).excludes(id),
+    Read: requires(id, name), // ERROR: Rc<String> is neither Send nor Sync
+    assert_auto_traits = { Read: true },
+    suffix = "Form"
+)]
+#[derive(Debug)]
+struct User {
+    pub id: u64,
+    pub name: Rc<String>,
+}
+
+fn main() {}
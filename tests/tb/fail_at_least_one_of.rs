@@ -0,0 +1,14 @@
+use context_variants::variants;
+
+#[variants(
+    Update: requires(id, name).optional(email).excludes(metadata).at_least_one_of(name, email, metadata),
+    suffix = "Customer"
+)]
+struct Customer {
+    pub id: u64,
+    pub name: String,
+    pub email: String,
+    pub metadata: Option<String>,
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use context_variants::variants;
+
+#[variants(
+    Create: requires(name).excludes(id),
+    base_name = "UserRecord"
+)]
+struct User {
+    pub id: u64,
+    pub name: String,
+}
+
+fn main() {
+    // Only the renamed base exists
+    let _user = User { id: 1, name: "ann".to_string() };
+}
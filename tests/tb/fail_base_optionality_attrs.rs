@@ -0,0 +1,24 @@
+use context_variants::variants;
+
+#[variants(
+    Create: requires(name).default(exclude),
+    suffix = "Form"
+)]
+struct User {
+    // optional_base is off, so the base field is already required
+    #[ctx_base_required]
+    pub name: String,
+}
+
+#[variants(
+    Create: requires(x, y),
+    suffix = "Form"
+)]
+#[repr(C)]
+struct Point {
+    pub x: f64,
+    #[ctx_base_optional]
+    pub y: f64,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use context_variants::variants;
+
+#[variants(
+    Read: requires(first, last).computed(first: String = self.first.to_uppercase()),
+)]
+struct Person {
+    pub first: String,
+    pub last: String,
+}
+
+fn main() {}
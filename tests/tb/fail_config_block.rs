@@ -0,0 +1,19 @@
+use context_variants::variants;
+
+#[variants(
+    Create: requires(name),
+    config(naming(prefx = "Api")),
+)]
+struct User {
+    pub name: String,
+}
+
+#[variants(
+    Create: requires(name),
+    config(codgen(presence)),
+)]
+struct Post {
+    pub name: String,
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+// Test case: defaults keyed by a name that is neither a context set nor a context
+use context_variants::variants;
+
+#[variants(
+    context_sets = (write(Create, Update)),
+    defaults = { writes: exclude }, // ERROR: typo for write
+    Create: requires(name),
+    Update: requires(id),
+    suffix = "Form"
+)]
+#[derive(Debug)]
+struct User {
+    pub id: u64,
+    pub name: String,
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+use context_variants::variants;
+
+#[variants(
+    Create: requires(name).excludes(id),
+    conversions = { Create: true },
+    suffix = "Form"
+)]
+struct Product {
+    pub id: u64,
+    pub name: String,
+}
+
+fn main() {}
@@ -0,0 +1,23 @@
+#![deny(missing_docs)]
+//! Variant fields without docs are reported by `missing_docs`
+
+use context_variants::variants;
+
+/// A registered user
+#[variants(Read: requires(id, email), suffix = "Form")]
+pub struct User {
+    /// Identifier
+    pub id: u64,
+    /// Contact address, documented on the base only
+    #[ctx_skip_variant_field_docs]
+    pub email: String,
+}
+
+/// A post
+#[variants(Read: requires(title), copy_field_docs = false, prefix = "Post")]
+pub struct Post {
+    /// Headline
+    pub title: String,
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+use context_variants::variants;
+
+#[variants(Create: requires(name, email), suffix = "Form")]
+struct User {
+    #[ctx_order(1)]
+    pub name: String,
+    #[ctx_order(1)]
+    pub email: String,
+}
+
+#[variants(Create: requires(name), suffix = "Form")]
+struct Note {
+    #[ctx_order(first)]
+    pub name: String,
+}
+
+fn main() {}
@@ -0,0 +1,20 @@
+use context_variants::variants;
+
+#[variants(
+    Create: requires(name).excludes(id).name("UserForm"),
+    Update: requires(id).optional(name).name("UserForm")
+)]
+struct User {
+    pub id: u64,
+    pub name: String,
+}
+
+#[variants(
+    Create: requires(name).excludes(id).name("Not A Type")
+)]
+struct Post {
+    pub id: u64,
+    pub name: String,
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+use context_variants::variants;
+
+#[variants(Update: requires(id).default_for(matching("*_at"), sometimes))]
+struct Post {
+    pub id: u64,
+    pub created_at: u64,
+}
+
+#[variants(Create: requires(title).default_for(matching("*_at"), exclude))]
+struct Draft {
+    pub title: String,
+    pub created_at: u64,
+    pub body: String,
+}
+
+fn main() {}
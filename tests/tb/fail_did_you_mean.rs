@@ -0,0 +1,15 @@
+use context_variants::variants;
+
+// Common synonyms and near-misses point at the right spelling
+#[variants(
+    Create: required(name),
+    Update: requires(id).optional_fields(name),
+    Read: requries(id, name),
+    sufix = "Form"
+)]
+struct User {
+    pub id: u64,
+    pub name: String,
+}
+
+fn main() {}
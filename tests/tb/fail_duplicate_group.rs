@@ -0,0 +1,20 @@
+// Test case: a group name declared twice should be rejected instead of overwritten
+use context_variants::variants;
+
+#[variants(
+    groups = (
+        auth(user_id, token),
+        auth(name, email) // ERROR: auth already declared
+    ),
+    Login: requires(auth).default(exclude),
+    suffix = "Form"
+)]
+#[derive(Debug)]
+struct User {
+    pub user_id: String,
+    pub token: String,
+    pub name: String,
+    pub email: String,
+}
+
+fn main() {}
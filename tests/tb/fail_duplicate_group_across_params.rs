@@ -0,0 +1,15 @@
+use context_variants::variants;
+
+#[variants(
+    groups = (auth(user_id, token)),
+    Login: requires(auth).default(exclude),
+    groups = (auth(name)),
+    suffix = "Form"
+)]
+struct User {
+    pub user_id: String,
+    pub token: String,
+    pub name: String,
+}
+
+fn main() {}
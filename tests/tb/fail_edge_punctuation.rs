@@ -0,0 +1,12 @@
+use context_variants::variants;
+
+// A doubled comma is reported where it occurs
+#[variants(
+    Create: requires(name),,
+    suffix = "Form"
+)]
+struct User {
+    pub name: String,
+}
+
+fn main() {}
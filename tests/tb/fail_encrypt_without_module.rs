@@ -0,0 +1,14 @@
+use context_variants::variants;
+
+#[variants(
+    Create: requires(email).excludes(id),
+    Stored: requires(id, email),
+    suffix = "Contact"
+)]
+struct Contact {
+    pub id: u64,
+    #[ctx_encrypt(Stored)]
+    pub email: String,
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+use context_variants::variants;
+
+// The wrapper is tagged through serde, so the struct must derive a serde trait
+#[variants(
+    Create: requires(name).excludes(id),
+    enum_wrapper = "UserPayload"
+)]
+#[derive(Debug)]
+struct User {
+    pub id: u64,
+    pub name: String,
+}
+
+fn main() {}
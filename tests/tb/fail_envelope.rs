@@ -0,0 +1,15 @@
+use context_variants::variants;
+
+pub struct ApiResponse<T>(T);
+
+#[variants(
+    Create: requires(name).excludes(id).envelope(ApiResponse, Reed),
+    Read: requires(id, name).envelope(ApiResponse),
+    suffix = "Response"
+)]
+struct User {
+    pub id: u64,
+    pub name: String,
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+// Test case: except_groups() names a group that is not defined
+use context_variants::variants;
+
+#[variants(
+    groups = (pii(name, email)),
+    Public: requires(all_fields().except_groups(pi)),
+    suffix = "View"
+)]
+struct Customer {
+    pub id: u64,
+    pub name: String,
+    pub email: String,
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+use context_variants::variants;
+
+#[variants(
+    Login: requires(username).optional(password).default(exclude).one_of(password, sso_token),
+    suffix = "Auth"
+)]
+struct Auth {
+    pub username: String,
+    pub password: String,
+    pub sso_token: String,
+}
+
+fn main() {}
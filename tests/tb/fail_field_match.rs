@@ -0,0 +1,27 @@
+use context_variants::variants;
+use serde::Serialize;
+
+#[variants(
+    // Without field_match = case_insensitive, names must match exactly
+    Create: requires(serde("username"), Email),
+    suffix = "Form"
+)]
+#[derive(Serialize)]
+struct Account {
+    #[serde(rename = "userName")]
+    pub username: String,
+    pub email: String,
+}
+
+#[variants(
+    Create: requires(NAME),
+    field_match = case_insensitive,
+    suffix = "Form"
+)]
+struct Ambiguous {
+    #[allow(non_snake_case)]
+    pub Name: String,
+    pub name: String,
+}
+
+fn main() {}
@@ -0,0 +1,21 @@
+use context_variants::variants;
+
+// HasId is only implemented where id is required
+#[variants(
+    Create: requires(name).excludes(id),
+    Update: requires(name).optional(id),
+    field_traits = true
+)]
+struct User {
+    pub id: u64,
+    pub name: String,
+}
+
+fn id_of(value: &impl HasId) -> u64 {
+    *value.id()
+}
+
+fn main() {
+    let update = Update { id: None, name: "ann".to_string() };
+    id_of(&update);
+}
@@ -0,0 +1,22 @@
+use context_variants::variants;
+
+#[variants(
+    Request: requires(name).default(exclude),
+    Response: requires(fields_required_in(Requets), id).default(exclude),
+    suffix = "Dto"
+)]
+struct User {
+    pub id: u64,
+    pub name: String,
+}
+
+#[variants(
+    Left: requires(fields_required_in(Right)).default(exclude),
+    Right: requires(fields_required_in(Left), id).default(exclude),
+    suffix = "Side"
+)]
+struct Pair {
+    pub id: u64,
+}
+
+fn main() {}
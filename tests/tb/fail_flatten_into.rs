@@ -0,0 +1,39 @@
+use context_variants::variants;
+
+pub struct Db {
+    pub host: String,
+    pub port: u16,
+}
+
+#[variants(
+    Create: requires(name).optional(db),
+    suffix = "Form"
+)]
+struct Service {
+    pub name: String,
+    // Conversions need every hoisted field, so the field must be required there
+    #[ctx_flatten_into(Create, fields(host: String, port: u16))]
+    pub db: Db,
+}
+
+#[variants(
+    Create: requires(name, db),
+    suffix = "Form"
+)]
+struct Replica {
+    pub name: String,
+    #[ctx_flatten_into(Create, fields(name: String, port: u16))]
+    pub db: Db,
+}
+
+#[variants(
+    Create: requires(name, db),
+    suffix = "Form"
+)]
+struct Mirror {
+    pub name: String,
+    #[ctx_flatten_into(Create, fields(host: String))]
+    pub db: Option<Db>,
+}
+
+fn main() {}
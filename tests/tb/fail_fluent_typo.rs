@@ -0,0 +1,12 @@
+use context_variants::variants;
+
+// A typo inside a fluent context is reported at the typo, not as a prefix/suffix error
+#[variants(
+    Create: require(name),
+    suffix = "Form"
+)]
+struct User {
+    pub name: String,
+}
+
+fn main() {}
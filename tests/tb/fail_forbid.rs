@@ -0,0 +1,16 @@
+use context_variants::variants;
+
+#[variants(
+    groups = credentials(email, password),
+    Read: requires(all_fields()).forbid(password),
+    Export: optional(credentials).forbid(password).default(exclude),
+    Summary: requires(id).forbid(password).default(optional),
+    suffix = "Account"
+)]
+struct Account {
+    pub id: u64,
+    pub email: String,
+    pub password: String,
+}
+
+fn main() {}
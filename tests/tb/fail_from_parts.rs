@@ -0,0 +1,14 @@
+use context_variants::variants;
+
+// A named context must be able to build the base
+#[variants(
+    Update: requires(id).optional(name).excludes(created_at),
+    from_parts = { Update: true }
+)]
+struct User {
+    pub id: u64,
+    pub name: String,
+    pub created_at: String,
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use context_variants::variants;
+
+// The base wraps every field in Option, so a required field can't be filled
+#[variants(
+    from_ref = true,
+    Read: requires(id).optional(name),
+    optional_base = true,
+    suffix = "View"
+)]
+struct User {
+    pub id: u64,
+    pub name: String,
+}
+
+fn main() {}
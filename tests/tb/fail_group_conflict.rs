@@ -0,0 +1,21 @@
+// Test case: conflicts coming from groups and all_fields() should name their selectors
+use context_variants::variants;
+
+#[variants(
+    groups = (
+        auth(user_id, token),
+        contact(name, email)
+    ),
+    Login: requires(auth).excludes(all_fields().except(name, email)).default(exclude), // ERROR: user_id, token conflict
+    Register: requires(contact, token).optional(auth).default(exclude), // ERROR: token conflicts
+    suffix = "Form"
+)]
+#[derive(Debug)]
+struct User {
+    pub user_id: String,
+    pub token: String,
+    pub name: String,
+    pub email: String,
+}
+
+fn main() {}
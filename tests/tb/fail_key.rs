@@ -0,0 +1,13 @@
+use context_variants::variants;
+
+#[variants(
+    Create: requires(name).excludes(id).key(id),
+    Read: requires(id, name).key(id, missing),
+    suffix = "Account"
+)]
+struct Account {
+    pub id: u64,
+    pub name: String,
+}
+
+fn main() {}
@@ -0,0 +1,31 @@
+// Test case: a pub base above max_visibility, and a variant capped at private used outside its module
+use context_variants::variants;
+
+#[variants(
+    Create: requires(name).excludes(id),
+    max_visibility = pub(crate),
+    suffix = "Form"
+)]
+pub struct User {
+    pub id: u64,
+    pub name: String,
+}
+
+mod teams {
+    use context_variants::variants;
+
+    #[variants(
+        Create: requires(name).excludes(id),
+        max_visibility = private,
+        allow_public = true,
+        suffix = "Form"
+    )]
+    pub struct Team {
+        pub id: u64,
+        pub name: String,
+    }
+}
+
+fn main() {
+    let _ = teams::CreateForm { name: "core".to_string() };
+}
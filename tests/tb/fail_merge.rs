@@ -0,0 +1,9 @@
+use context_variants::variants;
+
+#[variants(Update: requires(id).optional(name), merge = { Update: true })]
+struct User {
+    pub id: u64,
+    pub name: String,
+}
+
+fn main() {}
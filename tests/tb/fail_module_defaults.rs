@@ -0,0 +1,4 @@
+#[context_variants::defaults(Create: requires(name), suffix = "Dto")]
+mod dto {}
+
+fn main() {}
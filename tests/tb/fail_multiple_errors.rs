@@ -0,0 +1,51 @@
+use context_variants::variants;
+
+// Every argument error is reported in one compile
+#[variants(
+    Create: requires(name).exclude(id),
+    Update: requires(id).optional(name),
+    prefix = Api,
+    sufix = "Request"
+)]
+struct User {
+    pub id: u64,
+    pub name: String,
+}
+
+// Unknown fields are reported alongside other validation errors
+#[variants(
+    Create: requires(nmae).excludes(id, emial),
+    Update: requires(id).optional(name).default(optional),
+    suffix = "Form"
+)]
+struct Profile {
+    pub id: u64,
+    pub name: String,
+}
+
+// Errors found while resolving per-context flags are reported together
+#[variants(
+    Create: requires(name).excludes(id),
+    Read: requires(id, name),
+    from_ref = { Raed: true },
+    as_conversions = true,
+    conversions = { Create: true },
+    suffix = "Dto"
+)]
+struct Account {
+    pub id: u64,
+    pub name: String,
+}
+
+// So are the checks made while expanding
+#[variants(
+    Create: requires(name).excludes(id),
+    Read: requires(id, name).name("Item"),
+    max_visibility = pub(crate)
+)]
+pub struct Item {
+    pub id: u64,
+    pub name: String,
+}
+
+fn main() {}
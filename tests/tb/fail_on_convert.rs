@@ -0,0 +1,13 @@
+use context_variants::variants;
+
+fn fill_user(_user: &mut User, _form: &CreateForm) {}
+
+// The hook runs inside the conversion, so the context must convert
+#[variants(Create: requires(name), Read: requires(name))]
+#[ctx_on_convert(Create = "fill_user")]
+#[derive(Clone)]
+struct User {
+    pub name: String,
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+// The warning is a deprecation; denying it makes the message visible to the test
+#![deny(deprecated)]
+
+use context_variants::variants;
+
+#[variants(
+    Read: requires(id).default(required),
+    Quiet: requires(id, nickname).default(required),
+    suffix = "View"
+)]
+struct Profile {
+    pub id: u64,
+    pub nickname: Option<String>,
+}
+
+fn main() {}
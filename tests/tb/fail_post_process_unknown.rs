@@ -0,0 +1,13 @@
+use context_variants::variants;
+
+// The hook is applied to every generated struct, so an unknown macro is reported
+#[variants(
+    post_process = audit_dto,
+    Create: requires(name),
+    suffix = "Tag"
+)]
+struct Tag {
+    pub name: String,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+// The warning is a deprecation; denying it makes the message visible to the test
+#![deny(deprecated)]
+
+use context_variants::variants;
+
+#[variants(Create: requires(name), preserve_attr_text = [builder])]
+struct User {
+    pub name: String,
+}
+
+fn main() {}
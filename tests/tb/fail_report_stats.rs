@@ -0,0 +1,19 @@
+// The report is a warning; denying it makes the message visible to the test
+#![deny(deprecated)]
+
+use context_variants::variants;
+
+#[variants(
+    report_stats = true,
+    presence = { Update: true },
+    Create: requires(name).excludes(id),
+    Update: requires(id).optional(name),
+    suffix = "Form"
+)]
+#[derive(Debug)]
+struct Item {
+    pub id: u64,
+    pub name: String,
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use context_variants::variants;
+
+#[variants(
+    Create: requires(x, y).excludes(id),
+    Update: requires(id).optional(x, y),
+    suffix = "Point"
+)]
+#[repr(C)]
+struct Point {
+    pub id: u64,
+    pub x: f64,
+    pub y: f64,
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+use context_variants::variants;
+
+// respect_base_optionality() replaces default(...), so the two can't be combined
+#[variants(
+    Create: requires(name).default(optional).respect_base_optionality(),
+    suffix = "Form"
+)]
+struct User {
+    pub name: String,
+    pub nickname: Option<String>,
+}
+
+fn main() {}
@@ -0,0 +1,29 @@
+use context_variants::variants;
+
+pub trait SameContext<Other> {
+    fn into_same(self) -> Other;
+}
+
+// The families must agree on the fields of each context
+#[variants(
+    Read: requires(id, name).default(exclude),
+    suffix = "User",
+    same_context = SameContext,
+    same_context_families = ["Admin{}User"]
+)]
+struct User {
+    pub id: u64,
+    pub name: String,
+}
+
+#[variants(
+    Read: requires(id, permissions).default(exclude),
+    prefix = "Admin",
+    suffix = "User"
+)]
+struct AdminUser {
+    pub id: u64,
+    pub permissions: Vec<String>,
+}
+
+fn main() {}
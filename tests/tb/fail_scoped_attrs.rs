@@ -0,0 +1,13 @@
+use context_variants::variants;
+
+// Scoped attrs must name a declared context or context set
+#[variants(
+    Create: requires(name),
+    Update: optional(name),
+    optional_attrs(Patch) = [allow(dead_code)]
+)]
+struct User {
+    pub name: String,
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+use context_variants::variants;
+use serde::Deserialize;
+
+// Every context must convert into the base
+#[variants(
+    Create: requires(name),
+    Read: requires(name),
+    enum_wrapper = "UserPayload",
+    serde_try_from = true,
+    conversions = { Create: true }
+)]
+#[derive(Deserialize)]
+struct User {
+    pub name: String,
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+use context_variants::variants;
+
+// `phone` was added to the struct, and the contexts without a default don't handle it
+#[variants(
+    Create: requires(name, email).excludes(id),
+    Update: requires(id).optional(name, email),
+    Read: requires(all_fields()),
+    Summary: requires(id).default(exclude)
+)]
+struct User {
+    pub id: u64,
+    pub name: String,
+    pub email: String,
+    pub phone: String,
+}
+
+fn main() {}
@@ -0,0 +1,19 @@
+// Test case: a group referencing a field the struct does not have
+use context_variants::variants;
+
+#[variants(
+    groups = (
+        auth(user_id, tokn), // ERROR: typo for token
+        contact(name)
+    ),
+    Login: requires(auth).default(exclude),
+    suffix = "Form"
+)]
+#[derive(Debug)]
+struct User {
+    pub user_id: String,
+    pub token: String,
+    pub name: String,
+}
+
+fn main() {}
@@ -0,0 +1,25 @@
+// The warnings are deprecations; denying them makes the messages visible to the test
+#![deny(deprecated)]
+
+use context_variants::variants;
+
+#[variants(
+    Create: requires(name, email).excludes(id, internal),
+    Update: requires(id, name).optional(email).excludes(internal),
+    build_base = false,
+    warn_dead_attrs = true,
+    suffix = "Form"
+)]
+struct User {
+    pub id: u64,
+    #[when_optional(doc = "never optional")]
+    pub name: String,
+    #[when_required(doc = "required in Create")]
+    #[when_base(doc = "no base struct")]
+    pub email: String,
+    #[when_required(doc = "excluded everywhere")]
+    #[when_optional(doc = "excluded everywhere")]
+    pub internal: u64,
+}
+
+fn main() {}
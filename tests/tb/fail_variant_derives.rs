@@ -0,0 +1,14 @@
+use context_variants::variants;
+
+// Variants already get the struct's derives
+#[variants(
+    Read: requires(id, name).derive(Debug, Hash),
+    suffix = "Form"
+)]
+#[derive(Debug)]
+struct User {
+    pub id: u64,
+    pub name: String,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use context_variants::variants;
+
+#[variants(
+    Create: requires(name).verbatim_field(pub name: String),
+    Read: requires(name).verbatim_field(pub note: String).verbatim_field("pub note: u8"),
+)]
+struct User {
+    pub name: String,
+}
+
+fn main() {}
@@ -0,0 +1,54 @@
+use context_variants::variants;
+use serde::{Deserialize, Serialize};
+
+// all_fields() skips #[ctx_passthrough], #[cfg(...)] and #[serde(skip)] fields;
+// contexts that want them name them
+#[variants(
+    Create: requires(all_fields()).optional(trace_id),
+    Read: requires(all_fields()),
+    suffix = "Event"
+)]
+#[derive(Debug, Serialize, Deserialize)]
+struct Audit {
+    pub id: u64,
+    pub action: String,
+    #[ctx_passthrough]
+    pub trace_id: String,
+    #[cfg(test)]
+    pub fixture: String,
+    #[serde(skip)]
+    pub cached: u32,
+}
+
+// all_fields_includes opts markers back in
+#[variants(
+    Full: requires(all_fields()),
+    all_fields_includes = [serde_skip, passthrough],
+    suffix = "Row"
+)]
+#[derive(Debug, Serialize)]
+struct Record {
+    pub id: u64,
+    #[ctx_passthrough]
+    pub trace_id: String,
+    #[serde(skip)]
+    pub cached: u32,
+    #[cfg(test)]
+    pub fixture: String,
+}
+
+fn main() {
+    let create = CreateEvent {
+        id: 1,
+        action: "login".to_string(),
+        trace_id: None,
+    };
+    assert_eq!(create.action, "login");
+
+    // Read names none of the marked fields
+    let read = ReadEvent { id: 1, action: "login".to_string() };
+    assert_eq!(format!("{:?}", read), r#"ReadEvent { id: 1, action: "login" }"#);
+
+    let full = FullRow { id: 1, trace_id: "t".to_string(), cached: 3 };
+    assert_eq!(full.cached, 3);
+}
@@ -0,0 +1,20 @@
+#![deny(deprecated)]
+
+use context_variants::variants;
+
+#[variants(
+    allow_option_required = true,
+    Read: requires(id).default(required),
+    Strict: requires(id, nickname as String),
+    suffix = "View"
+)]
+struct Profile {
+    pub id: u64,
+    pub nickname: Option<String>,
+}
+
+fn main() {
+    let read = ReadView { id: 1, nickname: None };
+    let strict = StrictView { id: read.id, nickname: "annie".to_string() };
+    assert_eq!(strict.nickname, "annie");
+}
@@ -0,0 +1,65 @@
+use context_variants::variants;
+use serde::Serialize;
+
+// Object-safe metadata trait, declared once in the application
+pub trait AnyVariant {
+    fn variant_name(&self) -> &'static str;
+    fn context(&self) -> &'static str;
+    fn required_fields(&self) -> &'static [&'static str];
+    fn to_json(&self) -> serde_json::Result<serde_json::Value>;
+}
+
+#[variants(
+    any_variant = AnyVariant,
+    Create: requires(name, email).excludes(id),
+    Update: requires(id).optional(name, email),
+    suffix = "User"
+)]
+#[derive(Debug, Serialize)]
+struct User {
+    pub id: u64,
+    pub name: String,
+    pub email: String,
+}
+
+// Generic structs get the impl when their parameters are serializable
+#[variants(
+    any_variant = crate::AnyVariant,
+    Read: requires(id, payload),
+    suffix = "Envelope"
+)]
+#[derive(Serialize)]
+struct Envelope<T> {
+    pub id: u64,
+    pub payload: T,
+}
+
+// Raw field names are listed without r#
+#[variants(
+    any_variant = AnyVariant,
+    Create: requires(r#type),
+    suffix = "Rule"
+)]
+#[derive(Serialize)]
+struct Rule {
+    pub r#type: String,
+}
+
+fn main() {
+    let items: Vec<Box<dyn AnyVariant>> = vec![
+        Box::new(CreateUser { name: "Ann".to_string(), email: "ann@example.com".to_string() }),
+        Box::new(UpdateUser { id: 7, name: None, email: Some("new@example.com".to_string()) }),
+        Box::new(ReadEnvelope { id: 1, payload: vec![1, 2, 3] }),
+    ];
+
+    assert_eq!(items[0].variant_name(), "CreateUser");
+    assert_eq!(items[0].context(), "Create");
+    assert_eq!(items[0].required_fields(), &["name", "email"]);
+    assert_eq!(items[1].required_fields(), &["id"]);
+    assert_eq!(items[1].to_json().unwrap(), serde_json::json!({ "id": 7, "name": null, "email": "new@example.com" }));
+    assert_eq!(items[2].context(), "Read");
+    assert_eq!(items[2].to_json().unwrap()["payload"], serde_json::json!([1, 2, 3]));
+
+    let rule: Box<dyn AnyVariant> = Box::new(CreateRule { r#type: "allow".to_string() });
+    assert_eq!(rule.required_fields(), &["type"]);
+}
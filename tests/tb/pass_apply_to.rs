@@ -0,0 +1,38 @@
+use context_variants::variants;
+
+#[variants(
+    Update: requires(id).optional(name, email, nickname, age as u8),
+    Rename: requires(name).excludes(id, email, nickname, age),
+    apply_to = true,
+    suffix = "Form"
+)]
+#[derive(Debug, Clone, PartialEq)]
+struct User {
+    pub id: u64,
+    pub name: String,
+    pub email: String,
+    pub nickname: Option<String>,
+    pub age: u32,
+}
+
+fn main() {
+    let mut user = User { id: 1, name: "Ada".to_string(), email: "ada@example.com".to_string(), nickname: None, age: 36 };
+
+    let patch = UpdateForm { id: 1, name: None, email: Some("ada@lovelace.dev".to_string()), nickname: Some("countess".to_string()), age: Some(37) };
+    patch.apply_to(&mut user);
+    assert_eq!(user, User {
+        id: 1,
+        name: "Ada".to_string(),
+        email: "ada@lovelace.dev".to_string(),
+        nickname: Some("countess".to_string()),
+        age: 37,
+    });
+
+    // A patch leaving a field out can't clear it
+    UpdateForm { id: 1, name: None, email: None, nickname: None, age: None }.apply_to(&mut user);
+    assert_eq!(user.nickname.as_deref(), Some("countess"));
+
+    // Required fields always overwrite
+    RenameForm { name: "Augusta".to_string() }.apply_to(&mut user);
+    assert_eq!(user.name, "Augusta");
+}
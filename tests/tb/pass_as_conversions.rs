@@ -0,0 +1,39 @@
+use context_variants::variants;
+use std::convert::TryFrom;
+
+#[variants(
+    Read: requires(id as String, name, score as u8, tags),
+    Patch: requires(id).optional(name, score as u64, tags),
+    as_conversions = true,
+    suffix = "View"
+)]
+#[derive(Debug, Clone, PartialEq)]
+struct Player {
+    pub id: u64,
+    pub name: String,
+    pub score: u32,
+    pub tags: Option<Vec<String>>,
+}
+
+fn main() {
+    let player = Player { id: 42, name: "ann".to_string(), score: 200, tags: None };
+
+    // u64 -> String goes through ToString, u32 -> u8 through TryFrom
+    let view = ReadView::try_from(player.clone()).unwrap();
+    assert_eq!(view.id, "42");
+    assert_eq!(view.score, 200u8);
+
+    // String -> u64 goes back through str::parse
+    assert_eq!(Player::try_from(view).unwrap(), player);
+
+    let too_high = Player { score: 300, ..player.clone() };
+    let err = ReadView::try_from(too_high).unwrap_err();
+    assert_eq!(err.context, "Read");
+    assert_eq!(err.field, "score");
+
+    let bad_id = ReadView { id: "forty-two".to_string(), name: "ann".to_string(), score: 1, tags: None };
+    assert_eq!(Player::try_from(bad_id).unwrap_err().field, "id");
+
+    // Patch has optional fields the base requires, so it has no conversions
+    let _ = PatchView { id: 1, name: None, score: Some(1), tags: None };
+}
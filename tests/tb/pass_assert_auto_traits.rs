@@ -0,0 +1,48 @@
+use context_variants::variants;
+use std::sync::Arc;
+
+// Test Send + Sync assertions on every generated struct
+#[variants(
+    Create: requires(name).excludes(id),
+    Read: requires(id, name),
+    assert_auto_traits = true,
+    suffix = "Form"
+)]
+#[derive(Debug, Clone)]
+struct User {
+    pub id: u64,
+    pub name: Arc<String>,
+}
+
+// Test generic structs: parameters are assumed Send + Sync
+#[variants(
+    Read: requires(id, data),
+    assert_auto_traits = true,
+    suffix = "Envelope"
+)]
+#[derive(Debug)]
+struct Wrapper<T: Clone> {
+    pub id: u64,
+    pub data: Vec<T>,
+}
+
+// Test assertions limited to one context: Draft holds an Rc and is not checked
+#[variants(
+    Create: requires(title).excludes(scratch),
+    Draft: requires(title, scratch),
+    assert_auto_traits = { Create: true },
+    suffix = "Post"
+)]
+#[derive(Debug)]
+struct Post {
+    pub title: String,
+    pub scratch: std::rc::Rc<String>,
+}
+
+fn main() {
+    let _create = CreateForm {
+        name: Arc::new("alice".to_string()),
+    };
+    let _read = ReadEnvelope::<u8> { id: 1, data: vec![1] };
+    let _post = CreatePost { title: "Hello".to_string() };
+}
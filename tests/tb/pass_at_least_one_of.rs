@@ -0,0 +1,27 @@
+use context_variants::variants;
+
+#[variants(
+    groups = contact(email, phone),
+    Create: requires(name, email).excludes(id, phone, metadata),
+    Update: requires(id).optional(name, email, phone, metadata).at_least_one_of(name, contact, metadata),
+    suffix = "Customer"
+)]
+#[derive(Debug)]
+struct Customer {
+    pub id: u64,
+    pub name: String,
+    pub email: String,
+    pub phone: Option<String>,
+    pub metadata: Option<String>,
+}
+
+fn main() {
+    let empty = UpdateCustomer { id: 1, name: None, email: None, phone: None, metadata: None };
+    assert_eq!(
+        empty.validate(),
+        Err("at least one of `name`, `email`, `phone`, `metadata` must be set".to_string())
+    );
+
+    let patch = UpdateCustomer { id: 1, name: None, email: None, phone: Some("555".to_string()), metadata: None };
+    assert_eq!(patch.validate(), Ok(()));
+}
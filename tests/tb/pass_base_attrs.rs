@@ -0,0 +1,23 @@
+use context_variants::variants;
+use serde::{Deserialize, Serialize};
+
+// Attributes added to the base struct only; variants don't see them
+#[variants(
+    Create: requires(name).default(exclude),
+    base_attrs = [derive(Deserialize), serde(deny_unknown_fields)],
+    suffix = "Form"
+)]
+#[derive(Debug, Serialize)]
+struct User {
+    pub id: u64,
+    pub name: String,
+}
+
+fn main() {
+    let user: User = serde_json::from_str(r#"{"id": 1, "name": "ann"}"#).unwrap();
+    assert_eq!(user.name, "ann");
+    assert!(serde_json::from_str::<User>(r#"{"id": 1, "name": "ann", "extra": 0}"#).is_err());
+
+    let form = CreateForm { name: "ann".to_string() };
+    assert_eq!(serde_json::to_string(&form).unwrap(), r#"{"name":"ann"}"#);
+}
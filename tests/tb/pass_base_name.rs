@@ -0,0 +1,23 @@
+use context_variants::variants;
+
+#[variants(
+    Create: requires(name).excludes(id),
+    Read: requires(id, name),
+    base_name = "UserRecord",
+    from_ref = { Read: true },
+    conversions = { Read: true },
+    suffix = "Form"
+)]
+#[derive(Debug, Clone, PartialEq)]
+struct User {
+    pub id: u64,
+    pub name: String,
+}
+
+fn main() {
+    // The base is emitted as UserRecord; variant names are unchanged
+    let record = UserRecord { id: 1, name: "ann".to_string() };
+    let read = ReadForm::from(&record);
+    assert_eq!(UserRecord::from(read), record);
+    let _create = CreateForm { name: "bob".to_string() };
+}
@@ -0,0 +1,35 @@
+use context_variants::variants;
+
+// The id and discriminator stay required in an otherwise optional base
+#[variants(
+    Create: requires(kind, name).excludes(id),
+    optional_base = true,
+    suffix = "Shape"
+)]
+struct Shape {
+    #[ctx_base_required]
+    pub id: u64,
+    #[ctx_base_required]
+    pub kind: String,
+    pub name: String,
+}
+
+// Without optional_base, single fields can still be optional in the base
+#[variants(
+    Create: requires(name, email).default(exclude),
+    suffix = "Form"
+)]
+struct User {
+    pub name: String,
+    #[ctx_base_optional]
+    pub email: String,
+}
+
+fn main() {
+    let _shape = Shape { id: 1, kind: "circle".to_string(), name: None };
+    let _create = CreateShape { kind: "circle".to_string(), name: "c".to_string() };
+
+    let _user = User { name: "ann".to_string(), email: None };
+    // Variants keep the declared type
+    let _form = CreateForm { name: "ann".to_string(), email: "ann@example.com".to_string() };
+}
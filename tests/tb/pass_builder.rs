@@ -0,0 +1,38 @@
+use context_variants::variants;
+
+#[variants(
+    Create: requires(name, email).optional(nickname).excludes(id),
+    Update: requires(id).optional(name, email, nickname),
+    builder = { Create: true },
+    suffix = "Form"
+)]
+#[derive(Debug, Clone, PartialEq)]
+struct User<T> {
+    pub id: u64,
+    pub name: String,
+    pub email: String,
+    pub nickname: Option<T>,
+}
+
+fn main() {
+    let create = CreateForm::<String>::builder()
+        .name("Ada".to_string())
+        .email("ada@example.com".to_string())
+        .nickname("countess".to_string())
+        .build()
+        .unwrap();
+    assert_eq!(create, CreateForm {
+        name: "Ada".to_string(),
+        email: "ada@example.com".to_string(),
+        nickname: Some("countess".to_string()),
+    });
+
+    // Optional fields can be left unset
+    let create = CreateFormBuilder::<String>::default().email("ada@example.com".to_string()).name("Ada".to_string()).build().unwrap();
+    assert_eq!(create.nickname, None);
+
+    // Every unset required field is reported
+    let error = CreateForm::<String>::builder().build().unwrap_err();
+    assert_eq!(error, UserMissingFields { variant: "CreateForm", fields: vec!["name", "email"] });
+    assert_eq!(error.to_string(), "CreateForm: missing required fields `name`, `email`");
+}
@@ -0,0 +1,30 @@
+use context_variants::variants;
+use serde::Serialize;
+use std::collections::HashMap;
+
+#[variants(
+    canonical_json = { Create: true },
+    Create: requires(name, tags, amount).excludes(id),
+    Read: requires(id, name, tags, amount),
+    suffix = "Payment"
+)]
+#[derive(Serialize)]
+struct Payment {
+    pub id: u64,
+    pub name: String,
+    pub tags: HashMap<String, u32>,
+    pub amount: f64,
+}
+
+fn main() {
+    let mut tags = HashMap::new();
+    tags.insert("zeta".to_string(), 1);
+    tags.insert("alpha".to_string(), 2);
+    let create = CreatePayment { name: "rent".to_string(), tags, amount: 12.5 };
+    assert_eq!(
+        String::from_utf8(create.canonical_bytes()).unwrap(),
+        r#"{"amount":12.5,"name":"rent","tags":{"alpha":2,"zeta":1}}"#
+    );
+
+    let _read = ReadPayment { id: 1, name: "rent".to_string(), tags: HashMap::new(), amount: 0.0 };
+}
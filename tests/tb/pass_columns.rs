@@ -0,0 +1,32 @@
+use context_variants::variants;
+
+#[variants(
+    columns = { Read: true },
+    Read: requires(id).optional(name),
+    Create: requires(name).excludes(id),
+    suffix = "Model"
+)]
+struct Event {
+    pub id: u64,
+    pub name: String,
+}
+
+fn main() {
+    let mut columns = ReadModelColumns::new();
+    assert!(columns.is_empty());
+    columns.push(ReadModel { id: 1, name: Some("open".to_string()) });
+    columns.extend(vec![ReadModel { id: 2, name: None }]);
+    assert_eq!(columns.len(), 2);
+    assert_eq!(columns.id, vec![1, 2]);
+    assert_eq!(columns.name, vec![Some("open".to_string()), None]);
+    assert_eq!(columns.id.iter().sum::<u64>(), 3);
+
+    let rows: Vec<ReadModel> = columns.into_rows().collect();
+    assert_eq!(rows[1].id, 2);
+    assert!(rows[1].name.is_none());
+
+    let collected: ReadModelColumns = rows.into_iter().collect();
+    assert_eq!(collected.len(), 2);
+
+    let _create = CreateModel { name: "open".to_string() };
+}
@@ -0,0 +1,38 @@
+use context_variants::variants;
+use std::collections::HashMap;
+
+// Types inside `as` use the full type grammar: nested generics, function pointers,
+// trait objects, references, tuples and turbofish paths
+#[variants(
+    Read: requires(
+        map as HashMap<String, Vec<Option<T>>>,
+        callback as fn(u8) -> u8,
+        handler as Box<dyn Fn(u8) -> u8 + Send>,
+        label as &'static str,
+        pair as (u8, Vec<T>),
+        bytes as Vec::<[u8; 4]>,
+        source as Option<Box<dyn std::error::Error + Send + Sync>>,
+    ),
+    suffix = "View"
+)]
+struct Widget<T> {
+    pub map: Vec<T>,
+    pub callback: u8,
+    pub handler: u8,
+    pub label: String,
+    pub pair: u8,
+    pub bytes: u8,
+    pub source: u8,
+}
+
+fn main() {
+    let _view: ReadView<u32> = ReadView {
+        map: HashMap::new(),
+        callback: |x| x,
+        handler: Box::new(|x| x),
+        label: "x",
+        pair: (1, vec![2u32]),
+        bytes: vec![[0; 4]],
+        source: None,
+    };
+}
@@ -0,0 +1,35 @@
+use context_variants::variants;
+
+#[variants(
+    Create: requires(first, last).excludes(id),
+    Read: requires(id, first, last)
+        .computed(display_name: String = format!("{} {}", self.first, self.last))
+        .computed(initials: String = "format!(\"{}{}\", &self.first[..1], &self.last[..1])"),
+    from_ref = { Read: true },
+    inherit_defaults = true,
+    suffix = "Form"
+)]
+#[derive(Debug, Clone)]
+struct Person {
+    pub id: u64,
+    pub first: String,
+    pub last: String,
+}
+
+impl Default for Person {
+    fn default() -> Self {
+        Person { id: 0, first: "Jane".to_string(), last: "Doe".to_string() }
+    }
+}
+
+fn main() {
+    let base = Person { id: 1, first: "Ada".to_string(), last: "Lovelace".to_string() };
+    let read = ReadForm::from(&base);
+    assert_eq!(read.display_name, "Ada Lovelace");
+    assert_eq!(read.initials, "AL");
+
+    // Defaults are computed from the base's defaults
+    assert_eq!(ReadForm::default().display_name, "Jane Doe");
+
+    let _create = CreateForm { first: "Ada".to_string(), last: "Lovelace".to_string() };
+}
@@ -0,0 +1,30 @@
+use context_variants::variants;
+use serde::Serialize;
+
+// The structured form groups the flat parameters; both forms can be mixed
+#[variants(
+    Create: requires(name).optional(email).default(exclude),
+    Update: requires(id).default(optional),
+    config(
+        naming(prefix = "Api", suffix = "Dto"),
+        base(optional = true, attrs = [derive(Clone)]),
+        fields(optional_attrs = [serde(skip_serializing_if = "Option::is_none")]),
+        codegen(presence, dedup_variants = false),
+    ),
+    report_stats = false
+)]
+#[derive(Debug, Serialize)]
+struct User {
+    pub id: u64,
+    pub name: String,
+    pub email: String,
+}
+
+fn main() {
+    let user = User { id: Some(1), name: None, email: None };
+    let _copy = user.clone();
+
+    let create = ApiCreateDto { name: "ann".to_string(), email: None };
+    assert_eq!(serde_json::to_string(&create).unwrap(), r#"{"name":"ann"}"#);
+    let _update = ApiUpdateDto { id: 1, name: None, email: None };
+}
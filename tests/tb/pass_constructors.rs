@@ -0,0 +1,34 @@
+use context_variants::variants;
+
+#[variants(
+    Create: requires(name, email).optional(nickname).excludes(id).verbatim_field(pub captcha: String),
+    Update: requires(id).optional(name, email, nickname),
+    constructors = true,
+    suffix = "Form"
+)]
+#[derive(Debug, Clone, PartialEq)]
+struct User {
+    pub id: u64,
+    pub name: String,
+    pub email: String,
+    pub nickname: Option<String>,
+}
+
+fn main() {
+    let create = CreateForm::new("Ada".to_string(), "ada@example.com".to_string(), "token".to_string());
+    assert_eq!(create, CreateForm {
+        name: "Ada".to_string(),
+        email: "ada@example.com".to_string(),
+        nickname: None,
+        captcha: "token".to_string(),
+    });
+
+    let update = UpdateForm::new(7);
+    assert_eq!(update, UpdateForm { id: 7, name: None, email: None, nickname: None });
+
+    // Optional fields have chainable setters taking the inner type
+    let update = UpdateForm::new(7).with_email("ada@lovelace.dev".to_string()).with_nickname("countess".to_string());
+    assert_eq!(update.email.as_deref(), Some("ada@lovelace.dev"));
+    assert_eq!(update.nickname.as_deref(), Some("countess"));
+    assert_eq!(update.name, None);
+}
@@ -0,0 +1,69 @@
+use context_variants::variants;
+use serde::{Deserialize, Serialize};
+
+// Test defaults shared by named context sets
+#[variants(
+    context_sets = (
+        write(Create, Update),
+        read(Read, Summary)
+    ),
+    defaults = { write: exclude, read: optional },
+    Create: requires(name, email),
+    Update: requires(id).optional(name),
+    Read: requires(id),
+    Summary: requires(id, name).default(exclude), // own default wins over the set's
+    suffix = "Form"
+)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct User {
+    pub id: u64,
+    pub name: String,
+    pub email: String,
+}
+
+// Test keys naming a single context next to a global default
+#[variants(
+    defaults = { Create: exclude },
+    default = required,
+    Create: requires(title),
+    Read: optional(body),
+    suffix = "Post"
+)]
+#[derive(Debug, Clone)]
+struct Post {
+    pub id: u64,
+    pub title: String,
+    pub body: String,
+}
+
+fn main() {
+    // write set: unspecified fields are excluded
+    let _create = CreateForm {
+        name: "alice".to_string(),
+        email: "alice@example.com".to_string(),
+    };
+    let _update = UpdateForm {
+        id: 1,
+        name: None,
+    };
+
+    // read set: unspecified fields are optional
+    let _read = ReadForm {
+        id: 1,
+        name: Some("alice".to_string()),
+        email: None,
+    };
+    let _summary = SummaryForm {
+        id: 1,
+        name: "alice".to_string(),
+    };
+
+    let _create_post = CreatePost {
+        title: "Hello".to_string(),
+    };
+    let _read_post = ReadPost {
+        id: 1,
+        title: "Hello".to_string(),
+        body: None,
+    };
+}
@@ -0,0 +1,36 @@
+use context_variants::variants;
+
+pub trait ContextVariant {
+    type Base;
+    const CONTEXT: &'static str;
+}
+
+#[variants(
+    Create: requires(name).excludes(id),
+    Update: requires(id).optional(name),
+    context_variant = crate::ContextVariant,
+    suffix = "Form"
+)]
+struct User<T> {
+    pub id: u64,
+    pub name: T,
+}
+
+#[variants(Create: requires(title), context_variant = ContextVariant, suffix = "Post")]
+struct Post {
+    pub title: String,
+}
+
+// Generic code accepts any variant of User and recovers the context name
+fn context_of<V: ContextVariant<Base = User<String>>>(_: &V) -> &'static str {
+    V::CONTEXT
+}
+
+fn main() {
+    let create = CreateForm { name: "Ada".to_string() };
+    let update = UpdateForm::<String> { id: 1, name: None };
+    assert_eq!(context_of(&create), "Create");
+    assert_eq!(context_of(&update), "Update");
+    assert_eq!(<CreatePost as ContextVariant>::CONTEXT, "Create");
+    let _: Option<<CreatePost as ContextVariant>::Base> = None::<Post>;
+}
@@ -0,0 +1,44 @@
+use context_variants::variants;
+
+pub struct Cents(pub u64);
+
+impl From<Cents> for u64 {
+    fn from(value: Cents) -> u64 {
+        value.0
+    }
+}
+
+#[variants(
+    Create: requires(name, price).excludes(id, note),
+    Read: requires(id, name, price).optional(note),
+    Update: requires(id).optional(name, price, note),
+    conversions = true,
+    suffix = "Form"
+)]
+#[derive(Debug, Clone, PartialEq)]
+struct Product {
+    pub id: u64,
+    pub name: String,
+    pub price: u64,
+    pub note: Option<String>,
+}
+
+// Named contexts convert changed types with Into
+#[variants(Import: requires(id, price as Cents), conversions = { Import: true })]
+struct Listing {
+    pub id: u64,
+    pub price: u64,
+}
+
+fn main() {
+    let read = ReadForm { id: 1, name: "Tea".to_string(), price: 300, note: None };
+    let product = Product::from(read);
+    assert_eq!(product, Product { id: 1, name: "Tea".to_string(), price: 300, note: None });
+
+    // Create lacks `id`, which isn't Option in the base, and Update's fields are optional,
+    // so neither gets a conversion; these would not compile:
+    // Product::from(CreateForm { .. });
+
+    let listing = Listing::from(Import { id: 2, price: Cents(150) });
+    assert_eq!(listing.price, 150);
+}
@@ -0,0 +1,24 @@
+use context_variants::variants;
+
+// With `link`, every documented field still has docs on the variants
+#[variants(
+    Create: requires(name).excludes(id),
+    Read: requires(id, name),
+    copy_field_docs = link,
+    suffix = "Form"
+)]
+#[deny(missing_docs)]
+/// A registered user
+pub struct User {
+    /// Identifier
+    ///
+    /// Assigned by the database on insert, never reused.
+    pub id: u64,
+    /// Display name
+    pub name: String,
+}
+
+fn main() {
+    let _create = CreateForm { name: "Ada".to_string() };
+    let _read = ReadForm { id: 1, name: "Ada".to_string() };
+}
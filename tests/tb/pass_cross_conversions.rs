@@ -0,0 +1,33 @@
+use context_variants::variants;
+
+#[variants(
+    Read: requires(id, name, email).optional(nickname),
+    Update: requires(id).optional(name, email, nickname),
+    Create: requires(name, email).excludes(id, nickname),
+    cross_conversions = true,
+    suffix = "Form"
+)]
+#[derive(Debug, Clone, PartialEq)]
+struct User {
+    pub id: u64,
+    pub name: String,
+    pub email: String,
+    pub nickname: Option<String>,
+}
+
+fn main() {
+    let read = ReadForm { id: 1, name: "Ada".to_string(), email: "ada@example.com".to_string(), nickname: None };
+
+    // Read covers Update, wrapping the required fields in Some
+    let update = UpdateForm::from(read.clone());
+    assert_eq!(update, UpdateForm { id: 1, name: Some("Ada".to_string()), email: Some("ada@example.com".to_string()), nickname: None });
+
+    // Read also covers Create, dropping id and nickname
+    let create: CreateForm = read.into();
+    assert_eq!(create, CreateForm { name: "Ada".to_string(), email: "ada@example.com".to_string() });
+
+    // Update can't fill Read's required fields and Create lacks id, so neither converts into Read;
+    // these would not compile:
+    // ReadForm::from(update);
+    // ReadForm::from(create);
+}
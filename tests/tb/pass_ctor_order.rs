@@ -0,0 +1,43 @@
+use context_variants::variants;
+
+// Optional fields that are still parameters move behind the required ones
+#[variants(
+    Create: requires(title, body).optional(tags as Vec<String>).excludes(id),
+    constructors = true,
+    builder = true,
+    ctor_order = required_first,
+    suffix = "Form"
+)]
+#[derive(Debug, Clone, PartialEq)]
+struct Post {
+    pub id: u64,
+    pub title: String,
+    pub tags: Option<Vec<String>>,
+    pub body: String,
+}
+
+// The default keeps the variant's field order
+#[variants(
+    Create: requires(title, body).optional(tags as Vec<String>).excludes(id),
+    constructors = true,
+    suffix = "Draft"
+)]
+struct Article {
+    pub id: u64,
+    pub title: String,
+    pub tags: Option<Vec<String>>,
+    pub body: String,
+}
+
+fn main() {
+    let create = CreateForm::new("Hello".to_string(), "World".to_string(), vec!["news".to_string()]);
+    assert_eq!(create.title, "Hello");
+    assert_eq!(create.body, "World");
+
+    // Builders list missing fields in the same order
+    let error = CreateForm::builder().build().unwrap_err();
+    assert_eq!(error.to_string(), "CreateForm: missing required fields `title`, `body`, `tags`");
+
+    let draft = CreateDraft::new("Hello".to_string(), vec![], "World".to_string());
+    assert!(draft.tags.is_empty());
+}
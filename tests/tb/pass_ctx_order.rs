@@ -0,0 +1,32 @@
+use context_variants::variants;
+use serde::Serialize;
+
+// Ordered fields come first in every variant, by position; the rest follow in declaration order
+#[variants(
+    Export: requires(all_fields()),
+    Summary: requires(name, id).default(exclude),
+    suffix = "Row"
+)]
+#[derive(Debug, Serialize)]
+struct User {
+    pub name: String,
+    pub email: String,
+    #[ctx_order(1)]
+    pub id: u64,
+    #[ctx_order(5)]
+    pub created_at: u64,
+}
+
+fn main() {
+    let export = ExportRow { id: 1, created_at: 0, name: "ann".to_string(), email: "a@x".to_string() };
+    assert_eq!(
+        serde_json::to_string(&export).unwrap(),
+        r#"{"id":1,"created_at":0,"name":"ann","email":"a@x"}"#
+    );
+    let summary = SummaryRow { id: 1, name: "ann".to_string() };
+    assert_eq!(format!("{:?}", summary), r#"SummaryRow { id: 1, name: "ann" }"#);
+
+    // The base struct keeps its declared layout
+    let base = User { name: "ann".to_string(), email: "a@x".to_string(), id: 1, created_at: 0 };
+    assert_eq!(serde_json::to_string(&base).unwrap(), r#"{"name":"ann","email":"a@x","id":1,"created_at":0}"#);
+}
@@ -0,0 +1,22 @@
+use context_variants::variants;
+
+#[variants(
+    Create: requires(name, email).excludes(id).name("UserCreationPayload"),
+    Update: requires(id).optional(name, email),
+    // A raw name is used as written
+    Read: requires(id, name, email).name("r#Profile"),
+    suffix = "Form"
+)]
+#[derive(Debug)]
+struct User {
+    pub id: u64,
+    pub name: String,
+    pub email: String,
+}
+
+fn main() {
+    // The named context ignores the suffix; the others keep it
+    let _create = UserCreationPayload { name: "ann".to_string(), email: "ann@example.com".to_string() };
+    let _update = UpdateForm { id: 1, name: None, email: None };
+    let _read = Profile { id: 1, name: "ann".to_string(), email: "ann@example.com".to_string() };
+}
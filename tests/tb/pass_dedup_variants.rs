@@ -0,0 +1,27 @@
+use context_variants::variants;
+
+#[variants(
+    dedup_variants = true,
+    Create: requires(name).excludes(id),
+    Patch: requires(id).optional(name),
+    Update: requires(id).optional(name),
+    suffix = "Form"
+)]
+#[derive(Debug, Clone, PartialEq)]
+struct Item {
+    pub id: u64,
+    pub name: String,
+}
+
+fn takes_patch(form: PatchForm) -> u64 {
+    form.id
+}
+
+fn main() {
+    // UpdateForm is an alias of the identical PatchForm
+    let update: UpdateForm = PatchForm { id: 7, name: None };
+    assert_eq!(takes_patch(update.clone()), 7);
+    assert_eq!(update, PatchForm { id: 7, name: None });
+
+    let _create = CreateForm { name: "pen".to_string() };
+}
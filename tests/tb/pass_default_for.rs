@@ -0,0 +1,26 @@
+use context_variants::variants;
+
+#[variants(
+    // Timestamps are left out, ids are required, everything else is optional
+    Update: default_for(matching("*_at"), exclude).default_for(matching("*_id"), id, required).default(optional),
+    // Explicit selectors still win over the rules
+    Audit: requires(updated_at).default_for(matching("*_at"), exclude).default(required),
+    suffix = "Form"
+)]
+#[derive(Debug, Clone, PartialEq)]
+struct Post {
+    pub id: u64,
+    pub author_id: u64,
+    pub title: String,
+    pub body: String,
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
+fn main() {
+    let update = UpdateForm { id: 1, author_id: 7, title: Some("Hello".to_string()), body: None };
+    assert_eq!(update.title.as_deref(), Some("Hello"));
+
+    let audit = AuditForm { id: 1, author_id: 7, title: "Hello".to_string(), body: String::new(), updated_at: 42 };
+    assert_eq!(audit.updated_at, 42);
+}
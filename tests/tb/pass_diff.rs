@@ -0,0 +1,38 @@
+use context_variants::variants;
+
+#[variants(
+    Update: requires(id).optional(name, email, nickname).excludes(password),
+    Create: requires(name, email, password).excludes(id, nickname),
+    diff = Update,
+    suffix = "Form"
+)]
+#[derive(Debug, Clone, PartialEq)]
+struct User {
+    pub id: u64,
+    pub name: String,
+    pub email: String,
+    pub nickname: Option<String>,
+    pub password: String,
+}
+
+fn main() {
+    let before = User {
+        id: 1,
+        name: "Ada".to_string(),
+        email: "ada@example.com".to_string(),
+        nickname: None,
+        password: "hunter2".to_string(),
+    };
+    let after = User { email: "ada@lovelace.dev".to_string(), nickname: Some("countess".to_string()), ..before.clone() };
+
+    let patch: UpdateForm = before.diff(&after);
+    assert_eq!(patch, UpdateForm {
+        id: 1,
+        name: None,
+        email: Some("ada@lovelace.dev".to_string()),
+        nickname: Some("countess".to_string()),
+    });
+
+    // No changes, no fields besides the required id
+    assert_eq!(after.diff(&after), UpdateForm { id: 1, name: None, email: None, nickname: None });
+}
@@ -0,0 +1,29 @@
+#![deny(invalid_doc_attributes)]
+
+use context_variants::variants;
+
+// Variants get `#[doc(alias = "Create")]` and `#[doc(alias = "UserCreate")]`,
+// minus any alias equal to the generated name itself
+#[variants(
+    Create: requires(name).excludes(id),
+    Read: requires(id, name),
+    prefix = "User"
+)]
+/// A user account
+struct User {
+    pub id: u64,
+    pub name: String,
+}
+
+// Without docs of its own, the base struct only lists its variants
+#[variants(Create: requires(title), suffix = "Note")]
+struct Note {
+    pub title: String,
+}
+
+fn main() {
+    let _create = UserCreate { name: "ann".to_string() };
+    let _read = UserRead { id: 1, name: "ann".to_string() };
+    let _note = CreateNote { title: "todo".to_string() };
+    let _base = Note { title: "todo".to_string() };
+}
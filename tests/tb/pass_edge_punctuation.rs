@@ -0,0 +1,38 @@
+use context_variants::variants;
+use std::collections::HashMap;
+
+// Trailing commas and comments are accepted everywhere
+#[variants(
+    groups = (contact(email, phone,)), // a single parenthesized group
+    context_sets = (write(Create, Update,),),
+    defaults = { write: exclude, },
+    Create: requires(name, contact,).optional(tags as HashMap<String, Vec<u8>>,),
+    /* block comments too */
+    Update: requires(id,).optional(name, email, phone, tags,),
+    Read: requires(all_fields().except(phone,),).optional(phone,),
+    suffix = "Contact",
+)]
+struct Contact {
+    pub id: u64,
+    pub name: String,
+    pub email: String,
+    pub phone: String,
+    pub tags: Vec<String>,
+}
+
+fn main() {
+    let _create = CreateContact {
+        name: "Ann".to_string(),
+        email: "ann@example.com".to_string(),
+        phone: "555".to_string(),
+        tags: Some(HashMap::new()),
+    };
+    let _update = UpdateContact { id: 1, name: None, email: None, phone: None, tags: None };
+    let _read = ReadContact {
+        id: 1,
+        name: "Ann".to_string(),
+        email: "ann@example.com".to_string(),
+        phone: None,
+        tags: Vec::new(),
+    };
+}
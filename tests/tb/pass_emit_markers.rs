@@ -0,0 +1,35 @@
+use context_variants::variants;
+
+// Inert markers are stripped by cfg evaluation, so the output compiles unchanged
+#[variants(
+    emit_markers = true,
+    Create: requires(name).excludes(id),
+    Update: requires(id).optional(name),
+    suffix = "Form"
+)]
+#[derive(Debug, Clone)]
+struct Item {
+    pub id: u64,
+    pub name: String,
+}
+
+// Doc markers reach derive macros and rustdoc
+#[variants(
+    emit_markers = doc,
+    Create: requires(name).excludes(id),
+    Update: requires(id).optional(name),
+    suffix = "Payload"
+)]
+#[derive(Debug, Clone)]
+struct Note {
+    /// Primary key
+    pub id: u64,
+    pub name: String,
+}
+
+fn main() {
+    let _create = CreateForm { name: "pen".to_string() };
+    let _update = UpdateForm { id: 1, name: None };
+    let _payload = UpdatePayload { id: 1, name: Some("todo".to_string()) };
+    let _note = Note { id: 1, name: "todo".to_string() };
+}
@@ -0,0 +1,40 @@
+use context_variants::variants;
+
+mod crypto {
+    use std::marker::PhantomData;
+
+    pub struct Encrypted<T> {
+        pub ciphertext: Vec<u8>,
+        _plaintext: PhantomData<T>,
+    }
+
+    pub fn encrypt(value: &str) -> Encrypted<String> {
+        Encrypted { ciphertext: value.bytes().rev().collect(), _plaintext: PhantomData }
+    }
+}
+
+#[variants(
+    Create: requires(email).optional(phone).excludes(id),
+    Stored: requires(id, email).optional(phone),
+    required_wrapper = Box,
+    suffix = "Contact"
+)]
+struct Contact {
+    pub id: u64,
+    #[ctx_encrypt(Stored, with = "crate::crypto")]
+    pub email: String,
+    #[ctx_encrypt(with = "crate::crypto")]
+    pub phone: Option<String>,
+}
+
+fn main() {
+    // Plaintext in the write context, except where every variant is encrypted
+    let create = CreateContact { email: Box::new("a@example.com".to_string()), phone: Some(crypto::encrypt("555")) };
+    assert_eq!(create.email.len(), 13);
+
+    // Ciphertext at rest, without the required wrapper
+    let stored = StoredContact { id: Box::new(1), email: crypto::encrypt("a@example.com"), phone: None };
+    assert_eq!(stored.email.ciphertext.len(), 13);
+
+    let _base = Contact { id: 1, email: "a@example.com".to_string(), phone: None };
+}
@@ -0,0 +1,27 @@
+use context_variants::variants;
+use serde::{Deserialize, Serialize};
+
+#[variants(
+    Create: requires(name, email).excludes(id),
+    Update: requires(id).optional(name, email),
+    enum_wrapper = "UserPayload",
+    suffix = "Form"
+)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct User {
+    pub id: u64,
+    pub name: String,
+    pub email: String,
+}
+
+fn main() {
+    let payload: UserPayload = serde_json::from_str(r#"{"context":"Create","name":"ann","email":"ann@example.com"}"#).unwrap();
+    match &payload {
+        UserPayload::Create(form) => assert_eq!(form.name, "ann"),
+        UserPayload::Update(_) => panic!("expected the Create context"),
+    }
+
+    let update = UserPayload::Update(UpdateForm { id: 7, name: None, email: Some("b@example.com".to_string()) });
+    assert_eq!(serde_json::to_string(&update).unwrap(), r#"{"context":"Update","id":7,"name":null,"email":"b@example.com"}"#);
+    assert_eq!(update.clone(), update);
+}
@@ -0,0 +1,35 @@
+use context_variants::variants;
+
+pub struct ApiResponse<T> {
+    pub data: T,
+    pub request_id: String,
+}
+
+mod envelopes {
+    pub struct Paged<T>(pub Vec<T>);
+}
+
+#[variants(
+    Create: requires(name).excludes(id).envelope(ApiResponse, Read),
+    Read: requires(id, name).envelope(envelopes::Paged),
+    suffix = "Form"
+)]
+struct User {
+    pub id: u64,
+    pub name: String,
+}
+
+fn main() {
+    // Create responds with the Read variant
+    let created: CreateResponse = ApiResponse {
+        data: ReadForm { id: 1, name: "Ada".to_string() },
+        request_id: "req-1".to_string(),
+    };
+    assert_eq!(created.data.id, 1);
+    assert_eq!(created.request_id, "req-1");
+
+    let read: ReadResponse = envelopes::Paged(vec![ReadForm { id: 1, name: "Ada".to_string() }]);
+    assert_eq!(read.0.len(), 1);
+
+    let _create = CreateForm { name: "Ada".to_string() };
+}
@@ -0,0 +1,35 @@
+use context_variants::variants;
+
+mod messages {
+    pub fn at_least_one_of(fields: &[&str], context: &str) -> String {
+        format!("{}: au moins un des champs {} est requis", context, fields.join(", "))
+    }
+
+    pub fn one_of(fields: &[&str], context: &str) -> String {
+        format!("{}: exactement un des champs {} est requis", context, fields.join(", "))
+    }
+}
+
+#[variants(
+    error_messages = crate::messages,
+    Update: requires(id).optional(email, phone, card, iban).at_least_one_of(email, phone).one_of(card, iban),
+    suffix = "Request"
+)]
+struct Customer {
+    pub id: u64,
+    pub email: String,
+    pub phone: String,
+    pub card: String,
+    pub iban: String,
+}
+
+fn main() {
+    let update = UpdateRequest { id: 1, email: None, phone: None, card: None, iban: None };
+    assert_eq!(update.validate(), Err("Update: au moins un des champs email, phone est requis".to_string()));
+
+    let update = UpdateRequest { email: Some("a@example.com".to_string()), ..update };
+    assert_eq!(update.validate(), Err("Update: exactement un des champs card, iban est requis".to_string()));
+
+    let update = UpdateRequest { iban: Some("DE00".to_string()), ..update };
+    assert_eq!(update.validate(), Ok(()));
+}
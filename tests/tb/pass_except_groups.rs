@@ -0,0 +1,37 @@
+use context_variants::variants;
+
+// except_groups subtracts whole groups from all_fields()
+#[variants(
+    groups = (pii(name, email, phone), audit(created_by, updated_by)),
+    Public: requires(all_fields().except_groups(pii, audit)).default(exclude),
+    Export: optional(all_fields().except(id).except_groups(audit)).requires(id).default(exclude),
+    Internal: requires(all_fields()),
+    suffix = "View"
+)]
+#[derive(Debug)]
+struct Customer {
+    pub id: u64,
+    pub tier: String,
+    pub name: String,
+    pub email: String,
+    pub phone: String,
+    pub created_by: String,
+    pub updated_by: String,
+}
+
+fn main() {
+    let public = PublicView { id: 1, tier: "gold".to_string() };
+    assert_eq!(format!("{:?}", public), r#"PublicView { id: 1, tier: "gold" }"#);
+
+    let _export = ExportView { id: 1, tier: None, name: Some("Ada".to_string()), email: None, phone: None };
+
+    let _internal = InternalView {
+        id: 1,
+        tier: "gold".to_string(),
+        name: "Ada".to_string(),
+        email: "ada@example.com".to_string(),
+        phone: "555".to_string(),
+        created_by: "admin".to_string(),
+        updated_by: "admin".to_string(),
+    };
+}
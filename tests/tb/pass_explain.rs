@@ -0,0 +1,20 @@
+use context_variants::variants;
+
+// The base struct's docs end with a table of each field's role per context
+#[variants(
+    Create: requires(name, age as u8).excludes(id),
+    Update: requires(id).optional(name, age),
+    explain = true,
+    suffix = "Form"
+)]
+/// A user account
+struct User {
+    pub id: u64,
+    pub name: String,
+    pub age: u32,
+}
+
+fn main() {
+    let _user = User { id: 1, name: "ann".to_string(), age: 30 };
+    let _create = CreateForm { name: "ann".to_string(), age: 30 };
+}
@@ -0,0 +1,26 @@
+use context_variants::variants;
+
+#[variants(
+    Create: requires(name, email).optional(bio).excludes(id, created_at),
+    Update: requires(id).default(optional),
+    field_consts = true,
+    suffix = "Profile"
+)]
+#[derive(Debug)]
+struct User {
+    pub id: u64,
+    pub created_at: u64,
+    pub name: String,
+    pub email: String,
+    pub bio: Option<String>,
+}
+
+fn main() {
+    assert_eq!(CreateProfile::REQUIRED_FIELDS, &["name", "email"]);
+    assert_eq!(CreateProfile::OPTIONAL_FIELDS, &["bio"]);
+    assert_eq!(CreateProfile::EXCLUDED_FIELDS, &["id", "created_at"]);
+
+    assert_eq!(UpdateProfile::REQUIRED_FIELDS, &["id"]);
+    assert_eq!(UpdateProfile::OPTIONAL_FIELDS, &["created_at", "name", "email", "bio"]);
+    assert!(UpdateProfile::EXCLUDED_FIELDS.is_empty());
+}
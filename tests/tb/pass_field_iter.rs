@@ -0,0 +1,58 @@
+use context_variants::variants;
+use serde::Serialize;
+use serde_json::json;
+
+#[variants(
+    field_iter = { Create: true, Update: true },
+    Create: requires(name, password).excludes(id),
+    Update: requires(id).optional(name).excludes(password),
+    suffix = "Form"
+)]
+#[derive(Serialize)]
+struct User {
+    pub id: u64,
+    /// Display name
+    #[serde(rename = "username")]
+    pub name: String,
+    #[ctx_secret]
+    pub password: String,
+}
+
+// Raw field names are yielded without r#
+#[variants(field_iter = true, Create: requires(r#type))]
+#[derive(Serialize)]
+struct Rule {
+    pub r#type: String,
+}
+
+// Generic renderer over any variant
+fn labels<'a, T>(form: &'a T) -> Vec<String>
+where
+    &'a T: IntoIterator<Item = UserFieldEntry>,
+{
+    form.into_iter()
+        .map(|entry| format!("{}{}", entry.serde_name, if entry.required { "*" } else { "" }))
+        .collect()
+}
+
+fn main() {
+    let update = UpdateForm { id: 7, name: None };
+    let entries: Vec<UserFieldEntry> = (&update).into_iter().collect();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].name, "id");
+    assert_eq!(entries[0].value, Some(json!(7)));
+    assert_eq!(entries[1].serde_name, "username");
+    assert_eq!(entries[1].doc, "Display name");
+    assert!(!entries[1].required);
+    assert_eq!(entries[1].value, None);
+
+    assert_eq!(labels(&update), ["id*", "username"]);
+
+    // Secret fields are never yielded
+    let create = CreateForm { name: "ann".to_string(), password: "hunter2".to_string() };
+    assert_eq!(labels(&create), ["username*"]);
+
+    let rule = Create { r#type: "allow".to_string() };
+    let entry = (&rule).into_iter().next().unwrap();
+    assert_eq!((entry.name, entry.serde_name), ("type", "type"));
+}
@@ -0,0 +1,43 @@
+use context_variants::variants;
+use serde::Serialize;
+
+// Contexts written in terms of an external API's wire names
+#[variants(
+    Create: requires(serde("userName"), serde("e-mail") as String).optional(serde("displayName")).excludes(id),
+    field_match = case_insensitive,
+    Update: requires(ID).optional(USERNAME, Email, displayname),
+    suffix = "Form"
+)]
+#[derive(Serialize)]
+struct Account {
+    pub id: u64,
+    #[serde(rename = "userName")]
+    pub username: String,
+    #[serde(rename = "e-mail")]
+    pub email: Option<String>,
+    #[serde(rename = "displayName")]
+    pub displayname: String,
+}
+
+#[variants(
+    // Serde names also match ignoring case
+    Create: requires(serde("USERNAME")).excludes(id),
+    field_match = case_insensitive,
+    suffix = "Member"
+)]
+#[derive(Serialize)]
+struct Member {
+    pub id: u64,
+    #[serde(rename = "userName")]
+    pub username: String,
+}
+
+fn main() {
+    let _create = CreateForm {
+        username: "ann".to_string(),
+        email: "ann@example.com".to_string(),
+        displayname: None,
+    };
+    let _update = UpdateForm { id: 1, username: None, email: None, displayname: None };
+    let _member = CreateMember { username: "ann".to_string() };
+}
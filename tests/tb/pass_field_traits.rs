@@ -0,0 +1,38 @@
+use context_variants::variants;
+use std::sync::Arc;
+
+#[variants(
+    Create: requires(name, email, extra).excludes(id),
+    Update: requires(id, extra).optional(name, email),
+    Read: requires(id, name, email, extra),
+    field_traits = true,
+    suffix = "User"
+)]
+#[derive(Debug)]
+struct Account<T> {
+    pub id: u64,
+    pub name: String,
+    #[ctx_shared(Read)]
+    pub email: String,
+    // Fields whose type names the struct's generics get no trait
+    pub extra: T,
+}
+
+fn describe_id(value: &impl HasId) -> String {
+    format!("#{}", value.id())
+}
+
+fn main() {
+    let update = UpdateUser::<()> { id: 7, name: None, email: None, extra: () };
+    let read = ReadUser::<()> { id: 8, name: "ann".to_string(), email: Arc::new("a@example.com".to_string()), extra: () };
+    let base = Account::<()> { id: 9, name: "bob".to_string(), email: "b@example.com".to_string(), extra: () };
+    assert_eq!(describe_id(&update), "#7");
+    assert_eq!(describe_id(&read), "#8");
+    assert_eq!(describe_id(&base), "#9");
+
+    let create = CreateUser::<()> { name: "cy".to_string(), email: "c@example.com".to_string(), extra: () };
+    assert_eq!(HasName::name(&create), "cy");
+    assert_eq!(HasEmail::email(&create), "c@example.com");
+    // Read holds email behind Arc, so only Create and the base implement HasEmail
+    assert_eq!(HasEmail::email(&base), "b@example.com");
+}
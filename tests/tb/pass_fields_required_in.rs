@@ -0,0 +1,23 @@
+use context_variants::variants;
+
+#[variants(
+    Request: requires(name, email).optional(nickname).default(exclude),
+    // Response = Request's required fields + server fields
+    Response: requires(fields_required_in(Request), id, created_at).default(exclude),
+    // References resolve in dependency order and accept exceptions
+    Audit: requires(fields_required_in(Response).except(email)).optional(nickname).default(exclude),
+    suffix = "Dto"
+)]
+struct User {
+    pub id: u64,
+    pub name: String,
+    pub email: String,
+    pub nickname: String,
+    pub created_at: u64,
+}
+
+fn main() {
+    let _request = RequestDto { name: "ann".to_string(), email: "ann@example.com".to_string(), nickname: None };
+    let _response = ResponseDto { id: 1, name: "ann".to_string(), email: "ann@example.com".to_string(), created_at: 0 };
+    let _audit = AuditDto { id: 1, name: "ann".to_string(), nickname: None, created_at: 0 };
+}
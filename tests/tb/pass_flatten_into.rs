@@ -0,0 +1,47 @@
+use context_variants::variants;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Db {
+    pub host: String,
+    pub port: u16,
+}
+
+// Create carries host and port itself; Read keeps the nested struct, flattened by serde
+#[variants(
+    Create: requires(name, db),
+    Read: requires(name, db),
+    from_ref = { Create: true },
+    conversions = { Create: true },
+    try_from = { Create: true },
+    apply_to = { Create: true },
+    constructors = { Create: true },
+    suffix = "Form"
+)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Service {
+    pub name: String,
+    #[serde(flatten)]
+    #[ctx_flatten_into(Create, fields(host: String, port: u16))]
+    pub db: Db,
+}
+
+fn main() {
+    let form = CreateForm::new("api".to_string(), "localhost".to_string(), 5432);
+    let json = serde_json::to_value(&form).unwrap();
+    assert_eq!(json, serde_json::json!({ "name": "api", "host": "localhost", "port": 5432 }));
+
+    let service = Service::from(form);
+    assert_eq!(service.db, Db { host: "localhost".to_string(), port: 5432 });
+    let back = CreateForm::from(&service);
+    assert_eq!((back.host.as_str(), back.port), ("localhost", 5432));
+    let moved = CreateForm::try_from(service.clone()).unwrap();
+    assert_eq!(moved.name, "api");
+
+    let mut target = service.clone();
+    CreateForm { name: "api".to_string(), host: "db.internal".to_string(), port: 6432 }.apply_to(&mut target);
+    assert_eq!(target.db, Db { host: "db.internal".to_string(), port: 6432 });
+
+    let read = ReadForm { name: "api".to_string(), db: service.db.clone() };
+    assert_eq!(serde_json::to_value(&read).unwrap(), json);
+}
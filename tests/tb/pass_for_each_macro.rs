@@ -0,0 +1,45 @@
+use context_variants::variants;
+
+#[variants(
+    Create: requires(name).excludes(id),
+    Read: requires(id, name),
+    Update: requires(id).optional(name),
+    for_each_macro = true,
+    suffix = "Form"
+)]
+struct UserProfile {
+    pub id: u64,
+    pub name: String,
+}
+
+// Exported at the crate root for downstream crates
+#[variants(Create: requires(title), Read: requires(title), for_each_macro = export)]
+struct Post {
+    pub title: String,
+}
+
+mod registry {
+    pub fn type_name<T>() -> &'static str {
+        std::any::type_name::<T>().rsplit("::").next().unwrap()
+    }
+}
+
+fn main() {
+    let mut routes = Vec::new();
+    macro_rules! register {
+        ($context:ident, $variant:ty) => {
+            routes.push((stringify!($context), registry::type_name::<$variant>()));
+        };
+    }
+    for_each_user_profile_context!(register);
+    assert_eq!(routes, [("Create", "CreateForm"), ("Read", "ReadForm"), ("Update", "UpdateForm")]);
+
+    let mut contexts = Vec::new();
+    macro_rules! collect {
+        ($context:ident, $variant:ty) => {
+            contexts.push(stringify!($context));
+        };
+    }
+    for_each_post_context!(collect);
+    assert_eq!(contexts, ["Create", "Read"]);
+}
@@ -0,0 +1,22 @@
+use context_variants::variants;
+
+// forbid() excludes the field and asserts no other rule brings it back
+#[variants(
+    Create: requires(email, password).excludes(id),
+    Read: requires(all_fields().except(password)).forbid(password),
+    Summary: requires(id).forbid(password).default(exclude),
+    Audit: optional(id, email).excludes(password).forbid(password).default(optional),
+    suffix = "Account"
+)]
+struct Account {
+    pub id: u64,
+    pub email: String,
+    pub password: String,
+}
+
+fn main() {
+    let _create = CreateAccount { email: "a@example.com".to_string(), password: "pw".to_string() };
+    let _read = ReadAccount { id: 1, email: "a@example.com".to_string() };
+    let _summary = SummaryAccount { id: 1 };
+    let _audit = AuditAccount { id: None, email: None };
+}
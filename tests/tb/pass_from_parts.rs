@@ -0,0 +1,29 @@
+use context_variants::variants;
+
+#[variants(
+    Create: requires(name, email).optional(nickname).excludes(id, created_at),
+    Update: requires(id).optional(name, email, nickname).excludes(created_at),
+    from_parts = true,
+    suffix = "Form"
+)]
+#[derive(Debug, PartialEq)]
+struct User {
+    pub id: u64,
+    pub name: String,
+    pub email: String,
+    pub nickname: Option<String>,
+    pub created_at: String,
+}
+
+fn main() {
+    let form = CreateForm { name: "ann".to_string(), email: "ann@example.com".to_string(), nickname: None };
+    let user = User::from_create(form, 7, "2024-01-01".to_string());
+    assert_eq!(user, User {
+        id: 7,
+        name: "ann".to_string(),
+        email: "ann@example.com".to_string(),
+        nickname: None,
+        created_at: "2024-01-01".to_string(),
+    });
+    // Update has fields that are optional only in the variant, so it gets no from_update
+}
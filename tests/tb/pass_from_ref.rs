@@ -0,0 +1,58 @@
+use context_variants::variants;
+use std::sync::Arc;
+
+mod crypto {
+    use std::marker::PhantomData;
+
+    pub struct Encrypted<T> {
+        pub ciphertext: Vec<u8>,
+        _plaintext: PhantomData<T>,
+    }
+
+    pub fn encrypt(value: &str) -> Encrypted<String> {
+        Encrypted { ciphertext: value.bytes().rev().collect(), _plaintext: PhantomData }
+    }
+}
+
+// Read projections of a long-lived base, cloning only the included fields
+#[variants(
+    from_ref = { Read: true, Summary: true, Stored: true },
+    Read: requires(id, name, tags).optional(bio).excludes(email),
+    Summary: requires(id, name as Arc<str>).optional(tags).default(exclude),
+    Stored: requires(id, email).default(exclude),
+    suffix = "View"
+)]
+struct User {
+    pub id: u64,
+    pub name: String,
+    #[ctx_shared(Read)]
+    pub tags: Vec<String>,
+    pub bio: Option<String>,
+    #[ctx_encrypt(Stored, with = "crate::crypto")]
+    pub email: String,
+}
+
+fn main() {
+    let user = User {
+        id: 1,
+        name: "ann".to_string(),
+        tags: vec!["admin".to_string()],
+        bio: None,
+        email: "ann@example.com".to_string(),
+    };
+
+    let read = ReadView::from(&user);
+    assert_eq!(read.name, "ann");
+    assert_eq!(read.tags[0], "admin");
+    assert!(read.bio.is_none());
+
+    let summary: SummaryView = (&user).into();
+    assert_eq!(&*summary.name, "ann");
+    assert_eq!(summary.tags.as_deref(), Some(&["admin".to_string()][..]));
+
+    let stored = StoredView::from(&user);
+    assert_eq!(stored.email.ciphertext.len(), 15);
+
+    // The base is still usable
+    assert_eq!(user.id, 1);
+}
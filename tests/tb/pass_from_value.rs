@@ -0,0 +1,50 @@
+use context_variants::variants;
+use serde::Deserialize;
+use serde_json::json;
+
+#[variants(
+    from_value = { Create: true },
+    Create: requires(name, age).optional(nickname).excludes(id),
+    Read: requires(id, name, age, nickname),
+    suffix = "Form"
+)]
+#[derive(Debug, Deserialize)]
+struct User {
+    pub id: u64,
+    #[serde(rename = "username")]
+    pub name: String,
+    pub age: u32,
+    pub nickname: Option<String>,
+}
+
+// serde drops the r# of a raw field name, and so do the errors
+#[variants(from_value = true, Create: requires(r#type))]
+#[derive(Debug, Deserialize)]
+struct Rule {
+    pub r#type: String,
+}
+
+fn main() {
+    let create = CreateForm::from_value(json!({ "username": "ann", "age": 30 })).unwrap();
+    assert_eq!(create.name, "ann");
+    assert!(create.nickname.is_none());
+
+    let missing = CreateForm::from_value(json!({ "age": 30 })).unwrap_err();
+    assert_eq!(missing.context, "Create");
+    assert_eq!(missing.field.as_deref(), Some("username"));
+    assert_eq!(missing.to_string(), "Create: field `username`: missing required field");
+
+    let mistyped = CreateForm::from_value(json!({ "username": "ann", "age": "thirty" })).unwrap_err();
+    assert_eq!(mistyped.field.as_deref(), Some("age"));
+    assert!(mistyped.message.contains("invalid type"));
+
+    let not_object = CreateForm::from_value(json!([1, 2])).unwrap_err();
+    assert_eq!(not_object.field, None);
+    assert_eq!(not_object.to_string(), "Create: expected a JSON object");
+
+    let raw = Create::from_value(json!({})).unwrap_err();
+    assert_eq!(raw.field.as_deref(), Some("type"));
+
+    let error: &dyn std::error::Error = &not_object;
+    let _ = error;
+}
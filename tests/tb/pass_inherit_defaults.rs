@@ -0,0 +1,50 @@
+use context_variants::variants;
+
+#[variants(
+    inherit_defaults = true,
+    Create: requires(name, retries, tags).optional(timeout).excludes(id),
+    Update: requires(id).optional(name, retries, timeout, tags),
+    Read: requires(id, name, retries as String, timeout, tags),
+    suffix = "Job"
+)]
+#[derive(Debug, Clone, PartialEq)]
+struct Job {
+    pub id: u64,
+    pub name: String,
+    pub retries: u32,
+    pub timeout: Option<u64>,
+    pub tags: Vec<String>,
+}
+
+impl Default for Job {
+    fn default() -> Self {
+        Job {
+            id: 0,
+            name: "job".to_string(),
+            retries: 3,
+            timeout: Some(30),
+            tags: vec!["default".to_string()],
+        }
+    }
+}
+
+fn main() {
+    // Required fields carry the base defaults, optional fields start empty
+    let create = CreateJob::default();
+    assert_eq!(create, CreateJob {
+        name: "job".to_string(),
+        retries: 3,
+        timeout: Some(30),
+        tags: vec!["default".to_string()],
+    });
+    let create = CreateJob { name: "nightly".to_string(), ..Default::default() };
+    assert_eq!(create.retries, 3);
+
+    let update = UpdateJob::default();
+    assert_eq!(update, UpdateJob { id: 0, name: None, retries: None, timeout: Some(30), tags: None });
+
+    // Fields with a different type fall back to that type's Default
+    let read = ReadJob::default();
+    assert_eq!(read.retries, String::new());
+    assert_eq!(read.name, "job");
+}
@@ -0,0 +1,32 @@
+use context_variants::variants;
+
+#[variants(
+    Create: requires(name, email).optional(bio).excludes(id),
+    Update: requires(id).optional(name, email, bio),
+    Publish: requires(id, name, bio).excludes(email),
+    optional_base = true,
+    suffix = "Draft"
+)]
+#[derive(Debug, Default)]
+struct Profile {
+    #[ctx_base_required]
+    pub id: u64,
+    pub name: String,
+    pub email: String,
+    pub bio: String,
+}
+
+fn main() {
+    let mut draft = Profile { id: 1, ..Default::default() };
+    // id is never Option in the base, so Update has nothing to check
+    assert!(draft.is_complete_for_update());
+    assert!(!draft.is_complete_for_create());
+
+    draft.name = Some("ann".to_string());
+    draft.email = Some("ann@example.com".to_string());
+    assert!(draft.is_complete_for_create());
+    assert!(!draft.is_complete_for_publish());
+
+    draft.bio = Some("hello".to_string());
+    assert!(draft.is_complete_for_publish());
+}
@@ -0,0 +1,38 @@
+use context_variants::variants;
+use std::collections::{BTreeMap, HashMap};
+
+#[variants(
+    Read: requires(id, tenant_id, name).key(tenant_id, id),
+    Update: requires(id).optional(tenant_id, name).key(id, tenant_id),
+    suffix = "Account"
+)]
+#[derive(Debug, Clone)]
+struct Account {
+    pub id: u64,
+    pub tenant_id: String,
+    pub name: String,
+}
+
+fn main() {
+    let read = ReadAccount { id: 7, tenant_id: "acme".to_string(), name: "Ops".to_string() };
+    let key = read.key();
+    assert_eq!(key, ReadAccountKey { tenant_id: "acme".to_string(), id: 7 });
+
+    let mut by_key = HashMap::new();
+    by_key.insert(key.clone(), read.clone());
+    assert_eq!(by_key[&key].name, "Ops");
+
+    // Ordered by the listed fields, in order
+    let mut ordered = BTreeMap::new();
+    ordered.insert(ReadAccountKey { tenant_id: "b".to_string(), id: 1 }, ());
+    ordered.insert(ReadAccountKey { tenant_id: "a".to_string(), id: 2 }, ());
+    assert_eq!(ordered.keys().next().unwrap().tenant_id, "a");
+
+    let base = Account { id: 7, tenant_id: "acme".to_string(), name: "Ops".to_string() };
+    assert_eq!(ReadAccountKey::from(&base), key);
+
+    // Optional fields keep their Option type in the key
+    let update = UpdateAccount { id: 7, tenant_id: None, name: None };
+    assert_eq!(update.key(), UpdateAccountKey { id: 7, tenant_id: None });
+    assert_eq!(UpdateAccountKey::from(&base).tenant_id.as_deref(), Some("acme"));
+}
@@ -0,0 +1,30 @@
+use context_variants::variants;
+use serde::{Deserialize, Serialize};
+
+#[variants(
+    Create: requires(name).excludes(id),
+    Read: requires(id, name),
+    list = { Read: true },
+    list_cursor = "next_page_token",
+    suffix = "Form"
+)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct User {
+    pub id: u64,
+    pub name: String,
+}
+
+fn main() {
+    let page = ReadFormPage::new(vec![ReadForm { id: 1, name: "Ada".to_string() }], Some("abc".to_string()));
+    assert!(!page.is_last());
+    assert_eq!(
+        serde_json::to_string(&page).unwrap(),
+        r#"{"items":[{"id":1,"name":"Ada"}],"next_page_token":"abc"}"#
+    );
+
+    let last: ReadFormPage = serde_json::from_str(r#"{"items":[],"next_page_token":null}"#).unwrap();
+    assert!(last.is_last());
+    assert_eq!(last.clone(), last);
+
+    let _create = CreateForm { name: "Ada".to_string() };
+}
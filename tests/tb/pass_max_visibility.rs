@@ -0,0 +1,46 @@
+use context_variants::variants;
+
+mod users {
+    use context_variants::variants;
+
+    // The base may be pub(super); the variants are capped at pub(crate) and are still usable here
+    #[variants(
+        Create: requires(name).excludes(id),
+        max_visibility = pub(crate),
+        suffix = "Form"
+    )]
+    pub(super) struct User {
+        pub id: u64,
+        pub name: String,
+    }
+
+    // allow_public acknowledges a public base whose variants stay inside the crate
+    #[variants(
+        Create: requires(name).excludes(id),
+        max_visibility = pub(crate),
+        allow_public = true,
+        suffix = "TeamForm"
+    )]
+    pub struct Team {
+        pub id: u64,
+        pub name: String,
+    }
+}
+
+// config(...) accepts the same keys
+#[variants(
+    Read: requires(id),
+    config(codegen(max_visibility = pub(crate), allow_public)),
+    suffix = "Row"
+)]
+pub struct Log {
+    pub id: u64,
+}
+
+fn main() {
+    let user = users::CreateForm { name: "Ada".to_string() };
+    assert_eq!(user.name, "Ada");
+    let team = users::CreateTeamForm { name: "core".to_string() };
+    assert_eq!(team.name, "core");
+    let _ = ReadRow { id: 1 };
+}
@@ -0,0 +1,30 @@
+use context_variants::variants;
+
+#[variants(
+    Overlay: optional(all_fields()),
+    Full: requires(all_fields()),
+    merge = true,
+    suffix = "Config"
+)]
+#[derive(Debug, Clone, PartialEq)]
+struct Config {
+    pub host: String,
+    pub port: u16,
+    pub verbose: bool,
+}
+
+fn main() {
+    let defaults = OverlayConfig { host: Some("localhost".to_string()), port: Some(8080), verbose: Some(false) };
+    let file = OverlayConfig { host: None, port: Some(9000), verbose: None };
+    let cli = OverlayConfig { host: None, port: None, verbose: Some(true) };
+
+    let merged = defaults.merge(file).merge(cli.clone());
+    assert_eq!(merged, OverlayConfig { host: Some("localhost".to_string()), port: Some(9000), verbose: Some(true) });
+
+    let mut layered = OverlayConfig { host: None, port: None, verbose: None };
+    layered.merge_from(cli);
+    assert_eq!(layered.verbose, Some(true));
+
+    // Full has required fields, so merge = true skips it
+    let _full = FullConfig { host: "h".to_string(), port: 1, verbose: false };
+}
@@ -0,0 +1,40 @@
+#[context_variants::defaults(
+    suffix = "Dto",
+    optional_attrs = [serde(skip_serializing_if = "Option::is_none")]
+)]
+mod dto {
+    use context_variants::variants;
+    use serde::{Deserialize, Serialize};
+
+    #[variants(Create: requires(name).excludes(id), Update: requires(id).optional(name))]
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct User {
+        pub id: u64,
+        pub name: String,
+    }
+
+    // A parameter on the struct overrides the default
+    #[variants(Create: requires(title), suffix = "Input")]
+    pub struct Post {
+        pub title: String,
+    }
+
+    // Nested defaults are applied after the outer ones
+    #[context_variants::defaults(prefix = "Admin")]
+    pub mod admin {
+        use context_variants::variants;
+
+        #[variants(Create: requires(role))]
+        pub struct Grant {
+            pub role: String,
+        }
+    }
+}
+
+fn main() {
+    let update = dto::UpdateDto { id: 1, name: None };
+    assert_eq!(serde_json::to_string(&update).unwrap(), r#"{"id":1}"#);
+    let _create = dto::CreateDto { name: "Ada".to_string() };
+    let _post = dto::CreateInput { title: "Hello".to_string() };
+    let _grant = dto::admin::AdminCreateDto { role: "owner".to_string() };
+}
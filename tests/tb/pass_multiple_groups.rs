@@ -0,0 +1,23 @@
+use context_variants::variants;
+
+// Groups may be split over several parameters and declared after the contexts that use them
+#[variants(
+    Login: requires(auth).default(exclude),
+    groups = (auth(user_id, token)),
+    Profile: requires(user_id).optional(contact, address).default(exclude),
+    groups = (contact(name, email), address(street, city)),
+    suffix = "Form"
+)]
+struct User {
+    pub user_id: String,
+    pub token: String,
+    pub name: String,
+    pub email: String,
+    pub street: String,
+    pub city: String,
+}
+
+fn main() {
+    let _login = LoginForm { user_id: "u1".to_string(), token: "t".to_string() };
+    let _profile = ProfileForm { user_id: "u1".to_string(), name: None, email: None, street: None, city: Some("Oslo".to_string()) };
+}
@@ -0,0 +1,34 @@
+use context_variants::variants;
+use serde::Serialize;
+
+// The hook fills what the form can't carry once the fields are mapped; neither struct is Clone
+fn fill_user(user: &mut User, form: &CreateForm) {
+    user.display_name = format!("{} <{}>", form.name, form.email);
+}
+
+#[variants(
+    Create: requires(name, email).excludes(id, display_name),
+    Read: requires(id, name, email, display_name),
+    conversions = { Create: true, Read: true },
+    suffix = "Form"
+)]
+#[ctx_on_convert(Create = "fill_user")]
+#[derive(Debug, PartialEq, Serialize)]
+struct User {
+    pub id: Option<u64>,
+    pub name: String,
+    pub email: String,
+    #[serde(skip)]
+    pub display_name: String,
+}
+
+fn main() {
+    let form = CreateForm { name: "Ann".to_string(), email: "ann@example.com".to_string() };
+    let user = User::from(form);
+    assert_eq!(user.id, None);
+    assert_eq!(user.display_name, "Ann <ann@example.com>");
+
+    // Contexts without a hook convert as before
+    let read = ReadForm { id: Some(1), name: "Bo".to_string(), email: "bo@example.com".to_string(), display_name: "Bo".to_string() };
+    assert_eq!(User::from(read).display_name, "Bo");
+}
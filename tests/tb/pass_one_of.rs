@@ -0,0 +1,31 @@
+use context_variants::variants;
+
+#[variants(
+    Login: requires(username).optional(password, sso_token).one_of(password, sso_token),
+    Read: requires(username).excludes(password, sso_token),
+    suffix = "Auth"
+)]
+struct Auth {
+    pub username: String,
+    pub password: String,
+    pub sso_token: String,
+}
+
+fn main() {
+    let message = "exactly one of `password`, `sso_token` must be set".to_string();
+
+    let neither = LoginAuth { username: "ann".to_string(), password: None, sso_token: None };
+    assert_eq!(neither.validate(), Err(message.clone()));
+
+    let both = LoginAuth {
+        username: "ann".to_string(),
+        password: Some("pw".to_string()),
+        sso_token: Some("token".to_string()),
+    };
+    assert_eq!(both.validate(), Err(message));
+
+    let password = LoginAuth { username: "ann".to_string(), password: Some("pw".to_string()), sso_token: None };
+    assert_eq!(password.validate(), Ok(()));
+
+    let _read = ReadAuth { username: "ann".to_string() };
+}
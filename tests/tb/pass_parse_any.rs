@@ -0,0 +1,26 @@
+use context_variants::variants;
+use serde::Deserialize;
+
+#[variants(
+    Create: requires(name, email).excludes(id),
+    Update: requires(id).optional(name, email),
+    parse_any = true,
+    suffix = "Form"
+)]
+#[derive(Debug, PartialEq, Deserialize)]
+struct User {
+    pub id: u64,
+    pub name: String,
+    pub email: String,
+}
+
+fn main() {
+    // Contexts are tried in declaration order: Create first, then Update
+    let create = User::parse_any(r#"{"name":"ann","email":"ann@example.com"}"#).unwrap();
+    assert!(matches!(create, UserAny::Create(CreateForm { .. })));
+
+    let update = User::parse_any(r#"{"id":7,"name":"ann"}"#).unwrap();
+    assert_eq!(update, UserAny::Update(UpdateForm { id: 7, name: Some("ann".to_string()), email: None }));
+
+    assert!(User::parse_any(r#"{"name":"ann"}"#).is_err());
+}
@@ -0,0 +1,21 @@
+use context_variants::variants;
+
+// Any attribute path works as a hook; a user attribute macro would see each
+// generated struct with all of its other attributes
+#[variants(
+    post_process = non_exhaustive,
+    Create: requires(name).excludes(id),
+    Read: requires(id, name),
+    suffix = "Tag"
+)]
+#[derive(Debug, Clone)]
+struct Tag {
+    pub id: u64,
+    pub name: String,
+}
+
+fn main() {
+    let create = CreateTag { name: "rust".to_string() };
+    let _read = ReadTag { id: 1, name: create.name.clone() };
+    let _tag = Tag { id: 1, name: create.name };
+}
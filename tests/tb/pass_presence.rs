@@ -0,0 +1,33 @@
+use context_variants::variants;
+
+#[variants(
+    presence = { Update: true },
+    Create: requires(name, email).excludes(id, nickname),
+    Update: requires(id).optional(name, email, nickname),
+    suffix = "Form"
+)]
+struct Profile {
+    pub id: u64,
+    pub name: String,
+    pub email: String,
+    pub nickname: Option<String>,
+}
+
+fn main() {
+    let update = UpdateForm {
+        id: 1,
+        name: Some("Ann".to_string()),
+        email: None,
+        nickname: Some("annie".to_string()),
+    };
+    let presence = update.presence();
+    assert!(presence.contains(UpdateFormPresence::NAME | UpdateFormPresence::NICKNAME));
+    assert!(!presence.contains(UpdateFormPresence::EMAIL));
+    assert_eq!(presence.bits(), 0b101);
+
+    let empty = UpdateForm { id: 1, name: None, email: None, nickname: None };
+    assert!(empty.presence().is_empty());
+    assert_eq!(empty.presence(), UpdateFormPresence::empty());
+
+    let _create = CreateForm { name: "Ann".to_string(), email: "ann@example.com".to_string() };
+}
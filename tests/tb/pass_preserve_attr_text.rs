@@ -0,0 +1,27 @@
+use context_variants::variants;
+use serde::{Deserialize, Serialize};
+
+// serde attributes are copied from when_* exactly as written; preserve_attr_text is
+// deprecated and only warns
+#[variants(
+    Create: requires(name).optional(nickname),
+    preserve_attr_text = [serde],
+    suffix = "Input"
+)]
+#[derive(Debug, Serialize, Deserialize)]
+struct Profile {
+    pub name: String,
+    #[when_optional(serde(default, skip_serializing_if = "Option::is_none", rename = "nick"))]
+    #[when_optional(doc = "Shown next to the name")]
+    pub nickname: String,
+}
+
+fn main() {
+    let input = CreateInput { name: "Ada".to_string(), nickname: None };
+    assert_eq!(serde_json::to_string(&input).unwrap(), r#"{"name":"Ada"}"#);
+
+    let parsed: CreateInput = serde_json::from_str(r#"{"name":"Ada","nick":"A"}"#).unwrap();
+    assert_eq!(parsed.nickname.as_deref(), Some("A"));
+
+    let _base = Profile { name: "Ada".to_string(), nickname: "A".to_string() };
+}
@@ -0,0 +1,43 @@
+#![allow(non_camel_case_types)]
+use context_variants::variants;
+
+// Contexts may share a name with a parameter; keywords are written as raw identifiers
+#[variants(
+    prefix: requires(name).default(exclude),
+    r#default: requires(name, email),
+    r#type: requires(fields_required_in(r#default).except(name)).default(exclude),
+    defaults = { r#default: optional },
+    context_sets = (write(prefix, r#type)),
+    required_wrapper = { write: Box },
+    suffix = "Form"
+)]
+struct User {
+    pub name: String,
+    pub email: String,
+    pub bio: String,
+}
+
+// Hooks name a raw context the same way
+fn fill_note(note: &mut Note, _form: &TypeNote) {
+    note.kind = "raw".to_string();
+}
+
+#[variants(
+    r#Type: requires(text).excludes(kind),
+    conversions = { r#Type: true },
+    suffix = "Note"
+)]
+#[ctx_on_convert(r#Type = "fill_note")]
+struct Note {
+    pub text: String,
+    pub kind: String,
+}
+
+fn main() {
+    let _prefix = prefixForm { name: Box::new("ann".to_string()) };
+    let _default = defaultForm { name: "ann".to_string(), email: "ann@example.com".to_string(), bio: None };
+    let _type = typeForm { email: Box::new("ann@example.com".to_string()) };
+
+    let note = Note::from(TypeNote { text: "hi".to_string() });
+    assert_eq!(note.kind, "raw");
+}
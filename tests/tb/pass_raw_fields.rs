@@ -0,0 +1,30 @@
+use context_variants::variants;
+use serde::Serialize;
+
+// Raw field names stay raw through every selector
+#[variants(Create: requires(r#type).optional(name))]
+struct Thing {
+    r#type: String,
+    name: String,
+}
+
+#[variants(
+    Create: requires(serde("kind")).excludes(r#match),
+    Update: requires(TYPE).optional(r#match),
+    field_match = case_insensitive,
+    suffix = "Form"
+)]
+#[derive(Serialize)]
+struct Rule {
+    #[serde(rename = "kind")]
+    pub r#type: String,
+    pub r#match: String,
+}
+
+fn main() {
+    let thing = Create { r#type: "box".to_string(), name: None };
+    assert_eq!(thing.r#type, "box");
+
+    let _create = CreateForm { r#type: "allow".to_string() };
+    let _update = UpdateForm { r#type: "deny".to_string(), r#match: Some("*".to_string()) };
+}
@@ -0,0 +1,32 @@
+use context_variants::variants;
+
+// A crate would usually wrap inventory::submit! or a linkme slice here
+pub trait Registered {
+    const ENTRY: (&'static str, &'static str, &'static str);
+}
+
+macro_rules! register_dto {
+    ($base:ident, $context:ident, $variant:ident) => {
+        impl Registered for $variant {
+            const ENTRY: (&'static str, &'static str, &'static str) =
+                (stringify!($base), stringify!($context), stringify!($variant));
+        }
+    };
+}
+
+#[variants(Create: requires(name).excludes(id), Read: requires(id, name), registry = register_dto, suffix = "Form")]
+struct User {
+    pub id: u64,
+    pub name: String,
+}
+
+#[variants(Create: requires(title), registry = register_dto, prefix = "Post")]
+struct Post {
+    pub title: String,
+}
+
+fn main() {
+    assert_eq!(CreateForm::ENTRY, ("User", "Create", "CreateForm"));
+    assert_eq!(ReadForm::ENTRY, ("User", "Read", "ReadForm"));
+    assert_eq!(PostCreate::ENTRY, ("Post", "Create", "PostCreate"));
+}
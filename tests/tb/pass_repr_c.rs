@@ -0,0 +1,21 @@
+use context_variants::variants;
+
+// Required and excluded fields keep their declared types; `as` gives an FFI-safe nullable type
+#[variants(
+    Create: requires(x, y, label).excludes(id),
+    Update: requires(id, x, y, label as *const u8),
+    suffix = "Point"
+)]
+#[repr(C)]
+struct Point {
+    pub id: u64,
+    pub x: f64,
+    pub y: f64,
+    pub label: *const u8,
+}
+
+fn main() {
+    let update = UpdatePoint { id: 1, x: 0.5, y: 1.5, label: std::ptr::null() };
+    assert!(update.label.is_null());
+    assert_eq!(std::mem::size_of::<CreatePoint>(), 24);
+}
@@ -0,0 +1,31 @@
+use context_variants::variants;
+use serde::{Serialize, Serializer};
+
+fn upper<S: Serializer>(value: &String, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&value.to_uppercase())
+}
+
+// required_attrs lands on required fields only, in every context
+#[variants(
+    Create: requires(name).optional(city),
+    Update: requires(city).optional(name),
+    required_attrs = [serde(serialize_with = "upper")],
+    suffix = "Form"
+)]
+#[derive(Serialize)]
+struct Place {
+    pub name: String,
+    pub city: String,
+}
+
+fn main() {
+    let create = CreateForm { name: "cafe".to_string(), city: Some("oslo".to_string()) };
+    assert_eq!(serde_json::to_string(&create).unwrap(), r#"{"name":"CAFE","city":"oslo"}"#);
+
+    let update = UpdateForm { name: Some("cafe".to_string()), city: "oslo".to_string() };
+    assert_eq!(serde_json::to_string(&update).unwrap(), r#"{"name":"cafe","city":"OSLO"}"#);
+
+    // The base struct doesn't get it
+    let base = Place { name: "cafe".to_string(), city: "oslo".to_string() };
+    assert_eq!(serde_json::to_string(&base).unwrap(), r#"{"name":"cafe","city":"oslo"}"#);
+}
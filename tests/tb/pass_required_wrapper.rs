@@ -0,0 +1,62 @@
+use context_variants::variants;
+
+/// Marker type recording that a value passed validation
+#[derive(Debug, Clone, PartialEq)]
+pub struct Validated<T>(pub T);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NonEmptyString(pub String);
+
+// Test required_wrapper scoped to a context set, with `as` types left untouched
+#[variants(
+    context_sets = (write(Create, Update)),
+    required_wrapper = { write: Validated },
+    Create: requires(name, email as NonEmptyString).default(exclude),
+    Update: requires(id).optional(name, email).default(exclude),
+    Read: requires(id, name, email),
+    suffix = "Form"
+)]
+#[derive(Debug, Clone)]
+struct User {
+    pub id: u64,
+    pub name: String,
+    pub email: String,
+}
+
+// Test a plain required_wrapper applying to every context
+#[variants(
+    required_wrapper = "Validated",
+    Create: requires(title).optional(body),
+    suffix = "Post"
+)]
+#[derive(Debug, Clone)]
+struct Post {
+    pub title: String,
+    pub body: String,
+}
+
+fn main() {
+    let _create = CreateForm {
+        name: Validated("alice".to_string()),
+        email: NonEmptyString("alice@example.com".to_string()),
+    };
+
+    // Optional fields are never wrapped
+    let _update = UpdateForm {
+        id: Validated(1),
+        name: Some("alice".to_string()),
+        email: None,
+    };
+
+    // Read is outside the write set and keeps plain types
+    let _read = ReadForm {
+        id: 1,
+        name: "alice".to_string(),
+        email: "alice@example.com".to_string(),
+    };
+
+    let _post = CreatePost {
+        title: Validated("Hello".to_string()),
+        body: None,
+    };
+}
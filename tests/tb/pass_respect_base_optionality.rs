@@ -0,0 +1,29 @@
+use context_variants::variants;
+
+// Unmentioned fields follow the base: Option fields stay optional, the rest are required
+#[variants(
+    Create: respect_base_optionality(),
+    // Explicit calls still win over the base shape
+    Patch: optional(name).excludes(id).respect_base_optionality(),
+    suffix = "Form"
+)]
+struct User {
+    pub id: u64,
+    pub name: String,
+    pub nickname: Option<String>,
+    pub bio: Option<String>,
+}
+
+fn main() {
+    let _create = CreateForm {
+        id: 1,
+        name: "ann".to_string(),
+        nickname: None,
+        bio: Some("hi".to_string()),
+    };
+    let _patch = PatchForm {
+        name: None,
+        nickname: None,
+        bio: None,
+    };
+}
@@ -0,0 +1,24 @@
+use context_variants::variants;
+
+#[variants(
+    Create: requires(name, email).excludes(id).role(input),
+    Read: requires(id, name, email).role(output),
+    suffix = "User"
+)]
+#[derive(Debug, Clone, PartialEq)]
+struct User {
+    pub id: u64,
+    pub name: String,
+    pub email: String,
+}
+
+fn main() {
+    // Input: Deserialize and new()
+    let create: CreateUser = serde_json::from_str(r#"{"name":"Ada","email":"ada@example.com"}"#).unwrap();
+    assert_eq!(create, CreateUser::new("Ada".to_string(), "ada@example.com".to_string()));
+
+    // Output: Serialize and From<&User>
+    let user = User { id: 1, name: "Ada".to_string(), email: "ada@example.com".to_string() };
+    let read = ReadUser::from(&user);
+    assert_eq!(serde_json::to_string(&read).unwrap(), r#"{"id":1,"name":"Ada","email":"ada@example.com"}"#);
+}
@@ -0,0 +1,59 @@
+use context_variants::variants;
+
+// Declared once in the user's crate
+pub trait SameContext<Other> {
+    fn into_same(self) -> Other;
+}
+
+#[variants(
+    Read: requires(id, name).default(exclude),
+    Update: requires(id).optional(name).default(exclude),
+    suffix = "User",
+    same_context = SameContext,
+    same_context_families = ["Admin{}User", "{}TenantUser"]
+)]
+struct User {
+    pub id: u64,
+    pub name: String,
+}
+
+// The admin family has the same field subsets under its own names
+#[variants(
+    Read: requires(id, name).default(exclude),
+    Update: requires(id).optional(name).default(exclude),
+    prefix = "Admin",
+    suffix = "User",
+    same_context = SameContext,
+    same_context_families = ["Admin{}User", "{}User"]
+)]
+struct AdminUser {
+    pub id: u64,
+    pub name: String,
+    pub permissions: Vec<String>,
+}
+
+#[variants(
+    Read: requires(id, name).default(exclude),
+    Update: requires(id).optional(name).default(exclude),
+    suffix = "TenantUser"
+)]
+struct TenantUser {
+    pub id: u64,
+    pub name: String,
+    pub tenant: u64,
+}
+
+fn main() {
+    let read = ReadUser { id: 1, name: "ann".to_string() };
+    let admin: AdminReadUser = read.into_same();
+    assert_eq!(admin.name, "ann");
+
+    // From is implemented in both directions once each family lists the other
+    let back = ReadUser::from(admin);
+    let tenant: ReadTenantUser = back.into();
+    assert_eq!(tenant.id, 1);
+
+    let update = AdminUpdateUser { id: 2, name: None };
+    let update: UpdateUser = update.into();
+    assert_eq!(update.id, 2);
+}
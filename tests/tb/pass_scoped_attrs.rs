@@ -0,0 +1,35 @@
+use context_variants::variants;
+use serde::{Deserialize, Serialize};
+
+// Scoped attrs apply only in the listed contexts, on top of the global ones
+#[variants(
+    Update: requires(id).optional(name, email),
+    Read: requires(id).optional(name, email),
+    Create: requires(name).optional(email).excludes(id),
+    context_sets = (writes(Update, Create)),
+    optional_attrs = [serde(default)],
+    optional_attrs(writes) = [serde(skip_serializing_if = "Option::is_none")],
+    suffix = "User"
+)]
+#[derive(Debug, Serialize, Deserialize)]
+struct User {
+    pub id: u64,
+    pub name: String,
+    pub email: String,
+}
+
+fn main() {
+    let update = UpdateUser { id: 1, name: None, email: Some("a@example.com".to_string()) };
+    assert_eq!(serde_json::to_string(&update).unwrap(), r#"{"id":1,"email":"a@example.com"}"#);
+
+    // Read always serializes nulls
+    let read = ReadUser { id: 1, name: None, email: None };
+    assert_eq!(serde_json::to_string(&read).unwrap(), r#"{"id":1,"name":null,"email":null}"#);
+
+    // The global serde(default) still applies everywhere
+    let read: ReadUser = serde_json::from_str(r#"{"id":2}"#).unwrap();
+    assert_eq!(read.name, None);
+    let create: CreateUser = serde_json::from_str(r#"{"name":"alice"}"#).unwrap();
+    assert_eq!(create.email, None);
+    assert_eq!(serde_json::to_string(&create).unwrap(), r#"{"name":"alice"}"#);
+}
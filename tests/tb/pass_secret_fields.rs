@@ -0,0 +1,89 @@
+use context_variants::variants;
+
+// Minimal stand-in for the `secrecy` crate
+pub struct Secret<T>(T);
+
+impl<T> Secret<T> {
+    pub fn new(value: T) -> Self {
+        Secret(value)
+    }
+}
+
+pub trait ExposeSecret<T> {
+    fn expose_secret(&self) -> &T;
+}
+
+impl<T> ExposeSecret<T> for Secret<T> {
+    fn expose_secret(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> std::fmt::Debug for Secret<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Secret(..)")
+    }
+}
+
+// Secret fields are skipped by all_fields() and defaults; write contexts name them explicitly
+#[variants(
+    Register: requires(username, password).optional(api_key).default(exclude),
+    Read: requires(all_fields()),
+    Summary: requires(username).default(optional),
+    required_wrapper = Box,
+    suffix = "Credentials"
+)]
+#[derive(Debug)]
+struct Credentials {
+    pub id: u64,
+    pub username: String,
+    pub password: Secret<String>,
+    #[ctx_secret]
+    pub api_key: String,
+}
+
+// Raw field names print as derive(Debug) prints them
+#[variants(Read: requires(r#type, token))]
+#[derive(Debug)]
+struct Grant {
+    pub r#type: String,
+    #[ctx_secret]
+    pub token: String,
+}
+
+fn main() {
+    let register = RegisterCredentials {
+        username: Box::new("alice".to_string()),
+        password: Secret::new("hunter2".to_string()),
+        api_key: Some("key-123".to_string()),
+    };
+    // Secret types are kept as declared, so ExposeSecret still works
+    assert_eq!(register.password.expose_secret(), "hunter2");
+
+    let debug = format!("{:?}", register);
+    assert!(!debug.contains("key-123"));
+    assert!(debug.contains("api_key: [REDACTED]"));
+
+    // Read only has the non-secret fields
+    let read = ReadCredentials {
+        id: Box::new(1),
+        username: Box::new("alice".to_string()),
+    };
+    assert_eq!(format!("{:?}", read), r#"ReadCredentials { id: 1, username: "alice" }"#);
+
+    let _summary = SummaryCredentials {
+        id: None,
+        username: Box::new("alice".to_string()),
+    };
+
+    let base = Credentials {
+        id: 1,
+        username: "alice".to_string(),
+        password: Secret::new("hunter2".to_string()),
+        api_key: "key-123".to_string(),
+    };
+    assert!(!format!("{:?}", base).contains("key-123"));
+
+    let grant = Read { r#type: "bearer".to_string(), token: "t0k3n".to_string() };
+    assert_eq!(format!("{:?}", grant), r#"Read { type: "bearer", token: [REDACTED] }"#);
+}
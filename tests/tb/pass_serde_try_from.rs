@@ -0,0 +1,35 @@
+use context_variants::variants;
+use serde::Deserialize;
+
+// Deserializing the base goes through the tagged enum and each context's checks
+#[variants(
+    Create: requires(name).optional(email, phone).excludes(id).at_least_one_of(email, phone),
+    Read: requires(id, name).optional(email, phone),
+    enum_wrapper = "UserPayload",
+    serde_try_from = true,
+    conversions = true,
+    suffix = "Form"
+)]
+#[derive(Debug, PartialEq, Deserialize)]
+struct User {
+    pub id: Option<u64>,
+    pub name: String,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+}
+
+fn main() {
+    let user: User = serde_json::from_str(r#"{"context":"Create","name":"Ann","email":"ann@example.com"}"#).unwrap();
+    assert_eq!(user, User { id: None, name: "Ann".to_string(), email: Some("ann@example.com".to_string()), phone: None });
+
+    let user: User = serde_json::from_str(r#"{"context":"Read","id":7,"name":"Bo"}"#).unwrap();
+    assert_eq!(user.id, Some(7));
+
+    // Create's at_least_one_of check runs while deserializing
+    let error = serde_json::from_str::<User>(r#"{"context":"Create","name":"Cy"}"#).unwrap_err();
+    assert!(error.to_string().contains("email"), "{}", error);
+
+    // The variants still deserialize on their own
+    let form: CreateForm = serde_json::from_str(r#"{"name":"Di","phone":"555"}"#).unwrap();
+    assert_eq!(form.phone.as_deref(), Some("555"));
+}
@@ -0,0 +1,30 @@
+use context_variants::variants;
+use serde::Serialize;
+
+#[variants(
+    Create: requires(amount, currency, account).optional(memo).excludes(id),
+    serialize_required = { Create: true },
+    suffix = "Payment"
+)]
+#[derive(Debug, Serialize)]
+struct Transfer<T> {
+    pub id: u64,
+    pub amount: T,
+    #[serde(rename = "ccy")]
+    pub currency: String,
+    pub account: String,
+    pub memo: Option<String>,
+}
+
+fn main() {
+    let create = CreatePayment {
+        amount: 125u32,
+        currency: "EUR".to_string(),
+        account: "acct-1".to_string(),
+        memo: Some("rent".to_string()),
+    };
+    let mut bytes = Vec::new();
+    create.serialize_required(&mut serde_json::Serializer::new(&mut bytes)).unwrap();
+    // The optional memo is left out, so the digest is stable across edits to it
+    assert_eq!(String::from_utf8(bytes).unwrap(), r#"{"amount":125,"ccy":"EUR","account":"acct-1"}"#);
+}
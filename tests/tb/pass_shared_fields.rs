@@ -0,0 +1,95 @@
+use context_variants::variants;
+use std::sync::Arc;
+
+// Test #[ctx_shared] on selected contexts and share_heavy_fields per context set
+#[variants(
+    context_sets = (read(Read, Summary)),
+    share_heavy_fields = { read: true },
+    Create: requires(title, body, tags).default(exclude),
+    Read: requires(id, title, body, tags, attachment).default(optional),
+    Summary: requires(id, title).default(exclude),
+    suffix = "Article"
+)]
+#[derive(Debug, Clone)]
+struct Article {
+    pub id: u64,
+    pub title: String,
+    pub body: String,
+    pub tags: Vec<String>,
+    #[ctx_shared(Read)]
+    pub attachment: Option<Blob>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Blob(pub Vec<u8>);
+
+// Test #[ctx_shared] without arguments applying to every context
+#[variants(
+    Read: requires(id, payload),
+    suffix = "Event"
+)]
+#[derive(Debug, Clone)]
+struct Event {
+    pub id: u64,
+    #[ctx_shared]
+    pub payload: Blob,
+}
+
+// Test #[ctx_shared] on a type that is not a collection, shared from the base by from_ref
+#[variants(
+    Read: requires(id, thumbnail),
+    share_heavy_fields = true,
+    from_ref = true,
+    suffix = "Image"
+)]
+#[derive(Debug, Clone)]
+struct Image {
+    pub id: u64,
+    #[ctx_shared]
+    pub thumbnail: [u8; 64],
+}
+
+fn main() {
+    // Write contexts keep owned values
+    let _create = CreateArticle {
+        title: "Hello".to_string(),
+        body: "World".to_string(),
+        tags: vec!["intro".to_string()],
+    };
+
+    // Read contexts hold heavy fields behind Arc, cloning cheaply
+    let read = ReadArticle {
+        id: 1,
+        title: Arc::new("Hello".to_string()),
+        body: Arc::new("World".to_string()),
+        tags: Arc::new(vec!["intro".to_string()]),
+        attachment: Some(Arc::new(Blob(vec![0; 1024]))),
+    };
+    let copy = read.clone();
+    assert!(Arc::ptr_eq(&read.body, &copy.body));
+
+    let _summary = SummaryArticle {
+        id: 1,
+        title: Arc::new("Hello".to_string()),
+    };
+
+    // The base struct is unaffected
+    let _article = Article {
+        id: 1,
+        title: "Hello".to_string(),
+        body: "World".to_string(),
+        tags: Vec::new(),
+        attachment: None,
+    };
+
+    let _event = ReadEvent {
+        id: 1,
+        payload: Arc::new(Blob(Vec::new())),
+    };
+
+    // share_heavy_fields leaves the id alone; the array is shared because it is marked
+    let read = ReadImage::from(&Image { id: 1, thumbnail: [7; 64] });
+    let id: u64 = read.id;
+    assert_eq!((id, read.thumbnail[0]), (1, 7));
+    assert!(Arc::ptr_eq(&read.thumbnail, &read.clone().thumbnail));
+}
@@ -0,0 +1,30 @@
+use context_variants::variants;
+
+// base_name renames the partial base; the struct as declared stays next to it
+#[variants(
+    Create: requires(name, email).excludes(id),
+    Read: requires(id, name, email),
+    optional_base = true,
+    base_name = "UserPartial",
+    suffix = "Form"
+)]
+#[derive(Debug, Clone, PartialEq)]
+struct User {
+    #[ctx_base_required]
+    pub id: u64,
+    pub name: String,
+    pub email: String,
+}
+
+fn main() {
+    let user = User { id: 1, name: "Ann".to_string(), email: "ann@example.com".to_string() };
+    let partial = UserPartial::from(user.clone());
+    assert_eq!(partial, UserPartial { id: 1, name: Some("Ann".to_string()), email: Some("ann@example.com".to_string()) });
+    assert_eq!(User::try_from(partial), Ok(user));
+
+    let error = User::try_from(UserPartial { id: 2, name: None, email: None }).unwrap_err();
+    assert_eq!(error.fields, ["name", "email"]);
+    assert_eq!(error.to_string(), "User: missing required fields `name`, `email`");
+
+    let _create = CreateForm { name: "Bo".to_string(), email: "bo@example.com".to_string() };
+}
@@ -0,0 +1,24 @@
+use context_variants::variants;
+
+#[variants(
+    Create: requires(name).excludes(id).tag(http = "POST /users", auth = "admin", rate_limit = 10u32),
+    Read: requires(id, name).tag(http = "GET /users/{id}", cached = true, r#type = "query"),
+    suffix = "Form"
+)]
+struct User {
+    pub id: u64,
+    pub name: String,
+}
+
+fn main() {
+    assert_eq!(CreateForm::TAG_HTTP, "POST /users");
+    assert_eq!(CreateForm::TAG_AUTH, "admin");
+    let limit: u32 = CreateForm::TAG_RATE_LIMIT;
+    assert_eq!(limit, 10);
+    assert_eq!(ReadForm::TAG_HTTP, "GET /users/{id}");
+    const { assert!(ReadForm::TAG_CACHED) };
+    // Raw keys drop the r# prefix
+    assert_eq!(ReadForm::TAG_TYPE, "query");
+
+    let _create = CreateForm { name: "ann".to_string() };
+}
@@ -0,0 +1,27 @@
+use context_variants::variants;
+use serde::{Deserialize, Serialize};
+
+// Attribute arguments are passed through as token trees, so they don't have
+// to be valid Rust expressions. `cfg_attr(any(), ...)` stands in for a derive
+// with its own argument syntax.
+#[variants(
+    Create: requires(name).optional(nickname),
+    optional_attrs = [serde(default), cfg_attr(any(), lint(a => b; c))],
+    required_attrs = [cfg_attr(any(), validate { length >= 1 })],
+    suffix = "Input"
+)]
+#[derive(Debug, Serialize, Deserialize)]
+struct Profile {
+    #[when_required(cfg_attr(any(), validate(length >= 1, message: "name")))]
+    pub name: String,
+    #[when_optional(serde(skip_serializing_if = "Option::is_none"))]
+    pub nickname: String,
+}
+
+fn main() {
+    let input: CreateInput = serde_json::from_str(r#"{"name":"Ada"}"#).unwrap();
+    assert_eq!(input.nickname, None);
+    assert_eq!(serde_json::to_string(&input).unwrap(), r#"{"name":"Ada"}"#);
+
+    let _base = Profile { name: "Ada".to_string(), nickname: "A".to_string() };
+}
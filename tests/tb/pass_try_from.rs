@@ -0,0 +1,40 @@
+use context_variants::variants;
+
+#[variants(
+    Create: requires(name, email).optional(nickname).excludes(id),
+    Update: requires(id).optional(name, email, nickname),
+    optional_base = true,
+    try_from = true,
+    suffix = "Request"
+)]
+#[derive(Debug, Clone, PartialEq)]
+struct User {
+    #[ctx_base_required]
+    pub id: u64,
+    pub name: String,
+    pub email: String,
+    pub nickname: String,
+}
+
+fn main() {
+    let draft = User { id: 7, name: Some("Ada".to_string()), email: None, nickname: None };
+    let err = CreateRequest::try_from(draft.clone()).unwrap_err();
+    assert_eq!(err, UserMissingFields { variant: "CreateRequest", fields: vec!["email"] });
+    assert_eq!(err.to_string(), "CreateRequest: missing required field `email`");
+
+    let empty = User { id: 7, name: None, email: None, nickname: None };
+    let err = CreateRequest::try_from(empty).unwrap_err();
+    assert_eq!(err.fields, ["name", "email"]);
+    assert_eq!(err.to_string(), "CreateRequest: missing required fields `name`, `email`");
+
+    let complete = User { email: Some("ada@example.com".to_string()), ..draft.clone() };
+    let create = CreateRequest::try_from(complete).unwrap();
+    assert_eq!(create.name, "Ada");
+    assert_eq!(create.email, "ada@example.com");
+    assert_eq!(create.nickname, None);
+
+    // Nothing Update requires can be None, so it always converts
+    let update = UpdateRequest::try_from(draft).unwrap();
+    assert_eq!(update.id, 7);
+    assert_eq!(update.email, None);
+}
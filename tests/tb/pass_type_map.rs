@@ -0,0 +1,68 @@
+use context_variants::variants;
+
+// Minimal stand-in for chrono's date-time types
+mod chrono {
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct DateTime<Tz>(pub i64, pub Tz);
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Utc;
+}
+
+use chrono::{DateTime, Utc};
+
+// Timestamps are strings in write contexts and native types elsewhere
+#[variants(
+    context_sets = (write(Create, Update)),
+    type_map = { chrono::DateTime<Utc> => String in [write] },
+    Create: requires(title, published_at).excludes(id, updated_at),
+    Update: requires(id).optional(title, published_at, updated_at),
+    Read: requires(id, title, published_at, updated_at),
+    suffix = "Post"
+)]
+#[derive(Debug, Clone)]
+struct Post {
+    pub id: u64,
+    pub title: String,
+    pub published_at: DateTime<Utc>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+// Rules without `in [...]` apply everywhere; an explicit `as` type takes precedence
+#[variants(
+    type_map = { u32 => u64, bool => String in [Read] },
+    Read: requires(count, flag, other as u8),
+    suffix = "Stats"
+)]
+struct Stats {
+    pub count: u32,
+    pub flag: bool,
+    pub other: u32,
+}
+
+fn main() {
+    let _create = CreatePost {
+        title: "Hello".to_string(),
+        published_at: "2024-01-01T00:00:00Z".to_string(),
+    };
+
+    let _update = UpdatePost {
+        id: 1,
+        title: None,
+        published_at: Some("2024-01-01T00:00:00Z".to_string()),
+        updated_at: None::<String>,
+    };
+
+    let _read = ReadPost {
+        id: 1,
+        title: "Hello".to_string(),
+        published_at: DateTime(0, Utc),
+        updated_at: Some(DateTime(0, Utc)),
+    };
+
+    let _stats = ReadStats {
+        count: 1u64,
+        flag: "yes".to_string(),
+        other: 1u8,
+    };
+}
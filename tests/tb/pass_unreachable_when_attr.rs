@@ -0,0 +1,25 @@
+// Without warn_dead_attrs, attributes that never apply stay silent even under deny(warnings)
+#![deny(warnings)]
+
+use context_variants::variants;
+use serde::{Deserialize, Serialize};
+
+#[variants(
+    Login: requires(email, password),
+    Profile: requires(email).excludes(password),
+    suffix = "Form"
+)]
+#[derive(Serialize, Deserialize)]
+struct ConditionalForm {
+    pub email: String,
+    #[when_base(serde(rename = "base_password"))]
+    #[when_optional(serde(default))]
+    #[when_required(serde(rename = "pwd"))]
+    pub password: String,
+}
+
+fn main() {
+    let login = LoginForm { email: "ann@example.com".to_string(), password: "hunter2".to_string() };
+    assert_eq!(serde_json::to_value(&login).unwrap()["pwd"], "hunter2");
+    let _profile = ProfileForm { email: "ann@example.com".to_string() };
+}
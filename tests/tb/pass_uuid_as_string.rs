@@ -0,0 +1,40 @@
+use context_variants::variants;
+
+// Minimal stand-in for uuid::Uuid
+mod uuid {
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct Uuid(pub u128);
+}
+
+// Uuid fields are plain strings in the write contexts only
+#[variants(
+    uuid_as_string = [Create, Update],
+    Create: requires(owner_id, name).excludes(id),
+    Update: requires(id).optional(owner_id, name),
+    Read: requires(id, owner_id, name),
+    suffix = "Project"
+)]
+struct Project {
+    pub id: uuid::Uuid,
+    pub owner_id: Option<uuid::Uuid>,
+    pub name: String,
+}
+
+fn main() {
+    let _create = CreateProject {
+        owner_id: Some("67e55044-10b1-426f-9247-bb680e5fe0c8".to_string()),
+        name: "Apollo".to_string(),
+    };
+
+    let _update = UpdateProject {
+        id: "67e55044-10b1-426f-9247-bb680e5fe0c8".to_string(),
+        owner_id: None,
+        name: None,
+    };
+
+    let _read = ReadProject {
+        id: uuid::Uuid(1),
+        owner_id: Some(uuid::Uuid(2)),
+        name: "Apollo".to_string(),
+    };
+}
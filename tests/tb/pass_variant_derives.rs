@@ -0,0 +1,24 @@
+use context_variants::variants;
+use serde::Serialize;
+use std::collections::HashSet;
+
+#[variants(
+    Create: requires(name).excludes(id).derive(Default),
+    Read: requires(id, name).derive(PartialEq, Eq, Hash),
+    suffix = "Form"
+)]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct User {
+    pub id: u64,
+    pub name: String,
+}
+
+fn main() {
+    let create = CreateForm::default();
+    assert_eq!(create.name, "");
+
+    let mut seen = HashSet::new();
+    seen.insert(ReadForm { id: 1, name: "ann".to_string() });
+    assert!(seen.contains(&ReadForm { id: 1, name: "ann".to_string() }));
+}
@@ -0,0 +1,28 @@
+use context_variants::variants;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[variants(
+    Create: requires(name).excludes(id)
+        .verbatim_field(#[serde(flatten)] pub extra: HashMap<String, serde_json::Value>),
+    Read: requires(id, name).verbatim_field("#[serde(default)] pub etag: Option<String>"),
+    from_ref = { Read: true },
+    suffix = "Form"
+)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct User {
+    pub id: u64,
+    pub name: String,
+}
+
+fn main() {
+    // Unknown keys land in the catch-all map
+    let create: CreateForm = serde_json::from_str(r#"{"name":"Ada","color":"blue"}"#).unwrap();
+    assert_eq!(create.name, "Ada");
+    assert_eq!(create.extra["color"], "blue");
+
+    // Conversions fill verbatim fields with Default
+    let base = User { id: 1, name: "Ada".to_string() };
+    let read = ReadForm::from(&base);
+    assert_eq!(read.etag, None);
+}
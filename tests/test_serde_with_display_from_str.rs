@@ -1,7 +1,6 @@
 use context_variants::variants;
 use serde::{Deserialize, Serialize};
 use serde_with::{serde_as, DisplayFromStr, skip_serializing_none};
-use serde_json;
 
 #[variants(
     suffix = "Request",